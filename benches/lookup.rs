@@ -0,0 +1,48 @@
+//! Measures flag lookup cost when parsing against a large number of declared flags.
+//!
+//! Run with `cargo bench --bench lookup`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use yafp::Parser;
+
+const FLAGS: usize = 200;
+const ITERATIONS: u32 = 2_000;
+
+fn main() {
+    let names: Vec<String> = (0..FLAGS)
+        .map(|i| format!("some-rather-long-flag-name-number-{}", i))
+        .collect();
+    let mut args: Vec<String> = vec!["bench".to_string()];
+    for name in names.iter().step_by(2) {
+        args.push(format!("-{}", name));
+        args.push("value".to_string());
+    }
+
+    let mut declare = Duration::ZERO;
+    let mut finalize = Duration::ZERO;
+    let mut get_value = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let mut parser = Parser::from_iter(&args);
+        for name in &names {
+            parser.optional_flag(name, "this is a flag used for benchmarking");
+        }
+        declare += start.elapsed();
+
+        let start = Instant::now();
+        black_box(parser.finalize().unwrap());
+        finalize += start.elapsed();
+
+        let start = Instant::now();
+        for name in &names {
+            black_box(parser.get_value::<String>(name));
+        }
+        get_value += start.elapsed();
+    }
+
+    println!("{} flags, {} iterations", FLAGS, ITERATIONS);
+    println!("declare:   {:?} per parse", declare / ITERATIONS);
+    println!("finalize:  {:?} per parse", finalize / ITERATIONS);
+    println!("get_value: {:?} per parse", get_value / ITERATIONS);
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use yafp::fuzzing::{ArgSequence, ParserConfig};
+
+fuzz_target!(|input: (ParserConfig, ArgSequence)| {
+    let (config, args) = input;
+    let mut parser = config.build(&args);
+    // Invalid definitions panic by design in debug builds.
+    if parser.validate_definition().is_err() {
+        return;
+    }
+    if parser.finalize().is_ok() {
+        let _ = parser.help();
+    }
+});
@@ -0,0 +1,153 @@
+//! Defines the sources of argument tokens consumed by the parser.
+use std::borrow::Cow;
+
+use crate::errors::{Error, Result};
+
+/// A source of argument tokens, consumed lazily by [`crate::Parser::finalize`].
+///
+/// It is implemented for any iterator of string-like items, so [`std::env::args`], a
+/// [`Vec`] or a borrowed slice can be used directly. Implement it for custom sources which should
+/// produce tokens on demand.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+/// use yafp::Parser;
+/// use yafp::args::ArgSource;
+/// use yafp::errors::Error;
+///
+/// /// Produces the command name followed by a number of numbered files.
+/// struct Files {
+///     n: usize,
+///     total: usize,
+/// }
+///
+/// impl ArgSource<'static> for Files {
+///     fn next_arg(&mut self) -> Option<Cow<'static, str>> {
+///         self.n += 1;
+///         match self.n {
+///             1 => Some(Cow::Borrowed("cat")),
+///             n if n <= self.total + 1 => Some(Cow::Owned(format!("file{}.txt", n - 1))),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let mut parser = Parser::from_source(Files { n: 0, total: 2 });
+/// let remaining = parser.finalize()?;
+/// assert_eq!(vec!["file1.txt", "file2.txt"], remaining);
+/// # Ok::<(), Error>(())
+/// ```
+pub trait ArgSource<'a> {
+    /// Returns the next argument token or `None` once the source is exhausted.
+    fn next_arg(&mut self) -> Option<Cow<'a, str>>;
+}
+
+impl<'a, I, S> ArgSource<'a> for I
+where
+    I: Iterator<Item = S>,
+    S: Into<Cow<'a, str>>,
+{
+    fn next_arg(&mut self) -> Option<Cow<'a, str>> {
+        self.next().map(Into::into)
+    }
+}
+
+/// Limits on the args consumed by [`crate::Parser::finalize`], guarding parsers given untrusted
+/// input against unbounded work. No limit is set by default.
+///
+/// # Examples
+///
+/// ```
+/// use yafp::Parser;
+/// use yafp::args::Limits;
+/// use yafp::errors::Error;
+///
+/// let mut parser = Parser::from_iter(["echo", "a", "b", "c"]);
+/// parser.set_limits(Limits::new().max_args(2).max_arg_len(4096));
+///
+/// assert_eq!(parser.finalize(), Err(Error::TooManyArgs(2)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    max_args: Option<usize>,
+    max_arg_len: Option<usize>,
+    max_glob_matches: Option<usize>,
+}
+
+impl Limits {
+    /// Creates limits accepting any input.
+    pub const fn new() -> Self {
+        Self {
+            max_args: None,
+            max_arg_len: None,
+            max_glob_matches: None,
+        }
+    }
+
+    /// Sets the most args accepted after the command name, counting flags and their values.
+    pub const fn max_args(mut self, max: usize) -> Self {
+        self.max_args = Some(max);
+        self
+    }
+
+    /// Sets the most bytes accepted in a single arg.
+    pub const fn max_arg_len(mut self, max: usize) -> Self {
+        self.max_arg_len = Some(max);
+        self
+    }
+
+    /// Sets the most paths a single pattern expands to with [`crate::Parser::expand_globs`],
+    /// which stops reading directories once the limit is passed.
+    pub const fn max_glob_matches(mut self, max: usize) -> Self {
+        self.max_glob_matches = Some(max);
+        self
+    }
+
+    /// Checks the arg at a position, counted from one after the command name.
+    pub(crate) fn check_arg(&self, position: usize, arg: &str) -> Result<()> {
+        if let Some(max) = self.max_args.filter(|max| position > *max) {
+            return Err(Error::TooManyArgs(max));
+        }
+        if let Some(max) = self.max_arg_len.filter(|max| arg.len() > *max) {
+            return Err(Error::ArgTooLong(max));
+        }
+        Ok(())
+    }
+
+    /// Returns the most paths a single pattern expands to, if limited.
+    pub(crate) const fn glob_matches(&self) -> Option<usize> {
+        self.max_glob_matches
+    }
+
+    /// Checks the number of paths a glob pattern expands to.
+    pub(crate) fn check_glob(&self, pattern: &str, matches: usize) -> Result<()> {
+        match self.max_glob_matches.filter(|max| matches > *max) {
+            Some(max) => Err(Error::TooManyMatches(pattern.to_string(), max)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn limits() {
+        let limits = Limits::new().max_args(2).max_arg_len(3);
+        assert_eq!(limits.check_arg(2, "abc"), Ok(()));
+        assert_eq!(limits.check_arg(3, "a"), Err(Error::TooManyArgs(2)));
+        assert_eq!(limits.check_arg(1, "abcd"), Err(Error::ArgTooLong(3)));
+        assert_eq!(limits.check_glob("*", 100), Ok(()));
+        assert_eq!(Limits::new().check_arg(usize::MAX, "a"), Ok(()));
+
+        let limits = Limits::new().max_glob_matches(1);
+        assert_eq!(
+            limits.check_glob("*.rs", 2),
+            Err(Error::TooManyMatches("*.rs".to_string(), 1))
+        );
+    }
+}
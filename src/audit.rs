@@ -0,0 +1,138 @@
+//! Summarizes parsed invocations so tool usage can be audited, see
+//! [`crate::Parser::set_audit_fn`].
+use crate::{Parser, Source, LIST_SEPARATOR};
+
+/// A summary of a parsed command line, with the values of sensitive flags redacted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    /// The command, prefixed with its parent commands for a subcommand, such as `tool build`.
+    pub command: String,
+    /// The flags set on the command line, from the environment or from the configuration, in
+    /// declaration order. Flags left to their default are not listed.
+    pub flags: Vec<SetFlag>,
+    /// The subcommand given, if any.
+    pub subcommand: Option<Box<Invocation>>,
+}
+
+/// A flag listed in an [`Invocation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetFlag {
+    /// The name of the flag.
+    pub name: String,
+    /// The value, with multiple values joined by `,` and redacted if the flag is sensitive.
+    pub value: String,
+    /// Where the value came from.
+    pub source: Source,
+}
+
+impl Parser<'_> {
+    /// Accepts a closure that receives a summary of the invocation after each successful
+    /// [`crate::Parser::finalize`], such as to record which internal tools and flags are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let log = Rc::new(RefCell::new(Vec::new()));
+    /// let mut parser = Parser::from_iter(["deploy", "-token", "abc", "-env", "prod"]);
+    /// parser.optional_flag("token", "this is used to authenticate").sensitive();
+    /// parser.optional_flag("env", "this is used to set the environment");
+    /// parser.optional_flag("region", "this is used to set the region").default_value("eu");
+    /// parser.set_audit_fn({
+    ///     let log = Rc::clone(&log);
+    ///     move |invocation| {
+    ///         let flags: Vec<String> = invocation
+    ///             .flags
+    ///             .iter()
+    ///             .map(|flag| format!("{}={}", flag.name, flag.value))
+    ///             .collect();
+    ///         log.borrow_mut().push(format!("{} {}", invocation.command, flags.join(" ")));
+    ///     }
+    /// });
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(*log.borrow(), ["deploy token=******** env=prod"]);
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn set_audit_fn(&mut self, f: impl Fn(&Invocation) + 'static) {
+        self.audit_fn = Some(Box::new(f));
+    }
+
+    /// Returns a summary of the invocation, as passed to the closure set with
+    /// [`crate::Parser::set_audit_fn`]. This should be called after [`crate::Parser::finalize`].
+    pub fn invocation(&self) -> Invocation {
+        let flags = self
+            .flags
+            .iter()
+            .filter_map(|(name, entry)| {
+                let source = entry.source.filter(|source| *source != Source::Default)?;
+                Some(SetFlag {
+                    name: name.to_string(),
+                    value: entry.display_value()?.replace(LIST_SEPARATOR, ","),
+                    source,
+                })
+            })
+            .collect();
+        Invocation {
+            command: self.command.to_string(),
+            flags,
+            subcommand: self.subcommand().map(|(_, sub)| Box::new(sub.invocation())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn invocation() {
+        let mut parser = Parser::from_iter(["tool", "-verbose", "build", "-target", "x86"]);
+        parser.set_env([("TOOL_JOBS".to_string(), "4".to_string())].into());
+        parser.env_prefix("TOOL");
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.optional_flag("jobs", "this is used to set the number of jobs");
+        parser.bool_flag("quiet", "this is used to get less output");
+        parser
+            .add_subcommand("build", "compile the project")
+            .optional_flag("target", "this is used to set the target");
+
+        let invocations = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        parser.set_audit_fn({
+            let invocations = std::rc::Rc::clone(&invocations);
+            move |invocation| invocations.borrow_mut().push(invocation.clone())
+        });
+        parser.finalize().unwrap();
+
+        let set = |name: &str, value: &str, source| SetFlag {
+            name: name.to_string(),
+            value: value.to_string(),
+            source,
+        };
+        assert_eq!(
+            *invocations.borrow(),
+            [Invocation {
+                command: "tool".to_string(),
+                flags: vec![
+                    set("verbose", "true", Source::Cli),
+                    set("jobs", "4", Source::Env)
+                ],
+                subcommand: Some(Box::new(Invocation {
+                    command: "tool build".to_string(),
+                    flags: vec![set("target", "x86", Source::Cli)],
+                    subcommand: None,
+                })),
+            }]
+        );
+
+        // A failed finalize is not reported.
+        let mut parser = Parser::from_iter(["tool", "-unknown"]);
+        parser.set_audit_fn(|_| panic!("reported a failed invocation"));
+        assert!(parser.finalize().is_err());
+    }
+}
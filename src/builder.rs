@@ -0,0 +1,1101 @@
+//! Defines the builder used to configure a flag after it is declared.
+use std::cell::OnceCell;
+use std::fmt::Display;
+use std::ops::RangeBounds;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::value::{self, ValueParser};
+use crate::{
+    lookup_env, DecimalMark, FlagEntry, ListMerge, Parser, SizeUnits, ValueEnum, LIST_SEPARATOR,
+};
+
+/// Name of the flag which skips confirmation of flags declared with [`crate::FlagBuilder::confirm`].
+pub(crate) const CONFIRM_FLAG: &str = "yes";
+
+/// Allows further configuration of a flag after it is declared.
+pub struct FlagBuilder<'p, 'a> {
+    pub(crate) parser: &'p mut Parser<'a>,
+    pub(crate) flag: String,
+    /// Configures a positional declared with [`crate::Parser::positional`] instead of a flag.
+    pub(crate) positional: bool,
+}
+
+impl<'a> FlagBuilder<'_, 'a> {
+    fn entry(&mut self) -> &mut FlagEntry<'a> {
+        let entries = if self.positional {
+            &mut self.parser.declared_positionals
+        } else {
+            &mut self.parser.flags
+        };
+        entries.get_mut(&self.flag).unwrap()
+    }
+
+    /// Sets the value used when the flag is not given by any other source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["head", "file.txt"]);
+    /// parser
+    ///     .optional_flag("lines", "this is used to set the number of lines")
+    ///     .default_value("10");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(10), parser.get_value::<u32>("lines"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn default_value(mut self, value: &str) -> Self {
+        self.entry().default = Some(value.to_string());
+        self
+    }
+
+    /// Sets the value used instead of the default when another flag has the given value, which is
+    /// `true` for a boolean flag that is set. It may be called several times, the first condition
+    /// which holds is used.
+    ///
+    /// Conditions are checked during [`crate::Parser::finalize`] once every source is resolved, so
+    /// the other flag may come from any source, while a value given for this flag by a source
+    /// with a higher precedence than the default is still used. The value of the other flag and
+    /// the one it is compared with are both normalized and parsed like the other flag, and a
+    /// conditional default does not affect the conditions of other flags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["serve", "-tls"]);
+    /// parser.bool_flag("tls", "this is used to enable tls");
+    /// parser
+    ///     .optional_flag("port", "this is used to set the port")
+    ///     .default_value("80")
+    ///     .default_value_if("tls", "true", "443");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(443), parser.get_value::<u16>("port"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn default_value_if(mut self, flag: &str, value: &str, default: &str) -> Self {
+        self.entry()
+            .default_if
+            .push((flag.to_string(), value.to_string(), default.to_string()));
+        self
+    }
+
+    /// Marks the flag as sensitive so its value is redacted in any output produced by the parser,
+    /// such as [`std::fmt::Debug`] output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["login", "-password", "hunter2"]);
+    /// parser
+    ///     .required_flag("password", "this is used to set the password")
+    ///     .sensitive();
+    ///
+    /// parser.finalize()?;
+    /// assert!(!format!("{:?}", parser).contains("hunter2"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn sensitive(mut self) -> Self {
+        self.entry().sensitive = true;
+        self
+    }
+
+    /// Hides the flag from the help, shell completions and specs, such as a flag declared by
+    /// another crate which the command does not advertise. The flag is still parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "-trace"]);
+    /// parser.bool_flag("trace", "this is used to trace every call").hidden();
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(true), parser.get_value("trace"));
+    /// # #[cfg(feature = "help")]
+    /// assert!(!parser.help().contains("trace"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn hidden(mut self) -> Self {
+        self.entry().hidden = true;
+        self
+    }
+
+    /// Links the flag name in colored help to online documentation, using the OSC 8 terminal
+    /// hyperlinks which terminals without support show as plain text.
+    ///
+    /// Links are only written along with colors, see [`crate::Parser::write_help_colored`], and
+    /// can be turned off with [`crate::style::Theme::hyperlinks`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// parser
+    ///     .bool_flag("verbose", "this is used to get verbose output")
+    ///     .docs_url("https://example.com/docs#verbose");
+    ///
+    /// let mut help = String::new();
+    /// parser.write_help_colored(&mut help).unwrap();
+    /// assert!(help.contains("\x1b]8;;https://example.com/docs#verbose\x1b\\"));
+    /// assert!(!parser.help().contains("example.com"));
+    /// ```
+    #[cfg(feature = "help")]
+    pub fn docs_url(mut self, url: &str) -> Self {
+        self.entry().docs_url = Some(url.to_string());
+        self
+    }
+
+    /// Requires interactive confirmation during [`crate::Parser::finalize`] when this boolean
+    /// flag is set, unless the companion `-yes` flag is also set.
+    ///
+    /// The `-yes` flag is declared automatically. If the user does not confirm then
+    /// [`crate::Parser::finalize`] returns an error result of type
+    /// [`crate::errors::Error::NotConfirmed`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["rm", "-force-delete", "-yes"]);
+    /// parser
+    ///     .bool_flag("force-delete", "this is used to delete without a backup")
+    ///     .confirm("Delete all files without a backup?");
+    ///
+    /// /// Since the yes flag is set no confirmation is asked for.
+    /// parser.finalize()?;
+    /// assert_eq!(Some(true), parser.get_value::<bool>("force-delete"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn confirm(mut self, prompt: &str) -> Self {
+        self.entry().confirm = Some(prompt.to_string());
+        if !self.parser.flags.contains_key(CONFIRM_FLAG) {
+            self.parser
+                .bool_flag(CONFIRM_FLAG, "this is used to skip confirmation prompts");
+        }
+        self
+    }
+
+    /// Requires the value of the flag to be a path which exists on the filesystem.
+    ///
+    /// The check runs during [`crate::Parser::finalize`] on the resolved value, whichever source it
+    /// came from. If it fails [`crate::Parser::finalize`] returns an error result of type
+    /// [`crate::errors::Error::InvalidValue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["cat", "-input", "/does/not/exist"]);
+    /// parser
+    ///     .required_flag("input", "this is used to set the input file")
+    ///     .path_must_exist();
+    ///
+    /// let error = parser.finalize().unwrap_err();
+    /// assert_eq!(
+    ///     "argument 'input' has an invalid value: path does not exist",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn path_must_exist(self) -> Self {
+        self.parser(Box::new(value::path_exists))
+    }
+
+    /// Rewrites the value of the flag into a canonical form with a normalizer, so code reading it
+    /// does not have to clean it up.
+    ///
+    /// Normalizers run during [`crate::Parser::finalize`] in the order they are declared, on the
+    /// value from any source and on each value of a flag taking multiple values, before any check
+    /// declared on the flag such as [`FlagBuilder::choices`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["ls", "-sort", " Name "]);
+    /// parser
+    ///     .optional_flag("sort", "this is used to set the sort order")
+    ///     .choices(&["name", "time"])
+    ///     .normalize(|value| value.replace('_', "-"))
+    ///     .trim()
+    ///     .lowercase();
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some("name".to_string()), parser.get_value::<String>("sort"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn normalize(mut self, f: impl Fn(&str) -> String + 'static) -> Self {
+        self.entry().normalizers.push(Box::new(f));
+        self
+    }
+
+    /// Removes leading and trailing whitespace from the value of the flag.
+    ///
+    /// See [`FlagBuilder::normalize`] for when normalizers run.
+    pub fn trim(self) -> Self {
+        self.normalize(|value| value.trim().to_string())
+    }
+
+    /// Lowercases the value of the flag.
+    ///
+    /// See [`FlagBuilder::normalize`] for when normalizers run.
+    pub fn lowercase(self) -> Self {
+        self.normalize(str::to_lowercase)
+    }
+
+    /// Removes trailing slashes from the value of the flag, keeping a lone `/`, so a path or URL
+    /// has a single spelling.
+    ///
+    /// See [`FlagBuilder::normalize`] for when normalizers run.
+    pub fn strip_trailing_slash(self) -> Self {
+        self.normalize(value::strip_trailing_slash)
+    }
+
+    /// Requires the value of the flag to be a path to an existing directory.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    pub fn must_be_dir(self) -> Self {
+        self.parser(Box::new(value::path_is_dir))
+    }
+
+    /// Requires the value of the flag to be a path to an existing regular file.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    pub fn must_be_file(self) -> Self {
+        self.parser(Box::new(value::path_is_file))
+    }
+
+    /// Expands a leading `~` to the home directory and `$VAR` or `${VAR}` references to the value
+    /// of the environment variable in the value of the flag, looked up with
+    /// [`crate::Parser::set_env_fn`] if it is set.
+    ///
+    /// The expansion runs during [`crate::Parser::finalize`] before any other check declared on
+    /// the flag, such as [`FlagBuilder::path_must_exist`]. If a referenced variable is not set
+    /// [`crate::Parser::finalize`] returns an error result of type
+    /// [`crate::errors::Error::InvalidValue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["serve", "-config", "$MYAPP_DIR/app.toml"]);
+    /// parser.set_env(HashMap::from([("MYAPP_DIR".to_string(), "/etc/myapp".to_string())]));
+    /// parser
+    ///     .required_flag("config", "this is used to set the configuration file")
+    ///     .expand_path();
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(
+    ///     Some("/etc/myapp/app.toml".to_string()),
+    ///     parser.get_value::<String>("config")
+    /// );
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn expand_path(mut self) -> Self {
+        let env_fn = Rc::clone(&self.parser.env_fn);
+        let expand = move |v: &str| value::expand_path(v, &|name| lookup_env(&env_fn, name));
+        self.entry().parsers.insert(0, Box::new(expand));
+        self
+    }
+
+    /// Requires the flag during [`crate::Parser::finalize`] when another flag has the given value,
+    /// which is `true` for a boolean flag that is set. It may be called several times, the flag is
+    /// then required when any of the conditions holds. A boolean or counted flag is required to be
+    /// given, its default does not count.
+    ///
+    /// If the flag is missing [`crate::Parser::finalize`] returns an error result of type
+    /// [`crate::errors::Error::MissingArgumentIf`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["serve", "-tls"]);
+    /// parser.bool_flag("tls", "this is used to enable tls");
+    /// parser
+    ///     .optional_flag("key-file", "this is used to set the key file")
+    ///     .required_if("tls", "true");
+    ///
+    /// let error = parser.finalize().unwrap_err();
+    /// assert_eq!(
+    ///     "argument 'key-file' is required when 'tls' is 'true'",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn required_if(mut self, flag: &str, value: &str) -> Self {
+        self.entry()
+            .required_if
+            .push((flag.to_string(), value.to_string()));
+        self
+    }
+
+    /// Requires the flag during [`crate::Parser::finalize`] unless one of the given flags is
+    /// present, so it may be supplied in alternative ways. A flag is present when it has a value
+    /// which does not come from its default, and for a boolean flag when it is set.
+    ///
+    /// If neither the flag nor any of the alternatives is present [`crate::Parser::finalize`]
+    /// returns an error result of type [`crate::errors::Error::MissingArgumentUnless`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["login"]);
+    /// parser.optional_flag("password-file", "this is used to read the password");
+    /// parser.bool_flag("anonymous", "this is used to log in anonymously");
+    /// parser
+    ///     .optional_flag("password", "this is used to set the password")
+    ///     .required_unless(&["password-file", "anonymous"]);
+    ///
+    /// let error = parser.finalize().unwrap_err();
+    /// assert_eq!(
+    ///     "argument 'password' is required unless one of 'password-file', 'anonymous' is given",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn required_unless(mut self, flags: &[&str]) -> Self {
+        self.entry()
+            .required_unless
+            .extend(flags.iter().map(|flag| flag.to_string()));
+        self
+    }
+
+    /// Requires the value of the flag to parse as `T` and lie within a range, such as `1..=10` or
+    /// `0.5..`.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["head", "0"]);
+    /// parser
+    ///     .positional("lines", "the number of lines to print")
+    ///     .range(1..=100);
+    ///
+    /// let error = parser.finalize().unwrap_err();
+    /// assert_eq!(
+    ///     "positional argument 'lines' (the number of lines to print) has an invalid value: \
+    ///      expected a value between 1 and 100",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn range<T, R>(self, range: R) -> Self
+    where
+        T: FromStr + PartialOrd + Display + 'static,
+        R: RangeBounds<T> + 'static,
+    {
+        self.parser(Box::new(move |v| value::in_range(v, &range)))
+    }
+
+    /// Requires the value of the flag to pass a closure, which returns the reason the value is
+    /// invalid otherwise. The reason should not repeat the value since it may be sensitive.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["greet", "-name", "x"]);
+    /// parser
+    ///     .optional_flag("name", "this is used to set the name to greet")
+    ///     .validate(|v| match v.len() {
+    ///         2.. => Ok(()),
+    ///         _ => Err("expected at least two characters".to_string()),
+    ///     });
+    ///
+    /// let error = parser.finalize().unwrap_err();
+    /// assert_eq!(
+    ///     "argument 'name' has an invalid value: expected at least two characters",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn validate(self, f: impl Fn(&str) -> Result<(), String> + 'static) -> Self {
+        self.parser(Box::new(move |v| f(v).map(|()| v.to_string())))
+    }
+
+    /// Checks the value of the flag with a value parser registered under a name with
+    /// [`crate::Parser::register_value_parser`], storing the value it returns.
+    ///
+    /// The name may be registered after the flag is declared. A name which is still not
+    /// registered when [`crate::Parser::finalize`] is called makes it return an error result of
+    /// type [`crate::errors::Error::UnknownValueParser`], and is reported by
+    /// [`crate::Parser::validate_definition`]. See [`FlagBuilder::path_must_exist`] for when the
+    /// check runs.
+    pub fn value_parser(self, name: &str) -> Self {
+        match self.parser.value_parsers.get(name) {
+            Some(parser) => {
+                let parser = Rc::clone(parser);
+                self.parser(Box::new(move |v| parser(v)))
+            }
+            None => {
+                let slot: Rc<OnceCell<_>> = Rc::new(OnceCell::new());
+                let unknown = (self.flag.clone(), name.to_string(), Rc::clone(&slot));
+                self.parser.unknown_value_parsers.push(unknown);
+                self.parser(Box::new(move |v| match slot.get() {
+                    Some(parser) => parser(v),
+                    None => Err("the value parser is not registered".to_string()),
+                }))
+            }
+        }
+    }
+
+    /// Requires the value of the flag to be one of the given choices, which are listed in the
+    /// help.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["ls", "-sort", "size"]);
+    /// parser
+    ///     .optional_flag("sort", "this is used to set the sort order")
+    ///     .choices(&["name", "time"]);
+    ///
+    /// let error = parser.finalize().unwrap_err();
+    /// assert_eq!(
+    ///     "argument 'sort' has an invalid value: expected one of: name, time",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn choices(mut self, choices: &[&str]) -> Self {
+        let choices: Vec<String> = choices.iter().map(|c| c.to_string()).collect();
+        self.entry().choices = choices.clone();
+        self.parser(Box::new(move |v| value::choice(v, &choices)))
+    }
+
+    /// Requires the value of the flag to be the name of a [`crate::ValueEnum`] variant, usually
+    /// defined with [`crate::value_enum!`], and lists the names in the help.
+    ///
+    /// The value is read with `get_value::<T>()`, see [`crate::value_enum!`] for an example.
+    pub fn value_enum<T: ValueEnum>(self) -> Self {
+        self.choices(T::VARIANTS)
+    }
+
+    /// Lets the flag take multiple values, read with [`crate::Parser::get_values`].
+    ///
+    /// Every occurrence on the command line adds a value, while environment values are split on
+    /// a `,` unless set otherwise with [`FlagBuilder::env_delimiter`] and configuration values
+    /// are split on a `,`. Checks declared on the flag run on each value. See
+    /// [`FlagBuilder::merge`] for how values from several sources are combined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["link", "-ports", "80", "-ports", "443"]);
+    /// parser.env_prefix("MYAPP");
+    /// parser.set_env(HashMap::from([(
+    ///     "MYAPP_PATHS".to_string(),
+    ///     "/usr/lib:/opt/lib".to_string(),
+    /// )]));
+    /// parser
+    ///     .optional_flag("ports", "this is used to set the ports to listen on")
+    ///     .multiple()
+    ///     .integer();
+    /// parser
+    ///     .optional_flag("paths", "this is used to set the library paths")
+    ///     .multiple()
+    ///     .env_delimiter(':');
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(vec![80, 443]), parser.get_values::<u16>("ports"));
+    /// assert_eq!(
+    ///     Some(vec!["/usr/lib".to_string(), "/opt/lib".to_string()]),
+    ///     parser.get_values("paths")
+    /// );
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn multiple(mut self) -> Self {
+        let entry = self.entry();
+        entry.multiple = true;
+        entry.accumulate = Some(LIST_SEPARATOR);
+        self
+    }
+
+    /// Makes the value of the flag a tuple with a fixed number of items, given as one value
+    /// separated by the delimiter, such as `800x600`, or on the command line as consecutive
+    /// tokens, such as `-offset 10 20`. The tuple is read with [`crate::Parser::get_tuple`].
+    ///
+    /// If the value does not hold the number of items [`crate::Parser::finalize`] returns an
+    /// error result of type [`crate::errors::Error::InvalidValue`], see
+    /// [`FlagBuilder::path_must_exist`] for when the check runs.
+    pub fn tuple(mut self, arity: usize, delimiter: char) -> Self {
+        self.entry().tuple = Some((arity, delimiter));
+        self.parser(Box::new(move |v| crate::tuple::check(v, arity, delimiter)))
+    }
+
+    /// Requires a flag taking multiple values, see [`FlagBuilder::multiple`], to be given at least
+    /// a number of times, counting every value from the source it is resolved from. A default
+    /// value is not counted.
+    ///
+    /// If the flag is given fewer times [`crate::Parser::finalize`] returns an error result of
+    /// type [`crate::errors::Error::TooFewOccurrences`] with the count and the minimum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["cat"]);
+    /// parser
+    ///     .optional_flag("input", "this is used to add an input file")
+    ///     .multiple()
+    ///     .min_occurrences(1);
+    ///
+    /// let error = parser.finalize().unwrap_err();
+    /// assert_eq!(
+    ///     "argument 'input' is given 0 times but at least 1 are required",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn min_occurrences(mut self, min: usize) -> Self {
+        self.entry().min_occurrences = min;
+        self
+    }
+
+    /// Allows a flag taking multiple values, see [`FlagBuilder::multiple`], to be given at most a
+    /// number of times, counting every value from the source it is resolved from. A default value
+    /// is not counted.
+    ///
+    /// If the flag is given more times [`crate::Parser::finalize`] returns an error result of
+    /// type [`crate::errors::Error::TooManyOccurrences`] with the count and the maximum.
+    pub fn max_occurrences(mut self, max: usize) -> Self {
+        self.entry().max_occurrences = Some(max);
+        self
+    }
+
+    /// Sets environment variables providing the value of the flag, checked in order before the
+    /// one derived from [`crate::Parser::env_prefix`], such as a current and a legacy name.
+    ///
+    /// The variable which provided the value is returned by [`crate::Parser::value_env_var`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["release"]);
+    /// parser.set_env(HashMap::from([("GH_TOKEN".to_string(), "secret".to_string())]));
+    /// parser
+    ///     .optional_flag("token", "this is used to set the API token")
+    ///     .env(&["GITHUB_TOKEN", "GH_TOKEN"]);
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some("secret".to_string()), parser.get_value("token"));
+    /// assert_eq!(Some("GH_TOKEN"), parser.value_env_var("token"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn env(mut self, names: &[&str]) -> Self {
+        self.entry().env_vars = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Sets how the values of a flag taking multiple values are combined when several sources
+    /// provide them, [`ListMerge::Replace`] by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::{ListMerge, Parser};
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["cc", "-include", "/usr/local/include"]);
+    /// parser
+    ///     .optional_flag("include", "this is used to add include paths")
+    ///     .multiple()
+    ///     .merge(ListMerge::Append);
+    /// parser.config_value("include", "/usr/include");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(
+    ///     Some(vec!["/usr/include".to_string(), "/usr/local/include".to_string()]),
+    ///     parser.get_values("include")
+    /// );
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn merge(mut self, merge: ListMerge) -> Self {
+        self.entry().merge = merge;
+        self
+    }
+
+    /// Sets the delimiter splitting an environment value into the values of a flag taking
+    /// multiple values, such as `:` for `PATH` style variables. The default is `,`.
+    pub fn env_delimiter(mut self, delimiter: char) -> Self {
+        self.entry().env_delimiter = delimiter;
+        self
+    }
+
+    /// Requires the value of the flag to be a list of the given names separated by `|` or `,`,
+    /// combining the bits of each name into a single value read with `get_value::<u64>()`.
+    ///
+    /// The flag may also be repeated, in which case the names of every occurrence are combined.
+    /// The result suits types built with the `bitflags` crate through `from_bits_truncate`. The
+    /// names are listed in the help, see [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// const COMPRESS: u64 = 1;
+    /// const ENCRYPT: u64 = 2;
+    /// const DEDUPE: u64 = 4;
+    ///
+    /// let args = ["backup", "-features", "compress|encrypt", "-features", "dedupe"];
+    /// let mut parser = Parser::from_iter(args);
+    /// parser
+    ///     .optional_flag("features", "this is used to enable backup features")
+    ///     .bitflags(&[("compress", COMPRESS), ("encrypt", ENCRYPT), ("dedupe", DEDUPE)]);
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(COMPRESS | ENCRYPT | DEDUPE), parser.get_value::<u64>("features"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn bitflags(mut self, flags: &[(&str, u64)]) -> Self {
+        let flags: Vec<(String, u64)> = flags
+            .iter()
+            .map(|(name, bits)| (name.to_string(), *bits))
+            .collect();
+        let entry = self.entry();
+        entry.choices = flags.iter().map(|(name, _)| name.clone()).collect();
+        entry.accumulate = Some('|');
+        self.parser(Box::new(move |v| value::bitflags(v, &flags)))
+    }
+
+    /// Requires the value of the flag to be an integer, accepting hexadecimal, octal and binary
+    /// literals such as `0xff`, `0o755` and `0b1010` besides decimal.
+    ///
+    /// The value is stored in decimal so it can be read with `get_value` as any integer type. See
+    /// [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["chmod", "-mode", "0o755"]);
+    /// parser
+    ///     .required_flag("mode", "this is used to set the file mode")
+    ///     .integer();
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(0o755), parser.get_value::<u32>("mode"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn integer(self) -> Self {
+        self.parser(Box::new(value::integer))
+    }
+
+    /// Accepts numbers written with a decimal mark and digit grouping, such as `1.234,56` with
+    /// [`crate::DecimalMark::Comma`] or `1,000,000` and `1_000_000` with
+    /// [`crate::DecimalMark::Point`], for tools used with locales other than English.
+    ///
+    /// Digits of the integer part may be grouped by three with the other mark, underscores,
+    /// apostrophes or spaces. The value is stored with a point and without grouping, so it can be
+    /// read with `get_value` as any numeric type, and is rewritten before the other checks on the
+    /// flag, such as [`FlagBuilder::range`], run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::{DecimalMark, Parser};
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["invoice", "-amount", "1.234,56"]);
+    /// parser
+    ///     .required_flag("amount", "this is used to set the amount")
+    ///     .range(0.0..)
+    ///     .localized_number(DecimalMark::Comma);
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(1234.56), parser.get_value::<f64>("amount"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn localized_number(mut self, mark: DecimalMark) -> Self {
+        self.entry()
+            .parsers
+            .insert(0, Box::new(move |v| value::localized_number(v, mark)));
+        self
+    }
+
+    /// Requires the value of the flag to be a byte size such as `512`, `4k`, `10MiB` or `2G`.
+    ///
+    /// Unit prefixes with an `i`, such as `KiB`, are always powers of 1024 while `k`, `kB` and the
+    /// like follow the given units. The value is stored as a number of bytes so it can be read
+    /// with `get_value::<u64>()`. See [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::{Parser, SizeUnits};
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["cache", "-max-size", "4k"]);
+    /// parser
+    ///     .required_flag("max-size", "this is used to set the maximum cache size")
+    ///     .byte_size(SizeUnits::Iec);
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(4096), parser.get_value::<u64>("max-size"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn byte_size(self, units: SizeUnits) -> Self {
+        self.parser(Box::new(move |v| value::byte_size(v, units)))
+    }
+
+    /// Requires the value of the flag to be a color such as `#ff8000`, `rgb(255, 128, 0)` or
+    /// `teal`, read with `get_value::<yafp::color::Rgb>()`.
+    ///
+    /// See [`crate::color::Rgb`] for an example and [`FlagBuilder::path_must_exist`] for when the
+    /// check runs.
+    pub fn color(self) -> Self {
+        self.parser(Box::new(crate::color::parse))
+    }
+
+    /// Requires the value of the flag to be standard or URL-safe base64, with or without padding,
+    /// which is decoded into the bytes returned by [`crate::Parser::get_bytes`].
+    ///
+    /// The value read with `get_value` stays as given. See [`FlagBuilder::path_must_exist`] for
+    /// when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["sign", "-key", "3q2+7w=="]);
+    /// parser
+    ///     .required_flag("key", "this is used to set the signing key")
+    ///     .sensitive()
+    ///     .base64();
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(vec![0xde, 0xad, 0xbe, 0xef]), parser.get_bytes("key"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn base64(mut self) -> Self {
+        self.entry().decoder = Some(Box::new(value::base64));
+        self
+    }
+
+    /// Treats the value of the flag as the path of a file whose contents become the value, for
+    /// flags such as `-script` or `-cert`.
+    ///
+    /// The file is read during [`crate::Parser::finalize`], which returns an error result of type
+    /// [`crate::errors::Error::InvalidValue`] if it cannot be read. The contents are read as text
+    /// with `get_value`, replacing invalid UTF-8, or as bytes with [`crate::Parser::get_bytes`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["check", "-manifest", "Cargo.toml"]);
+    /// parser
+    ///     .required_flag("manifest", "this is used to set the manifest to check")
+    ///     .file_contents();
+    ///
+    /// parser.finalize()?;
+    /// let manifest: String = parser.get_value("manifest").unwrap();
+    /// assert!(manifest.contains("[package]"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn file_contents(mut self) -> Self {
+        let entry = self.entry();
+        entry.decoder = Some(Box::new(value::file_contents));
+        entry.decoded_value = true;
+        self
+    }
+
+    /// Reads the value of the flag from stdin when it is given as `-`, so pipelines such as
+    /// `generate | mytool -payload -` work without temporary files.
+    ///
+    /// A single trailing newline is removed from the value. For flags declared with
+    /// [`FlagBuilder::file_contents`] stdin is read as the contents of the file instead, kept as
+    /// is. Only one flag may read stdin, otherwise [`crate::Parser::finalize`] returns an error
+    /// result of type [`crate::errors::Error::InvalidValue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["send", "-payload", "-"]);
+    /// parser
+    ///     .required_flag("payload", "this is used to set the payload to send")
+    ///     .stdin();
+    ///
+    /// /// Stand in for `echo hello | send -payload -`.
+    /// parser.set_stdin_fn(|| Ok(b"hello\n".to_vec()));
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some("hello".to_string()), parser.get_value::<String>("payload"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn stdin(mut self) -> Self {
+        self.entry().stdin = true;
+        self
+    }
+
+    /// Requires the value of the flag to be an IPv4 or IPv6 address, read with
+    /// `get_value::<std::net::IpAddr>()`.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    pub fn ip_addr(self) -> Self {
+        self.parser(Box::new(value::ip_addr))
+    }
+
+    /// Requires the value of the flag to be an address with a port, such as `0.0.0.0:8080` or
+    /// `[::1]:8080`, read with `get_value::<std::net::SocketAddr>()`.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    pub fn socket_addr(self) -> Self {
+        self.parser(Box::new(|v| value::socket_addr(v, None)))
+    }
+
+    /// Requires the value of the flag to be an address with an optional port, using the given
+    /// port when it is left out.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::SocketAddr;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["serve", "-listen", "0.0.0.0"]);
+    /// parser
+    ///     .required_flag("listen", "this is used to set the listen address")
+    ///     .socket_addr_default_port(8080);
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(
+    ///     Some("0.0.0.0:8080".parse::<SocketAddr>().unwrap()),
+    ///     parser.get_value::<SocketAddr>("listen")
+    /// );
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn socket_addr_default_port(self, port: u16) -> Self {
+        self.parser(Box::new(move |v| value::socket_addr(v, Some(port))))
+    }
+
+    /// Requires the value of the flag to be a network range such as `10.0.0.0/8`, read with
+    /// `get_value::<yafp::cidr::Cidr>()`.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    #[cfg(feature = "cidr")]
+    pub fn cidr(self) -> Self {
+        self.parser(Box::new(crate::cidr::parse))
+    }
+
+    /// Requires the value of the flag to be an absolute URL with a host, read with
+    /// `get_value::<url::Url>()`.
+    ///
+    /// If any schemes are given the URL must use one of them, an empty list allows any scheme.
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["fetch", "-url", "http://example.com"]);
+    /// parser
+    ///     .required_flag("url", "this is used to set the URL to fetch")
+    ///     .url(&["https"]);
+    ///
+    /// let error = parser.finalize().unwrap_err();
+    /// assert_eq!(
+    ///     "argument 'url' has an invalid value: URL scheme 'http' is not allowed, expected one of: https",
+    ///     error.to_string()
+    /// );
+    /// ```
+    #[cfg(feature = "url")]
+    pub fn url(self, schemes: &[&str]) -> Self {
+        let schemes: Vec<String> = schemes.iter().map(|s| s.to_string()).collect();
+        self.parser(Box::new(move |v| value::url(v, &schemes)))
+    }
+
+    /// Requires the value of the flag to be a semantic version such as `1.2.3`, read with
+    /// `get_value::<semver::Version>()` which allows comparing versions.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use semver::Version;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["release", "-min-version", "1.2.3"]);
+    /// parser
+    ///     .required_flag("min-version", "this is used to set the minimum version")
+    ///     .semver();
+    ///
+    /// parser.finalize()?;
+    /// let min: Version = parser.get_value("min-version").unwrap();
+    /// assert!(Version::new(1, 10, 0) > min);
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[cfg(feature = "semver")]
+    pub fn semver(self) -> Self {
+        self.parser(Box::new(value::semver))
+    }
+
+    /// Requires the value of the flag to be an RFC 3339 timestamp or a `YYYY-MM-DD` date taken as
+    /// midnight UTC, read with `get_value::<chrono::DateTime<chrono::FixedOffset>>()`.
+    ///
+    /// The value is stored as an RFC 3339 timestamp, which converts into a
+    /// [`std::time::SystemTime`] with [`Into`]. See [`FlagBuilder::path_must_exist`] for when the
+    /// check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{DateTime, FixedOffset};
+    /// use std::time::SystemTime;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["logs", "-since", "2024-01-31"]);
+    /// parser
+    ///     .required_flag("since", "this is used to set the start of the logs")
+    ///     .datetime();
+    ///
+    /// parser.finalize()?;
+    /// let since: DateTime<FixedOffset> = parser.get_value("since").unwrap();
+    /// assert_eq!("2024-01-31T00:00:00+00:00", since.to_rfc3339());
+    ///
+    /// let since: SystemTime = since.into();
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn datetime(self) -> Self {
+        self.parser(Box::new(value::datetime))
+    }
+
+    /// Requires the value of the flag to be a `YYYY-MM-DD` date, read with
+    /// `get_value::<chrono::NaiveDate>()`.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    #[cfg(feature = "chrono")]
+    pub fn date(self) -> Self {
+        self.parser(Box::new(value::date))
+    }
+
+    /// Requires the value of the flag to be a regular expression which compiles, read with
+    /// `get_value::<regex::Regex>()`.
+    ///
+    /// Compilation errors are returned by [`crate::Parser::finalize`] as
+    /// [`crate::errors::Error::InvalidValue`], so a later `get_value` only returns `None` when
+    /// the flag is unset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use regex::Regex;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["grep", "-pattern", "^fn [a-z_]+"]);
+    /// parser
+    ///     .required_flag("pattern", "this is used to set the pattern to search for")
+    ///     .regex();
+    ///
+    /// parser.finalize()?;
+    /// let pattern: Regex = parser.get_value("pattern").unwrap();
+    /// assert!(pattern.is_match("fn main"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn regex(self) -> Self {
+        self.parser(Box::new(value::regex))
+    }
+
+    /// Requires the value of the flag to be JSON, read with `get_value::<serde_json::Value>()` or
+    /// deserialized with [`crate::Parser::get_json`].
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    #[cfg(feature = "serde_json")]
+    pub fn json(self) -> Self {
+        self.parser(Box::new(value::json::<serde_json::Value>))
+    }
+
+    /// Requires the value of the flag to be JSON which deserializes into `T`, so that
+    /// [`crate::Parser::get_json`] with the same type always succeeds for a set flag.
+    ///
+    /// See [`FlagBuilder::path_must_exist`] for when the check runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["plot", "-points", r#"{"x": 1}"#]);
+    /// parser
+    ///     .optional_flag("points", "this is used to set the points to plot")
+    ///     .json_as::<Vec<(f64, f64)>>();
+    ///
+    /// let error = parser.finalize().unwrap_err();
+    /// assert!(matches!(error, Error::InvalidValue(..)));
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn json_as<T: serde::de::DeserializeOwned + 'static>(self) -> Self {
+        self.parser(Box::new(value::json::<T>))
+    }
+
+    fn parser(mut self, parser: ValueParser) -> Self {
+        self.entry().parsers.push(parser);
+        self
+    }
+}
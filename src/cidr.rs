@@ -0,0 +1,167 @@
+//! Defines a CIDR network range value type, behind the `cidr` feature.
+use std::fmt::{self, Display};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// A network range written as an address and a prefix length, such as `10.0.0.0/8`.
+///
+/// The address must not have bits set beyond the prefix length.
+///
+/// # Examples
+///
+/// ```
+/// use yafp::Parser;
+/// use yafp::cidr::Cidr;
+/// use yafp::errors::Error;
+///
+/// let mut parser = Parser::from_iter(["scan", "-network", "10.0.0.0/8"]);
+/// parser
+///     .required_flag("network", "this is used to set the network to scan")
+///     .cidr();
+///
+/// parser.finalize()?;
+/// let network: Cidr = parser.get_value("network").unwrap();
+/// assert!(network.contains("10.1.2.3".parse().unwrap()));
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    /// Returns the network address.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Returns the number of leading bits which identify the network.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Returns whether an address belongs to the network range.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                u32::from(addr) & mask(self.prefix_len, 32) as u32 == u32::from(net)
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                u128::from(addr) & mask(self.prefix_len, 128) == u128::from(net)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns whether the address has no bits set beyond the prefix length.
+    fn is_network_addr(&self) -> bool {
+        match self.addr {
+            IpAddr::V4(addr) => u32::from(addr) & !(mask(self.prefix_len, 32) as u32) == 0,
+            IpAddr::V6(addr) => u128::from(addr) & !mask(self.prefix_len, 128) == 0,
+        }
+    }
+}
+
+/// Returns a mask with the leading `prefix_len` bits of a `bits` wide address set.
+fn mask(prefix_len: u8, bits: u32) -> u128 {
+    match prefix_len {
+        0 => 0,
+        n => (u128::MAX << (128 - u32::from(n))) >> (128 - bits),
+    }
+}
+
+/// A list of possible errors when parsing a [`Cidr`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CidrError {
+    /// The `/` separating the address from the prefix length is missing.
+    MissingPrefix,
+
+    /// The address is not a valid IPv4 or IPv6 address.
+    InvalidAddress,
+
+    /// The prefix length is not a number or is longer than the address.
+    InvalidPrefix,
+
+    /// The address has bits set beyond the prefix length.
+    HostBitsSet,
+}
+
+impl Display for CidrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CidrError::MissingPrefix => write!(f, "network range is missing a prefix length"),
+            CidrError::InvalidAddress => write!(f, "network range has an invalid address"),
+            CidrError::InvalidPrefix => write!(f, "network range has an invalid prefix length"),
+            CidrError::HostBitsSet => {
+                write!(f, "network range has bits set beyond the prefix length")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CidrError {}
+
+impl FromStr for Cidr {
+    type Err = CidrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = s.split_once('/').ok_or(CidrError::MissingPrefix)?;
+        let addr: IpAddr = addr.parse().map_err(|_| CidrError::InvalidAddress)?;
+
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len: u8 = match prefix_len.parse() {
+            Ok(n) if u32::from(n) <= bits && !prefix_len.starts_with('+') => n,
+            _ => return Err(CidrError::InvalidPrefix),
+        };
+
+        let cidr = Cidr { addr, prefix_len };
+        if !cidr.is_network_addr() {
+            return Err(CidrError::HostBitsSet);
+        }
+        Ok(cidr)
+    }
+}
+
+impl Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// Checks that a value is a [`Cidr`] network range.
+pub(crate) fn parse(value: &str) -> Result<String, String> {
+    value
+        .parse::<Cidr>()
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn ranges() {
+        let net: Cidr = "192.168.0.0/16".parse().unwrap();
+        assert!(net.contains("192.168.10.1".parse().unwrap()));
+        assert!(!net.contains("192.169.0.1".parse().unwrap()));
+        assert!(!net.contains("::1".parse().unwrap()));
+
+        let all: Cidr = "0.0.0.0/0".parse().unwrap();
+        assert!(all.contains("8.8.8.8".parse().unwrap()));
+
+        let v6: Cidr = "2001:db8::/32".parse().unwrap();
+        assert!(v6.contains("2001:db8::1".parse().unwrap()));
+        assert_eq!(v6.to_string(), "2001:db8::/32");
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!("10.0.0.0".parse::<Cidr>(), Err(CidrError::MissingPrefix));
+        assert_eq!("10.0.0/8".parse::<Cidr>(), Err(CidrError::InvalidAddress));
+        assert_eq!("10.0.0.0/33".parse::<Cidr>(), Err(CidrError::InvalidPrefix));
+        assert_eq!("10.0.0.1/8".parse::<Cidr>(), Err(CidrError::HostBitsSet));
+    }
+}
@@ -0,0 +1,161 @@
+//! Maps a subset of the clap builder API onto the parser, so an existing CLI can be migrated one
+//! declaration at a time, see [`crate::Parser::arg`].
+use crate::Parser;
+
+/// An argument declared in the style of clap's `Arg`.
+///
+/// An argument with [`Arg::long`] becomes a flag named after it, a boolean flag unless it
+/// [`Arg::takes_value`]. An argument without it becomes a declared positional, see
+/// [`crate::Parser::positional`]. Values are read by the id, as with clap, while the long name is
+/// the one given on the command line and shown in the help. Flags are given with a single dash
+/// like any other flag of the parser.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Arg {
+    id: String,
+    long: Option<String>,
+    help: String,
+    takes_value: bool,
+    required: bool,
+    default_value: Option<String>,
+}
+
+impl Arg {
+    /// Creates an argument with an id, which names it if it has no long name.
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the long name, making the argument a flag.
+    pub fn long(mut self, long: &str) -> Self {
+        self.long = Some(long.to_string());
+        self
+    }
+
+    /// Sets the help text, used as the usage of the flag.
+    pub fn help(mut self, help: &str) -> Self {
+        self.help = help.to_string();
+        self
+    }
+
+    /// Sets whether the flag takes a value, otherwise it is a boolean flag.
+    pub fn takes_value(mut self, yes: bool) -> Self {
+        self.takes_value = yes;
+        self
+    }
+
+    /// Sets whether the argument is required, which has no effect on boolean flags or on
+    /// arguments with a default.
+    pub fn required(mut self, yes: bool) -> Self {
+        self.required = yes;
+        self
+    }
+
+    /// Sets the value used when the argument is not given.
+    pub fn default_value(mut self, value: &str) -> Self {
+        self.default_value = Some(value.to_string());
+        self
+    }
+}
+
+impl Parser<'_> {
+    /// Declares an argument built in the style of clap, returning the parser so calls can be
+    /// chained like on clap's `Command`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use yafp::Parser;
+    /// use yafp::clap_compat::Arg;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["grep", "-pattern", "fn", "-count", "src/lib.rs"]);
+    /// parser
+    ///     .arg(
+    ///         Arg::new("pattern")
+    ///             .long("pattern")
+    ///             .takes_value(true)
+    ///             .required(true)
+    ///             .help("the pattern to search for"),
+    ///     )
+    ///     .arg(Arg::new("count").long("count").help("only count matches"))
+    ///     .arg(Arg::new("file").required(true).help("the file to search"));
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some("fn".to_string()), parser.get_value("pattern"));
+    /// assert_eq!(Some(true), parser.get_value("count"));
+    /// assert_eq!(Some(PathBuf::from("src/lib.rs")), parser.get_value("file"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn arg(&mut self, arg: Arg) -> &mut Self {
+        let required = arg.required && arg.default_value.is_none();
+        let builder = match (&arg.long, arg.takes_value) {
+            (None, _) if required => self.positional(&arg.id, &arg.help),
+            (None, _) => self.optional_positional(&arg.id, &arg.help),
+            (Some(long), false) => self.bool_flag(long, &arg.help),
+            (Some(long), true) if required => self.required_flag(long, &arg.help),
+            (Some(long), true) => self.optional_flag(long, &arg.help),
+        };
+        if let Some(default) = &arg.default_value {
+            builder.default_value(default);
+        }
+        if let Some(long) = arg.long.filter(|long| *long != arg.id) {
+            self.arg_ids.insert(arg.id, long);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::errors::Error;
+
+    #[test]
+    fn mapping() {
+        let mut parser = Parser::from_iter(["tool"]);
+        parser
+            .arg(
+                Arg::new("config")
+                    .long("config")
+                    .takes_value(true)
+                    .required(true),
+            )
+            .arg(
+                Arg::new("level")
+                    .long("level")
+                    .takes_value(true)
+                    .required(true)
+                    .default_value("info"),
+            );
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::MissingArgument("config".to_string()))
+        );
+
+        let mut parser = Parser::from_iter(["tool", "-config", "a.toml"]);
+        parser
+            .arg(Arg::new("config").long("config").takes_value(true))
+            .arg(
+                Arg::new("level")
+                    .long("level")
+                    .takes_value(true)
+                    .default_value("info"),
+            )
+            .arg(Arg::new("dir").default_value("."));
+        assert!(parser.finalize().unwrap().is_empty());
+        assert_eq!(parser.get_value::<String>("level").as_deref(), Some("info"));
+        assert_eq!(parser.get_value::<String>("dir").as_deref(), Some("."));
+
+        // The value is read by the id, the long name is given on the command line.
+        let mut parser = Parser::from_iter(["tool", "-debug"]);
+        parser.arg(Arg::new("verbose").long("debug"));
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<bool>("verbose"), Some(true));
+        assert_eq!(parser.get_value::<bool>("debug"), Some(true));
+    }
+}
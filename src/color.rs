@@ -0,0 +1,156 @@
+//! Defines an RGB color value type.
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// The named colors accepted by [`Rgb`], following the basic CSS color keywords.
+const NAMED: [(&str, Rgb); 17] = [
+    ("black", Rgb::new(0, 0, 0)),
+    ("silver", Rgb::new(192, 192, 192)),
+    ("gray", Rgb::new(128, 128, 128)),
+    ("grey", Rgb::new(128, 128, 128)),
+    ("white", Rgb::new(255, 255, 255)),
+    ("maroon", Rgb::new(128, 0, 0)),
+    ("red", Rgb::new(255, 0, 0)),
+    ("purple", Rgb::new(128, 0, 128)),
+    ("fuchsia", Rgb::new(255, 0, 255)),
+    ("green", Rgb::new(0, 128, 0)),
+    ("lime", Rgb::new(0, 255, 0)),
+    ("olive", Rgb::new(128, 128, 0)),
+    ("yellow", Rgb::new(255, 255, 0)),
+    ("navy", Rgb::new(0, 0, 128)),
+    ("blue", Rgb::new(0, 0, 255)),
+    ("teal", Rgb::new(0, 128, 128)),
+    ("aqua", Rgb::new(0, 255, 255)),
+];
+
+/// A color given as `#RRGGBB`, `#RGB`, `rgb(R, G, B)` or a basic CSS color name such as `teal`.
+///
+/// # Examples
+///
+/// ```
+/// use yafp::Parser;
+/// use yafp::color::Rgb;
+/// use yafp::errors::Error;
+///
+/// let mut parser = Parser::from_iter(["theme", "-accent", "rgb(255, 128, 0)"]);
+/// parser
+///     .optional_flag("accent", "this is used to set the accent color")
+///     .color();
+///
+/// parser.finalize()?;
+/// assert_eq!(Some(Rgb::new(255, 128, 0)), parser.get_value::<Rgb>("accent"));
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rgb {
+    /// The red component.
+    pub r: u8,
+    /// The green component.
+    pub g: u8,
+    /// The blue component.
+    pub b: u8,
+}
+
+impl Rgb {
+    /// Creates a color from its components.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        if let Some(hex) = s.strip_prefix('#') {
+            return parse_hex(hex).ok_or_else(|| "not a valid hex color".to_string());
+        }
+        if let Some(args) = s.strip_prefix("rgb(").and_then(|s| s.strip_suffix(')')) {
+            return parse_rgb(args).ok_or_else(|| "not a valid rgb() color".to_string());
+        }
+        NAMED
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, color)| *color)
+            .ok_or_else(|| "not a known color name".to_string())
+    }
+}
+
+/// Parses the digits of a `#RRGGBB` or `#RGB` color.
+fn parse_hex(hex: &str) -> Option<Rgb> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let component = |i: usize, len: usize| {
+        let n = u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).ok()?;
+        // Short colors repeat each digit, so `f` is `ff`.
+        Some(if len == 1 { n * 17 } else { n })
+    };
+    let len = match hex.len() {
+        3 => 1,
+        6 => 2,
+        _ => return None,
+    };
+    Some(Rgb::new(
+        component(0, len)?,
+        component(1, len)?,
+        component(2, len)?,
+    ))
+}
+
+/// Parses the comma separated components of a `rgb(R, G, B)` color.
+fn parse_rgb(args: &str) -> Option<Rgb> {
+    let mut components = args.split(',').map(|c| c.trim().parse::<u8>());
+    let color = Rgb::new(
+        components.next()?.ok()?,
+        components.next()?.ok()?,
+        components.next()?.ok()?,
+    );
+    match components.next() {
+        Some(_) => None,
+        None => Some(color),
+    }
+}
+
+impl Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Checks that a value is an [`Rgb`] color, returning it as `#rrggbb`.
+pub(crate) fn parse(value: &str) -> Result<String, String> {
+    value.parse::<Rgb>().map(|color| color.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn formats() {
+        assert_eq!("#FF8000".parse(), Ok(Rgb::new(255, 128, 0)));
+        assert_eq!("#f80".parse(), Ok(Rgb::new(255, 136, 0)));
+        assert_eq!("rgb(1, 2,3)".parse(), Ok(Rgb::new(1, 2, 3)));
+        assert_eq!("Teal".parse(), Ok(Rgb::new(0, 128, 128)));
+        assert_eq!(Rgb::new(255, 128, 0).to_string(), "#ff8000");
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            "#ff80".parse::<Rgb>(),
+            Err("not a valid hex color".to_string())
+        );
+        assert_eq!(
+            "rgb(1, 2, 300)".parse::<Rgb>(),
+            Err("not a valid rgb() color".to_string())
+        );
+        assert_eq!(
+            "blurple".parse::<Rgb>(),
+            Err("not a known color name".to_string())
+        );
+    }
+}
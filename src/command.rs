@@ -0,0 +1,65 @@
+//! Allows subcommands to be implemented separately and registered on a parser.
+use crate::Parser;
+
+/// A subcommand implemented as a type, so it can live in its own crate and be registered with
+/// [`crate::Parser::register_commands`].
+///
+/// # Examples
+///
+/// ```
+/// use yafp::{Command, Parser};
+/// use yafp::errors::Error;
+///
+/// struct Build;
+///
+/// impl Command for Build {
+///     fn name(&self) -> &str {
+///         "build"
+///     }
+///
+///     fn about(&self) -> &str {
+///         "compile the project"
+///     }
+///
+///     fn configure(&self, parser: &mut Parser<'_>) {
+///         parser.bool_flag("release", "this is used to build with optimizations");
+///     }
+///
+///     fn run(&self, matches: &Parser<'_>) -> Result<(), Box<dyn std::error::Error>> {
+///         let release: bool = matches.get_value("release").unwrap();
+///         println!("building, release: {}", release);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut parser = Parser::from_iter(["tool", "build", "-release"]);
+/// parser.register_commands(vec![Box::new(Build) as Box<dyn Command>]);
+///
+/// parser.dispatch()?;
+/// # Ok::<(), Error>(())
+/// ```
+pub trait Command {
+    /// Returns the name which selects the subcommand.
+    fn name(&self) -> &str;
+
+    /// Returns the description of the subcommand shown in help.
+    fn about(&self) -> &str;
+
+    /// Declares the flags and nested subcommands on the parser of the subcommand.
+    fn configure(&self, parser: &mut Parser<'_>);
+
+    /// Runs the subcommand with its parsed parser, see [`crate::Parser::dispatch`].
+    fn run(&self, matches: &Parser<'_>) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+impl Parser<'_> {
+    /// Declares a subcommand for each [`Command`], configured by the command and with a handler
+    /// running it.
+    pub fn register_commands(&mut self, commands: impl IntoIterator<Item = Box<dyn Command>>) {
+        for command in commands {
+            let parser = self.add_subcommand(command.name(), command.about());
+            command.configure(parser);
+            parser.set_handler(move |matches| command.run(matches));
+        }
+    }
+}
@@ -0,0 +1,1003 @@
+//! Generates shell completion scripts from the declared flags and subcommands and installs them,
+//! see [`crate::Parser::completions`], [`crate::Parser::install_completions`] and
+//! [`crate::Parser::completions_subcommand`], and exports specs for Fig and Carapace.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{lookup_env, value_enum, Flag, FlagEntry, Parser};
+
+/// Name of the subcommand printing completion scripts.
+const SUBCOMMAND: &str = "completions";
+
+value_enum! {
+    /// A shell completion scripts are generated for.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Shell {
+        /// GNU Bash.
+        Bash => "bash",
+        /// The Z shell.
+        Zsh => "zsh",
+        /// The friendly interactive shell.
+        Fish => "fish",
+    }
+}
+
+impl Shell {
+    /// Returns the shell named by the `SHELL` environment variable, if it is supported.
+    pub fn detect() -> Option<Shell> {
+        Self::from_path(&std::env::var("SHELL").ok()?)
+    }
+
+    /// Returns the shell whose executable is at a path such as `/bin/zsh`.
+    fn from_path(path: &str) -> Option<Shell> {
+        Path::new(path).file_name()?.to_str()?.parse().ok()
+    }
+
+    /// Returns where the script of a command is installed, relative to the home directory.
+    fn script_path(self, name: &str) -> PathBuf {
+        match self {
+            Shell::Bash => Path::new(".local/share/bash-completion/completions").join(name),
+            Shell::Zsh => Path::new(".zfunc").join(format!("_{}", name)),
+            Shell::Fish => Path::new(".config/fish/completions").join(format!("{}.fish", name)),
+        }
+    }
+
+    /// Returns the shell config, relative to the home directory, and the line it needs to load
+    /// the script at a path relative to the home directory, if the shell does not load it on its
+    /// own.
+    fn config(self, script: &Path) -> Option<(&'static str, String)> {
+        match self {
+            Shell::Bash => {
+                let script = format!("~/{}", script.display());
+                Some((".bashrc", format!("[ -r {0} ] && . {0}", script)))
+            }
+            Shell::Zsh => Some((".zshrc", "fpath=(~/.zfunc $fpath)".to_string())),
+            Shell::Fish => None,
+        }
+    }
+}
+
+/// Returns the offset of the first line of a zsh config running `compinit`, ignoring comments.
+fn compinit_offset(contents: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        let code = line.trim_start();
+        if !code.starts_with('#') && code.contains("compinit") {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Where [`crate::Parser::install_completions`] wrote a completion script, displayed as a message
+/// telling the user what was done.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Installed {
+    /// The shell the script is for.
+    pub shell: Shell,
+    /// The path of the script.
+    pub script: PathBuf,
+    /// The shell config a line loading the script was added to, if it needed one.
+    pub config: Option<PathBuf>,
+}
+
+impl fmt::Display for Installed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "installed {} completions to {}",
+            self.shell,
+            self.script.display()
+        )?;
+        if let Some(config) = &self.config {
+            write!(f, " and loaded them from {}", config.display())?;
+        }
+        write!(f, ", open a new shell to use them")
+    }
+}
+
+/// A flag or subcommand offered as a completion.
+struct Candidate<'p> {
+    word: String,
+    about: &'p str,
+    /// The values completed after a flag taking one, any value if empty.
+    values: Option<&'p [String]>,
+}
+
+/// Returns the visible flags of a parser, sorted by name, followed by its visible subcommands.
+fn candidates<'p>(parser: &'p Parser<'_>) -> Vec<Candidate<'p>> {
+    let mut candidates = Vec::new();
+    for (key, entry) in parser.visible_flags() {
+        let values = match entry.typ {
+            Flag::Value => Some(entry.choices.as_slice()),
+            Flag::Bool | Flag::TriState | Flag::Count => None,
+        };
+        candidates.push(Candidate {
+            word: format!("-{}", key),
+            about: &entry.usage,
+            values,
+        });
+        if let Flag::TriState = entry.typ {
+            candidates.push(Candidate {
+                word: format!("-no-{}", key),
+                about: &entry.usage,
+                values: None,
+            });
+        }
+    }
+    for sub in parser.subcommands.iter().filter(|sub| !sub.hidden) {
+        candidates.push(Candidate {
+            word: sub.name.clone(),
+            about: &sub.about,
+            values: None,
+        });
+    }
+    candidates
+}
+
+/// Quotes a string as a JSON string, which is also a valid TypeScript and YAML string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Returns the field completing the value of a Fig option or arg, its choices or else paths.
+fn fig_values(entry: &FlagEntry) -> (&'static str, String) {
+    if entry.choices.is_empty() {
+        return ("template", json_string("filepaths"));
+    }
+    let values: Vec<String> = entry.choices.iter().map(|v| json_string(v)).collect();
+    ("suggestions", format!("[{}]", values.join(", ")))
+}
+
+/// Returns whether a flag can be given more than once to add to its value.
+fn repeatable(entry: &FlagEntry) -> bool {
+    entry.multiple || entry.accumulate.is_some() || matches!(entry.typ, Flag::Count)
+}
+
+/// Writes a Fig object with one field per line, its lines indented by `pad`.
+fn write_fig_object(w: &mut impl fmt::Write, pad: &str, fields: &[(&str, String)]) -> fmt::Result {
+    writeln!(w, "{}{{", pad)?;
+    for (field, value) in fields {
+        writeln!(w, "{}  {}: {},", pad, field, value)?;
+    }
+    writeln!(w, "{}}},", pad)
+}
+
+/// Returns the Carapace values completing a flag or positional, its choices or else files.
+fn carapace_values(entry: &FlagEntry) -> String {
+    if entry.choices.is_empty() {
+        return "[\"$files\"]".to_string();
+    }
+    let values: Vec<String> = entry.choices.iter().map(|v| json_string(v)).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// Quotes a word for bash and zsh.
+fn quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// Quotes a word for fish, which escapes quotes and backslashes inside single quotes.
+fn quote_fish(word: &str) -> String {
+    format!("'{}'", word.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+impl<'a> Parser<'a> {
+    /// Returns a completion script for a shell, completing the declared flags, the values of
+    /// flags with [`crate::FlagBuilder::choices`] and the visible subcommands at any depth.
+    ///
+    /// The script completes the command named by the file name of the command, so a parser
+    /// built from `target/debug/tool` completes `tool`. Flags taking a value without choices
+    /// complete file names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::completions::Shell;
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser
+    ///     .add_subcommand("build", "compile the project")
+    ///     .bool_flag("release", "this is used to build with optimizations");
+    ///
+    /// let script = parser.completions(Shell::Bash);
+    /// assert!(script.contains("complete -o default -F _tool 'tool'"));
+    /// ```
+    pub fn completions(&self, shell: Shell) -> String {
+        let mut script = String::new();
+        // Writing into a String never fails.
+        let _ = self.write_completions(shell, &mut script);
+        script
+    }
+
+    /// Writes the completion script of a shell, as returned by [`crate::Parser::completions`],
+    /// into a [`std::fmt::Write`].
+    pub fn write_completions(&self, shell: Shell, w: &mut impl fmt::Write) -> fmt::Result {
+        match shell {
+            Shell::Bash => self.write_bash(w),
+            Shell::Zsh => self.write_zsh(w),
+            Shell::Fish => self.write_fish(w),
+        }
+    }
+
+    /// Writes the completion script of a shell to the standard location in the home directory,
+    /// adding a line loading it to the shell config if the shell needs one, and returns what was
+    /// done.
+    ///
+    /// The shell is detected from the `SHELL` environment variable when none is given, and the
+    /// home directory is read from `HOME`, both looked up with [`crate::Parser::set_env_fn`].
+    /// Scripts go to `~/.local/share/bash-completion/completions` for bash, `~/.zfunc` for zsh,
+    /// which is added to the `fpath` in `~/.zshrc` before its `compinit`, and
+    /// `~/.config/fish/completions` for fish. Bash scripts are also sourced from `~/.bashrc`, so
+    /// they work without the bash-completion package. Installing again overwrites the script and
+    /// leaves the config as it is.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(std::env::args());
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser.add_subcommand("completions", "manage shell completions");
+    ///
+    /// parser.finalize().unwrap();
+    /// if let Some(("completions", _)) = parser.subcommand() {
+    ///     match parser.install_completions(None) {
+    ///         Ok(installed) => println!("{}", installed),
+    ///         Err(e) => eprintln!("error: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn install_completions(&self, shell: Option<Shell>) -> io::Result<Installed> {
+        let detected = || Shell::from_path(&lookup_env(&self.env_fn, "SHELL")?);
+        let shell = shell.or_else(detected).ok_or_else(|| {
+            io::Error::other("could not detect the shell from SHELL, expected bash, zsh or fish")
+        })?;
+        let home = lookup_env(&self.env_fn, "HOME")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?;
+        self.install_completions_in(shell, Path::new(&home))
+    }
+
+    /// Installs the completion script of a shell relative to a home directory.
+    fn install_completions_in(&self, shell: Shell, home: &Path) -> io::Result<Installed> {
+        let relative = shell.script_path(self.bin_name());
+        let script = home.join(&relative);
+        if let Some(dir) = script.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&script, self.completions(shell))?;
+
+        let mut config = None;
+        if let Some((file, line)) = shell.config(&relative) {
+            let path = home.join(file);
+            let contents = match fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e),
+            };
+            if !contents.lines().any(|l| l == line) {
+                let mut block = format!("# {} completions\n{}\n", self.bin_name(), line);
+                let compinit = match shell {
+                    Shell::Zsh => compinit_offset(&contents),
+                    _ => None,
+                };
+                // The fpath must be set before compinit runs, which should only run once.
+                let updated = match compinit {
+                    Some(i) => format!("{}{}{}", &contents[..i], block, &contents[i..]),
+                    None => {
+                        if shell == Shell::Zsh {
+                            block.push_str("autoload -Uz compinit && compinit\n");
+                        }
+                        let mut updated = contents;
+                        if !updated.is_empty() && !updated.ends_with('\n') {
+                            updated.push('\n');
+                        }
+                        updated + &block
+                    }
+                };
+                fs::write(&path, updated)?;
+                config = Some(path);
+            }
+        }
+        Ok(Installed {
+            shell,
+            script,
+            config,
+        })
+    }
+
+    /// Declares a `completions <shell>` subcommand printing the completion script of the shell,
+    /// generated from this parser.
+    ///
+    /// When it is given, [`crate::Parser::finalize`] returns
+    /// [`crate::errors::Error::CompletionsRequested`] without checking the required flags, and
+    /// [`crate::Parser::run`] prints the script to stdout with exit code 0, so a program running
+    /// its parser needs no other code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::completions::Shell;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "completions", "zsh"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser.completions_subcommand();
+    ///
+    /// assert_eq!(Err(Error::CompletionsRequested(Shell::Zsh)), parser.finalize());
+    /// ```
+    pub fn completions_subcommand(&mut self) {
+        self.add_subcommand(SUBCOMMAND, "print the shell completion script")
+            .positional("shell", "the shell to complete, one of bash, zsh or fish")
+            .value_enum::<Shell>();
+        self.completions_command = true;
+    }
+
+    /// Returns whether a subcommand is the one declared with
+    /// [`crate::Parser::completions_subcommand`].
+    pub(crate) fn is_completions_subcommand(&self, i: usize) -> bool {
+        self.completions_command && self.subcommands[i].name == SUBCOMMAND
+    }
+
+    /// Returns the shell given to the subcommand declared with
+    /// [`crate::Parser::completions_subcommand`], if it was given.
+    pub(crate) fn requested_completions(&self) -> Option<Shell> {
+        if !self.completions_command {
+            return None;
+        }
+        match self.subcommand()? {
+            (SUBCOMMAND, sub) => sub.get_value("shell"),
+            _ => None,
+        }
+    }
+
+    /// Returns the name completions are registered for, the file name of the command.
+    fn bin_name(&self) -> &str {
+        Path::new(&*self.command)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.command)
+    }
+
+    /// Returns this parser and the parsers of its visible subcommands at any depth, each with
+    /// the subcommand names leading to it.
+    fn completion_commands(&self) -> Vec<(Vec<&str>, &Parser<'a>)> {
+        let mut commands = vec![(Vec::new(), self)];
+        let mut i = 0;
+        while let Some((path, parser)) = commands.get(i).cloned() {
+            for sub in parser.subcommands.iter().filter(|sub| !sub.hidden) {
+                let mut path = path.clone();
+                path.push(sub.name.as_str());
+                commands.push((path, &sub.parser));
+            }
+            i += 1;
+        }
+        commands
+    }
+
+    /// Writes the names of subcommands leading to a parser, starting with the command name.
+    fn completion_key(&self, path: &[&str]) -> String {
+        std::iter::once(self.bin_name())
+            .chain(path.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns the function name used by bash and zsh scripts.
+    fn completion_function(&self) -> String {
+        let name: String = self
+            .bin_name()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("_{}", name)
+    }
+
+    /// Writes the loop finding the subcommand being completed into `$cmd`, over the words
+    /// before the current one.
+    fn write_subcommand_loop(
+        &self,
+        w: &mut impl fmt::Write,
+        commands: &[(Vec<&str>, &Parser<'a>)],
+        range: &str,
+        word: &str,
+    ) -> fmt::Result {
+        let keys: Vec<String> = commands[1..]
+            .iter()
+            .map(|(path, _)| quote(&self.completion_key(path)))
+            .collect();
+        if keys.is_empty() {
+            return Ok(());
+        }
+        writeln!(w, "    for ({}); do", range)?;
+        writeln!(w, "        case \"$cmd {}\" in", word)?;
+        writeln!(
+            w,
+            "            {}) cmd=\"$cmd {}\" ;;",
+            keys.join("|"),
+            word
+        )?;
+        writeln!(w, "        esac")?;
+        writeln!(w, "    done")
+    }
+
+    fn write_bash(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        let function = self.completion_function();
+        let commands = self.completion_commands();
+        writeln!(w, "{}() {{", function)?;
+        writeln!(w, "    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+        writeln!(w, "    local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"")?;
+        writeln!(w, "    local cmd={} opts i", quote(self.bin_name()))?;
+        self.write_subcommand_loop(
+            w,
+            &commands,
+            "(i = 1; i < COMP_CWORD; i++)",
+            "${COMP_WORDS[i]}",
+        )?;
+        writeln!(w, "    case \"$cmd\" in")?;
+        for (path, parser) in &commands {
+            let candidates = candidates(parser);
+            writeln!(w, "        {})", quote(&self.completion_key(path)))?;
+            writeln!(w, "            case \"$prev\" in")?;
+            for c in &candidates {
+                match c.values {
+                    Some([]) => writeln!(
+                        w,
+                        "                {}) COMPREPLY=(); return ;;",
+                        quote(&c.word)
+                    )?,
+                    Some(values) => writeln!(
+                        w,
+                        "                {}) COMPREPLY=($(compgen -W {} -- \"$cur\")); return ;;",
+                        quote(&c.word),
+                        quote(&values.join(" "))
+                    )?,
+                    None => {}
+                }
+            }
+            writeln!(w, "            esac")?;
+            let words: Vec<&str> = candidates.iter().map(|c| c.word.as_str()).collect();
+            writeln!(w, "            opts={}", quote(&words.join(" ")))?;
+            writeln!(w, "            ;;")?;
+        }
+        writeln!(w, "    esac")?;
+        writeln!(w, "    COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))")?;
+        writeln!(w, "}}")?;
+        writeln!(
+            w,
+            "complete -o default -F {} {}",
+            function,
+            quote(self.bin_name())
+        )
+    }
+
+    fn write_zsh(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        let function = self.completion_function();
+        let commands = self.completion_commands();
+        writeln!(w, "#compdef {}", self.bin_name())?;
+        writeln!(w)?;
+        writeln!(w, "{}() {{", function)?;
+        writeln!(w, "    local cmd={} i", quote(self.bin_name()))?;
+        writeln!(w, "    local -a opts")?;
+        self.write_subcommand_loop(w, &commands, "(i = 2; i < CURRENT; i++)", "${words[i]}")?;
+        writeln!(w, "    case \"$cmd\" in")?;
+        for (path, parser) in &commands {
+            let candidates = candidates(parser);
+            writeln!(w, "        {})", quote(&self.completion_key(path)))?;
+            writeln!(w, "            case \"${{words[CURRENT-1]}}\" in")?;
+            for c in &candidates {
+                match c.values {
+                    Some([]) => {
+                        writeln!(w, "                {}) _files; return ;;", quote(&c.word))?
+                    }
+                    Some(values) => {
+                        let values: Vec<String> = values.iter().map(|v| quote(v)).collect();
+                        writeln!(
+                            w,
+                            "                {}) compadd -- {}; return ;;",
+                            quote(&c.word),
+                            values.join(" ")
+                        )?
+                    }
+                    None => {}
+                }
+            }
+            writeln!(w, "            esac")?;
+            let specs: Vec<String> = candidates
+                .iter()
+                .map(|c| quote(&format!("{}:{}", c.word.replace(':', r"\:"), c.about)))
+                .collect();
+            writeln!(w, "            opts=({})", specs.join(" "))?;
+            writeln!(w, "            ;;")?;
+        }
+        writeln!(w, "    esac")?;
+        writeln!(w, "    _describe 'option' opts || _files")?;
+        writeln!(w, "}}")?;
+        writeln!(w)?;
+        writeln!(w, "if [ \"$funcstack[1]\" = {} ]; then", quote(&function))?;
+        writeln!(w, "    {} \"$@\"", function)?;
+        writeln!(w, "else")?;
+        writeln!(w, "    compdef {} {}", function, quote(self.bin_name()))?;
+        writeln!(w, "fi")
+    }
+
+    fn write_fish(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        let name = quote_fish(self.bin_name());
+        for (path, parser) in self.completion_commands() {
+            let mut conditions: Vec<String> = path
+                .iter()
+                .map(|sub| format!("__fish_seen_subcommand_from {}", quote_fish(sub)))
+                .collect();
+            let subcommands: Vec<String> = parser
+                .subcommands
+                .iter()
+                .filter(|sub| !sub.hidden)
+                .map(|sub| quote_fish(&sub.name))
+                .collect();
+            if !subcommands.is_empty() {
+                conditions.push(format!(
+                    "not __fish_seen_subcommand_from {}",
+                    subcommands.join(" ")
+                ));
+            }
+            let condition = if conditions.is_empty() {
+                String::new()
+            } else {
+                format!(" -n {}", quote_fish(&conditions.join("; and ")))
+            };
+
+            for c in candidates(parser) {
+                write!(w, "complete -c {}{}", name, condition)?;
+                match c.word.strip_prefix('-') {
+                    Some(flag) => write!(w, " -o {}", quote_fish(flag))?,
+                    None => write!(w, " -f -a {}", quote_fish(&c.word))?,
+                }
+                match c.values {
+                    Some([]) => write!(w, " -r")?,
+                    Some(values) => write!(w, " -x -a {}", quote_fish(&values.join(" ")))?,
+                    None => {}
+                }
+                writeln!(w, " -d {}", quote_fish(c.about))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a Fig autocomplete spec, a TypeScript module exporting the declared flags,
+    /// positionals and visible subcommands at any depth with their descriptions.
+    ///
+    /// Flags taking a value suggest their choices, see [`crate::FlagBuilder::choices`], or else
+    /// file paths. The spec is named after the file name of the command, like the scripts of
+    /// [`crate::Parser::completions`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    ///
+    /// assert_eq!(
+    ///     parser.fig_spec(),
+    ///     [
+    ///         "const completionSpec: Fig.Spec = {\n",
+    ///         "  name: \"tool\",\n",
+    ///         "  options: [\n",
+    ///         "    {\n",
+    ///         "      name: \"-verbose\",\n",
+    ///         "      description: \"this is used to get verbose output\",\n",
+    ///         "    },\n",
+    ///         "  ],\n",
+    ///         "};\n",
+    ///         "\n",
+    ///         "export default completionSpec;\n",
+    ///     ]
+    ///     .concat()
+    /// );
+    /// ```
+    pub fn fig_spec(&self) -> String {
+        let mut spec = String::new();
+        // Writing into a String never fails.
+        let _ = self.write_fig_spec(&mut spec);
+        spec
+    }
+
+    /// Writes the Fig autocomplete spec, as returned by [`crate::Parser::fig_spec`], into a
+    /// [`std::fmt::Write`].
+    pub fn write_fig_spec(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        write!(w, "const completionSpec: Fig.Spec = ")?;
+        self.write_fig_command(w, self.bin_name(), self.spec_about(), "")?;
+        writeln!(w, ";")?;
+        writeln!(w)?;
+        writeln!(w, "export default completionSpec;")
+    }
+
+    /// Writes the object describing a command, its lines after the first indented by `pad`.
+    fn write_fig_command(
+        &self,
+        w: &mut impl fmt::Write,
+        name: &str,
+        about: Option<&str>,
+        pad: &str,
+    ) -> fmt::Result {
+        writeln!(w, "{{")?;
+        writeln!(w, "{}  name: {},", pad, json_string(name))?;
+        if let Some(about) = about {
+            writeln!(w, "{}  description: {},", pad, json_string(about))?;
+        }
+
+        let subcommands: Vec<_> = self.subcommands.iter().filter(|sub| !sub.hidden).collect();
+        if !subcommands.is_empty() {
+            writeln!(w, "{}  subcommands: [", pad)?;
+            for sub in subcommands {
+                write!(w, "{}    ", pad)?;
+                let pad = format!("{}    ", pad);
+                sub.parser
+                    .write_fig_command(w, &sub.name, Some(&sub.about), &pad)?;
+                writeln!(w, ",")?;
+            }
+            writeln!(w, "{}  ],", pad)?;
+        }
+
+        let item = format!("{}    ", pad);
+        let flags = self.visible_flags();
+        if !flags.is_empty() {
+            writeln!(w, "{}  options: [", pad)?;
+            for (key, entry) in flags {
+                let mut fields = vec![
+                    ("name", json_string(&format!("-{}", key))),
+                    ("description", json_string(&entry.usage)),
+                ];
+                if repeatable(entry) {
+                    fields.push(("isRepeatable", "true".to_string()));
+                }
+                if self.required.contains(key) {
+                    fields.push(("isRequired", "true".to_string()));
+                }
+                if let Flag::Value = entry.typ {
+                    let (field, values) = fig_values(entry);
+                    let args = format!("{{ name: \"value\", {}: {} }}", field, values);
+                    fields.push(("args", args));
+                }
+                write_fig_object(w, &item, &fields)?;
+                if let Flag::TriState = entry.typ {
+                    let fields = [
+                        ("name", json_string(&format!("-no-{}", key))),
+                        ("description", json_string(&entry.usage)),
+                    ];
+                    write_fig_object(w, &item, &fields)?;
+                }
+            }
+            writeln!(w, "{}  ],", pad)?;
+        }
+
+        if !self.declared_positionals.is_empty() {
+            writeln!(w, "{}  args: [", pad)?;
+            for (name, entry) in self.declared_positionals.iter() {
+                let mut fields = vec![
+                    ("name", json_string(name)),
+                    ("description", json_string(&entry.usage)),
+                ];
+                if entry.min_occurrences == 0 {
+                    fields.push(("isOptional", "true".to_string()));
+                }
+                if entry.multiple {
+                    fields.push(("isVariadic", "true".to_string()));
+                }
+                fields.push(fig_values(entry));
+                write_fig_object(w, &item, &fields)?;
+            }
+            writeln!(w, "{}  ],", pad)?;
+        }
+        write!(w, "{}}}", pad)
+    }
+
+    /// Returns a Carapace spec, a YAML document with the declared flags, positionals and visible
+    /// subcommands at any depth with their descriptions.
+    ///
+    /// Flags are written with a single dash, which Carapace reads as non-POSIX long flags, and
+    /// flags taking a value complete their choices, see [`crate::FlagBuilder::choices`], or else
+    /// files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// parser
+    ///     .required_flag("level", "this is used to set the log level")
+    ///     .choices(&["debug", "info"]);
+    ///
+    /// assert_eq!(
+    ///     parser.carapace_spec(),
+    ///     [
+    ///         "name: \"tool\"\n",
+    ///         "flags:\n",
+    ///         "  \"-level=!\": \"this is used to set the log level\"\n",
+    ///         "completion:\n",
+    ///         "  flag:\n",
+    ///         "    \"level\": [\"debug\", \"info\"]\n",
+    ///     ]
+    ///     .concat()
+    /// );
+    /// ```
+    pub fn carapace_spec(&self) -> String {
+        let mut spec = String::new();
+        // Writing into a String never fails.
+        let _ = self.write_carapace_spec(&mut spec);
+        spec
+    }
+
+    /// Writes the Carapace spec, as returned by [`crate::Parser::carapace_spec`], into a
+    /// [`std::fmt::Write`].
+    pub fn write_carapace_spec(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        self.write_carapace_command(w, self.bin_name(), self.spec_about(), "", "")
+    }
+
+    /// Writes the mapping describing a command, its first line prefixed with `first` and the
+    /// others indented by `pad`.
+    fn write_carapace_command(
+        &self,
+        w: &mut impl fmt::Write,
+        name: &str,
+        about: Option<&str>,
+        first: &str,
+        pad: &str,
+    ) -> fmt::Result {
+        writeln!(w, "{}name: {}", first, json_string(name))?;
+        if let Some(about) = about {
+            writeln!(w, "{}description: {}", pad, json_string(about))?;
+        }
+
+        let mut flag_values = Vec::new();
+        let flags = self.visible_flags();
+        if !flags.is_empty() {
+            writeln!(w, "{}flags:", pad)?;
+            for (key, entry) in flags {
+                let mut spec = format!("-{}", key);
+                if let Flag::Value = entry.typ {
+                    spec.push('=');
+                    flag_values.push((key, carapace_values(entry)));
+                }
+                if repeatable(entry) {
+                    spec.push('*');
+                }
+                if self.required.contains(key) {
+                    spec.push('!');
+                }
+                let usage = json_string(&entry.usage);
+                writeln!(w, "{}  {}: {}", pad, json_string(&spec), usage)?;
+                if let Flag::TriState = entry.typ {
+                    let spec = json_string(&format!("-no-{}", key));
+                    writeln!(w, "{}  {}: {}", pad, spec, usage)?;
+                }
+            }
+        }
+
+        let positionals: Vec<_> = self.declared_positionals.iter().collect();
+        if !flag_values.is_empty() || !positionals.is_empty() {
+            writeln!(w, "{}completion:", pad)?;
+        }
+        if !flag_values.is_empty() {
+            writeln!(w, "{}  flag:", pad)?;
+            for (key, values) in flag_values {
+                writeln!(w, "{}    {}: {}", pad, json_string(key), values)?;
+            }
+        }
+        if !positionals.is_empty() {
+            writeln!(w, "{}  positional:", pad)?;
+            for (_, entry) in &positionals {
+                writeln!(w, "{}    - {}", pad, carapace_values(entry))?;
+            }
+            if let Some((_, entry)) = positionals.last().filter(|(_, entry)| entry.multiple) {
+                writeln!(w, "{}  positionalany: {}", pad, carapace_values(entry))?;
+            }
+        }
+
+        let subcommands: Vec<_> = self.subcommands.iter().filter(|sub| !sub.hidden).collect();
+        if !subcommands.is_empty() {
+            writeln!(w, "{}commands:", pad)?;
+            for sub in subcommands {
+                let first = format!("{}  - ", pad);
+                let pad = format!("{}    ", pad);
+                sub.parser
+                    .write_carapace_command(w, &sub.name, Some(&sub.about), &first, &pad)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the description of the command, set with [`crate::Parser::set_about`].
+    #[cfg(feature = "help")]
+    fn spec_about(&self) -> Option<&str> {
+        self.about.as_deref()
+    }
+
+    /// Returns no description, as there is none without the `help` feature.
+    #[cfg(not(feature = "help"))]
+    fn spec_about(&self) -> Option<&str> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::errors::Error;
+
+    fn parser() -> Parser<'static> {
+        let mut parser = Parser::from_iter(["target/debug/tool"]);
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser
+            .optional_flag("level", "this is used to set the log level")
+            .choices(&["debug", "info"]);
+        let build = parser.add_subcommand("build", "compile the project");
+        build.optional_flag("config", "this is used to set the config file");
+        parser
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(Shell::from_path("/usr/bin/zsh"), Some(Shell::Zsh));
+        assert_eq!(Shell::from_path("fish"), Some(Shell::Fish));
+        assert_eq!(Shell::from_path("/bin/tcsh"), None);
+    }
+
+    #[test]
+    fn scripts() {
+        let parser = parser();
+
+        let bash = parser.completions(Shell::Bash);
+        assert!(bash.contains("            'tool build') cmd=\"$cmd ${COMP_WORDS[i]}\" ;;\n"));
+        assert!(bash.contains("                '-level') COMPREPLY=($(compgen -W 'debug info' -- \"$cur\")); return ;;\n"));
+        assert!(bash.contains("            opts='-level -verbose build'\n"));
+        assert!(bash.contains("                '-config') COMPREPLY=(); return ;;\n"));
+        assert!(bash.ends_with("complete -o default -F _tool 'tool'\n"));
+
+        let zsh = parser.completions(Shell::Zsh);
+        assert!(zsh.starts_with("#compdef tool\n"));
+        assert!(zsh.contains("'-level') compadd -- 'debug' 'info'; return ;;"));
+        assert!(zsh.contains("opts=('-config:this is used to set the config file')"));
+
+        let fish = parser.completions(Shell::Fish);
+        assert!(fish.contains("complete -c 'tool' -n 'not __fish_seen_subcommand_from \\'build\\'' -o 'level' -x -a 'debug info' -d 'this is used to set the log level'\n"));
+        assert!(fish.contains("complete -c 'tool' -n '__fish_seen_subcommand_from \\'build\\'' -o 'config' -r -d 'this is used to set the config file'\n"));
+    }
+
+    #[test]
+    fn specs() {
+        let mut parser = parser();
+        parser.tristate_flag("color", "this is used to color \"output\"");
+        parser
+            .optional_positional("dir", "the directory to serve")
+            .multiple();
+
+        let fig = parser.fig_spec();
+        assert!(fig.contains("      name: \"-no-color\",\n      description: \"this is used to color \\\"output\\\"\",\n"));
+        assert!(
+            fig.contains("      args: { name: \"value\", suggestions: [\"debug\", \"info\"] },\n")
+        );
+        assert!(fig.contains("      name: \"build\",\n      description: \"compile the project\",\n      options: [\n"));
+        assert!(fig.contains("          args: { name: \"value\", template: \"filepaths\" },\n"));
+        assert!(fig.contains(
+            "      isOptional: true,\n      isVariadic: true,\n      template: \"filepaths\",\n"
+        ));
+
+        let carapace = parser.carapace_spec();
+        assert!(carapace.contains("  \"-color\": \"this is used to color \\\"output\\\"\"\n"));
+        assert!(carapace.contains("    \"level\": [\"debug\", \"info\"]\n"));
+        assert!(
+            carapace.contains("  positional:\n    - [\"$files\"]\n  positionalany: [\"$files\"]\n")
+        );
+        assert!(carapace.contains(
+            "commands:\n  - name: \"build\"\n    description: \"compile the project\"\n"
+        ));
+
+        #[cfg(feature = "serde_yaml")]
+        {
+            let spec: serde_yaml::Value = serde_yaml::from_str(&carapace).unwrap();
+            assert_eq!(
+                spec["commands"][0]["flags"]["-config="],
+                "this is used to set the config file"
+            );
+        }
+    }
+
+    #[test]
+    fn subcommand() {
+        let mut parser = Parser::from_iter(["tool", "completions", "fish"]);
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.completions_subcommand();
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        assert_eq!(parser.run_with(&mut out, &mut err), 0);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            parser.completions(Shell::Fish)
+        );
+        assert!(err.is_empty());
+
+        let mut parser = Parser::from_iter(["tool", "completions", "tcsh"]);
+        parser.completions_subcommand();
+        assert!(parser.finalize().is_err());
+
+        let mut parser = Parser::from_iter(["tool", "completions", "zsh"]);
+        parser.required_flag("token", "this is used to authenticate");
+        parser.completions_subcommand();
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::CompletionsRequested(Shell::Zsh))
+        );
+    }
+
+    #[test]
+    fn install() {
+        let home = std::env::temp_dir().join(format!("yafp-completions-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        fs::write(home.join(".zshrc"), "export EDITOR=vi").unwrap();
+        let parser = parser();
+
+        let installed = parser.install_completions_in(Shell::Zsh, &home).unwrap();
+        assert_eq!(installed.script, home.join(".zfunc/_tool"));
+        assert_eq!(installed.config, Some(home.join(".zshrc")));
+        assert_eq!(
+            fs::read_to_string(&installed.script).unwrap(),
+            parser.completions(Shell::Zsh)
+        );
+        let zshrc = fs::read_to_string(home.join(".zshrc")).unwrap();
+        assert_eq!(
+            zshrc,
+            "export EDITOR=vi\n# tool completions\nfpath=(~/.zfunc $fpath)\nautoload -Uz compinit && compinit\n"
+        );
+
+        // Installing again leaves the config as it is.
+        let installed = parser.install_completions_in(Shell::Zsh, &home).unwrap();
+        assert_eq!(installed.config, None);
+        assert_eq!(fs::read_to_string(home.join(".zshrc")).unwrap(), zshrc);
+
+        // The fpath is set before a compinit the config already runs.
+        fs::write(
+            home.join(".zshrc"),
+            "# run compinit\nautoload -Uz compinit\ncompinit\n",
+        )
+        .unwrap();
+        parser.install_completions_in(Shell::Zsh, &home).unwrap();
+        assert_eq!(
+            fs::read_to_string(home.join(".zshrc")).unwrap(),
+            "# run compinit\n# tool completions\nfpath=(~/.zfunc $fpath)\nautoload -Uz compinit\ncompinit\n"
+        );
+
+        let installed = parser.install_completions_in(Shell::Fish, &home).unwrap();
+        assert_eq!(
+            installed.to_string(),
+            format!(
+                "installed fish completions to {}, open a new shell to use them",
+                home.join(".config/fish/completions/tool.fish").display()
+            )
+        );
+        fs::remove_dir_all(&home).unwrap();
+    }
+}
@@ -0,0 +1,351 @@
+//! Parses configuration files into values for the configuration layer.
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+
+use crate::ConfigFormat;
+
+/// The file extensions of the enabled formats, in the order they are searched.
+const EXTENSIONS: &[&str] = &[
+    #[cfg(feature = "ini")]
+    "ini",
+    #[cfg(feature = "toml")]
+    "toml",
+    #[cfg(feature = "serde_yaml")]
+    "yaml",
+    #[cfg(feature = "serde_yaml")]
+    "yml",
+];
+
+/// Returns the paths searched for the configuration file of an application, in order.
+///
+/// A `config` file with any enabled extension is searched in the `app` directory of
+/// `$XDG_CONFIG_HOME`, `~/.config`, the platform configuration directory and each of
+/// `$XDG_CONFIG_DIRS`, followed by an `app` file in the current directory. Variables are looked
+/// up with `env`.
+pub(crate) fn search_paths(app: &str, env: &dyn Fn(&str) -> Option<String>) -> Vec<PathBuf> {
+    let home = env("HOME")
+        .or_else(|| env("USERPROFILE"))
+        .map(PathBuf::from);
+
+    let mut dirs = Vec::new();
+    if let Some(dir) = env("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = &home {
+        dirs.push(home.join(".config"));
+    }
+    #[cfg(target_os = "macos")]
+    if let Some(home) = &home {
+        dirs.push(home.join("Library").join("Application Support"));
+    }
+    #[cfg(windows)]
+    if let Some(dir) = env("APPDATA") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(system) = env("XDG_CONFIG_DIRS") {
+        dirs.extend(std::env::split_paths(&system).filter(|dir| !dir.as_os_str().is_empty()));
+    }
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        let dir = dir.join(app);
+        paths.extend(
+            EXTENSIONS
+                .iter()
+                .map(|ext| dir.join(format!("config.{}", ext))),
+        );
+    }
+    paths.extend(
+        EXTENSIONS
+            .iter()
+            .map(|ext| PathBuf::from(format!("{}.{}", app, ext))),
+    );
+    paths
+}
+
+/// Parses the contents of a configuration file into a list of flag values.
+///
+/// Keys of nested tables are joined with a `.`, lists are joined with a `,` and other values are
+/// used as written.
+pub(crate) fn parse(contents: &str, format: ConfigFormat) -> Result<Vec<(String, String)>> {
+    let mut values = Vec::new();
+    match format {
+        #[cfg(feature = "ini")]
+        ConfigFormat::Ini => parse_ini(contents, &mut values)?,
+        #[cfg(feature = "toml")]
+        ConfigFormat::Toml => {
+            let table: toml::Table = contents
+                .parse()
+                .map_err(|e: toml::de::Error| Error::new(ErrorKind::InvalidData, e.message()))?;
+            flatten_toml("", &toml::Value::Table(table), &mut values)?;
+        }
+        #[cfg(feature = "serde_yaml")]
+        ConfigFormat::Yaml => {
+            let value: serde_yaml::Value = serde_yaml::from_str(contents)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+            match value {
+                serde_yaml::Value::Null => {}
+                serde_yaml::Value::Mapping(_) => flatten_yaml("", &value, &mut values)?,
+                _ => return Err(invalid("the document is not a mapping")),
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Writes a commented configuration entry for a flag in a format, commenting out the key too if
+/// there is no default to write.
+pub(crate) fn write_entry(
+    out: &mut String,
+    format: ConfigFormat,
+    key: &str,
+    usage: &str,
+    default: Option<&str>,
+) {
+    let comment = match format {
+        #[cfg(feature = "ini")]
+        ConfigFormat::Ini => ";",
+        #[cfg(feature = "toml")]
+        ConfigFormat::Toml => "#",
+        #[cfg(feature = "serde_yaml")]
+        ConfigFormat::Yaml => "#",
+    };
+    let line = match format {
+        #[cfg(feature = "ini")]
+        ConfigFormat::Ini => format!("{} = {}", key, default.unwrap_or_default()),
+        #[cfg(feature = "toml")]
+        ConfigFormat::Toml => format!("{} = {}", key, quote(default.unwrap_or_default())),
+        #[cfg(feature = "serde_yaml")]
+        ConfigFormat::Yaml => format!("{}: {}", key, quote(default.unwrap_or_default())),
+    };
+
+    out.push_str(&format!("{} {}\n", comment, usage));
+    match default {
+        Some(_) => out.push_str(&format!("{}\n", line)),
+        None => out.push_str(&format!("{} {}\n", comment, line)),
+    }
+}
+
+/// Quotes a value as a double quoted string, valid in both TOML and YAML.
+#[cfg(any(feature = "toml", feature = "serde_yaml"))]
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Returns an invalid data error with a reason.
+fn invalid(reason: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, reason.to_string())
+}
+
+/// Joins a nested key onto its parent key.
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Parses an INI file, where keys in a `[section]` are prefixed with the section name.
+///
+/// Lines starting with `;` or `#` are ignored and values may be wrapped in single or double quotes.
+#[cfg(feature = "ini")]
+fn parse_ini(contents: &str, values: &mut Vec<(String, String)>) -> Result<()> {
+    let mut section = String::new();
+    for (n, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            section = name
+                .strip_suffix(']')
+                .ok_or_else(|| invalid(&format!("line {} has an unclosed section", n + 1)))?
+                .trim()
+                .to_string();
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| invalid(&format!("line {} is not a key = value pair", n + 1)))?;
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('\'' | '"')) if value.len() > 1 && value.ends_with(quote) => {
+                &value[1..value.len() - 1]
+            }
+            _ => value,
+        };
+        values.push((join_key(&section, key.trim()), value.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "toml")]
+fn flatten_toml(key: &str, value: &toml::Value, values: &mut Vec<(String, String)>) -> Result<()> {
+    match value {
+        toml::Value::Table(table) => {
+            for (k, v) in table {
+                flatten_toml(&join_key(key, k), v, values)?;
+            }
+        }
+        toml::Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(toml_scalar)
+                .collect::<Option<Vec<String>>>()
+                .ok_or_else(|| invalid(&format!("key '{}' has a nested list or table", key)))?;
+            values.push((key.to_string(), items.join(",")));
+        }
+        _ => values.push((key.to_string(), toml_scalar(value).unwrap_or_default())),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "toml")]
+fn toml_scalar(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Integer(n) => Some(n.to_string()),
+        toml::Value::Float(n) => Some(n.to_string()),
+        toml::Value::Boolean(b) => Some(b.to_string()),
+        toml::Value::Datetime(d) => Some(d.to_string()),
+        toml::Value::Array(_) | toml::Value::Table(_) => None,
+    }
+}
+
+#[cfg(feature = "serde_yaml")]
+fn flatten_yaml(
+    key: &str,
+    value: &serde_yaml::Value,
+    values: &mut Vec<(String, String)>,
+) -> Result<()> {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            for (k, v) in mapping {
+                let k = yaml_scalar(k).ok_or_else(|| {
+                    invalid(&format!("key '{}' has a key which is not a scalar", key))
+                })?;
+                flatten_yaml(&join_key(key, &k), v, values)?;
+            }
+        }
+        serde_yaml::Value::Sequence(items) => {
+            let items = items
+                .iter()
+                .map(yaml_scalar)
+                .collect::<Option<Vec<String>>>()
+                .ok_or_else(|| invalid(&format!("key '{}' has a nested list or mapping", key)))?;
+            values.push((key.to_string(), items.join(",")));
+        }
+        serde_yaml::Value::Null => {}
+        serde_yaml::Value::Tagged(tagged) => flatten_yaml(key, &tagged.value, values)?,
+        _ => values.push((key.to_string(), yaml_scalar(value).unwrap_or_default())),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde_yaml")]
+fn yaml_scalar(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        serde_yaml::Value::Tagged(tagged) => yaml_scalar(&tagged.value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn dump_round_trip() {
+        let mut out = String::new();
+        write_entry(
+            &mut out,
+            ConfigFormat::Toml,
+            "name",
+            "the name",
+            Some("say \"hi\""),
+        );
+        write_entry(&mut out, ConfigFormat::Toml, "lines", "the lines", None);
+        assert_eq!(
+            out,
+            "# the name\nname = \"say \\\"hi\\\"\"\n# the lines\n# lines = \"\"\n"
+        );
+        assert_eq!(
+            parse(&out, ConfigFormat::Toml).unwrap(),
+            vec![("name".to_string(), "say \"hi\"".to_string())]
+        );
+    }
+
+    #[test]
+    fn search_order() {
+        let env = |name: &str| (name == "XDG_CONFIG_HOME").then(|| "/xdg".to_string());
+        let paths = search_paths("yafp-search", &env);
+        let first = PathBuf::from("/xdg/yafp-search").join(format!("config.{}", EXTENSIONS[0]));
+        assert_eq!(paths[0], first);
+        let last = format!("yafp-search.{}", EXTENSIONS[EXTENSIONS.len() - 1]);
+        assert_eq!(paths[paths.len() - 1], PathBuf::from(last));
+    }
+
+    #[test]
+    #[cfg(feature = "ini")]
+    fn ini_values() {
+        let contents = "; comment\nlines = 20\nname = \"my head\"\n\n[server]\nport=8080\n";
+        let values = parse(contents, ConfigFormat::Ini).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                ("lines".to_string(), "20".to_string()),
+                ("name".to_string(), "my head".to_string()),
+                ("server.port".to_string(), "8080".to_string()),
+            ]
+        );
+        assert!(parse("[server\n", ConfigFormat::Ini).is_err());
+        assert!(parse("lines\n", ConfigFormat::Ini).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn toml_values() {
+        let contents = "lines = 20\nverbose = true\nname = \"head\"\ntags = [\"a\", \"b\"]\n\n[server]\nport = 8080\n";
+        let mut values = parse(contents, ConfigFormat::Toml).unwrap();
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                ("lines".to_string(), "20".to_string()),
+                ("name".to_string(), "head".to_string()),
+                ("server.port".to_string(), "8080".to_string()),
+                ("tags".to_string(), "a,b".to_string()),
+                ("verbose".to_string(), "true".to_string()),
+            ]
+        );
+        assert!(parse("lines = ", ConfigFormat::Toml).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde_yaml")]
+    fn yaml_values() {
+        let contents =
+            "lines: 20\nverbose: true\nname: head\ntags: [a, b]\nserver:\n  port: 8080\nunset:\n";
+        let values = parse(contents, ConfigFormat::Yaml).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                ("lines".to_string(), "20".to_string()),
+                ("verbose".to_string(), "true".to_string()),
+                ("name".to_string(), "head".to_string()),
+                ("tags".to_string(), "a,b".to_string()),
+                ("server.port".to_string(), "8080".to_string()),
+            ]
+        );
+        assert!(parse("- a\n- b\n", ConfigFormat::Yaml).is_err());
+        assert!(parse("", ConfigFormat::Yaml).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+//! Parses `.env` files used as an environment fallback.
+use std::io::{Error, ErrorKind, Result};
+
+/// Parses the contents of a `.env` file into a list of variables.
+///
+/// Each line holds a `KEY=VALUE` pair optionally prefixed by `export`, values may be wrapped in
+/// single or double quotes and lines starting with `#` are ignored.
+pub(crate) fn parse(contents: &str) -> Result<Vec<(String, String)>> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    for (n, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("line {} is not a KEY=VALUE pair", n + 1),
+                ))
+            }
+        };
+
+        let value = match value.chars().next() {
+            Some(quote @ ('\'' | '"')) if value.len() > 1 && value.ends_with(quote) => {
+                let inner = &value[1..value.len() - 1];
+                if quote == '"' {
+                    inner.replace("\\n", "\n").replace("\\\"", "\"")
+                } else {
+                    inner.to_string()
+                }
+            }
+            // Strip trailing comments from unquoted values.
+            _ => match value.find(" #") {
+                Some(i) => value[..i].trim_end().to_string(),
+                None => value.to_string(),
+            },
+        };
+        vars.push((key.to_string(), value));
+    }
+    Ok(vars)
+}
+
+/// Returns the names of the `.env` files loaded for a mode, from the lowest precedence up.
+///
+/// This follows the layering used by dotenv tooling: `.env`, `.env.local`, `.env.<mode>` and
+/// `.env.<mode>.local`, where files for a mode override the shared ones and `.local` files, meant
+/// to be kept out of version control, override the committed ones.
+pub(crate) fn layers(mode: Option<&str>) -> Vec<String> {
+    let mut names = vec![".env".to_string(), ".env.local".to_string()];
+    if let Some(mode) = mode {
+        names.push(format!(".env.{}", mode));
+        names.push(format!(".env.{}.local", mode));
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn pairs() {
+        let contents = "# comment\nexport A=1\nB = 'two words'\nC=\"say \\\"hi\\\"\"\nD=4 # four\n";
+        let vars = parse(contents).unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "two words".to_string()),
+                ("C".to_string(), "say \"hi\"".to_string()),
+                ("D".to_string(), "4".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_line() {
+        let result = parse("A=1\nnot a pair\n");
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn layer_order() {
+        assert_eq!(layers(None), vec![".env", ".env.local"]);
+        assert_eq!(
+            layers(Some("test")),
+            vec![".env", ".env.local", ".env.test", ".env.test.local"]
+        );
+    }
+}
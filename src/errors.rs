@@ -1,14 +1,100 @@
 //! Defines errors encountered when parsing.
 use std::fmt::{self, Display};
 
+#[cfg(feature = "completions")]
+use crate::completions::Shell;
+
 /// A list of possible errors.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Error {
     /// A missing required argument.
     MissingArgument(String),
 
+    /// A missing argument which is required when another argument has a value, with that
+    /// argument and value.
+    MissingArgumentIf(String, String, String),
+
+    /// A missing argument which is required unless one of the listed arguments is given.
+    MissingArgumentUnless(String, Vec<String>),
+
+    /// An argument given fewer times than it requires, with the count and the minimum.
+    TooFewOccurrences(String, usize, usize),
+
+    /// An argument given more times than it allows, with the count and the maximum.
+    TooManyOccurrences(String, usize, usize),
+
+    /// A missing positional argument declared with [`crate::Parser::positional`].
+    MissingPositional(String),
+
+    /// More positional arguments than declared with [`crate::Parser::positional`], with the
+    /// number declared and the number given.
+    TooManyPositionals(usize, usize),
+
+    /// A positional argument that failed the checks declared on it, with its usage and the
+    /// reason it is invalid.
+    InvalidPositional(String, String, String),
+
     /// A missing value to an argument.
     MissingValue(String),
+
+    /// A flag that requires confirmation was not confirmed.
+    NotConfirmed(String),
+
+    /// An argument that does not match any declared flag.
+    UnknownFlag(String),
+
+    /// A positional argument given to a parser set to deny them with
+    /// [`crate::Parser::deny_positionals`].
+    UnexpectedArgument(String),
+
+    /// A first positional argument that does not match any declared subcommand, with the closest
+    /// subcommand name if it looks like a typo.
+    UnknownSubcommand(String, Option<String>),
+
+    /// A configuration key that does not match any declared flag, with the closest flag name if
+    /// it looks like a typo.
+    UnknownConfigKey(String, Option<String>),
+
+    /// The help flag was given.
+    HelpRequested,
+
+    /// The version flag was given on a parser with a version.
+    VersionRequested,
+
+    /// The subcommand declared with [`crate::Parser::completions_subcommand`] was given, with
+    /// the shell to print the completion script of.
+    #[cfg(feature = "completions")]
+    CompletionsRequested(Shell),
+
+    /// More args than allowed by [`crate::args::Limits::max_args`], with the maximum.
+    TooManyArgs(usize),
+
+    /// An arg longer than allowed by [`crate::args::Limits::max_arg_len`], with the maximum.
+    ArgTooLong(usize),
+
+    /// A glob pattern matching more paths than allowed by
+    /// [`crate::args::Limits::max_glob_matches`], with the maximum.
+    TooManyMatches(String, usize),
+
+    /// A quote that is never closed in a command line string.
+    UnterminatedQuote(char),
+
+    /// A value that failed the checks declared on its flag, with the reason it is invalid.
+    InvalidValue(String, String),
+
+    /// A flag using a value parser name which is not registered when parsing, with the name, see
+    /// [`crate::FlagBuilder::value_parser`].
+    UnknownValueParser(String, String),
+
+    /// A handler run by [`crate::Parser::dispatch`] failed, with the command and the reason.
+    CommandFailed(String, String),
+
+    /// A file given to `-replay` which could not be read or replayed, with the reason, see
+    /// [`crate::Parser::replay_flags`].
+    InvalidRecording(String, String),
+
+    /// A file given to `-record` which could not be written, with the reason.
+    RecordingFailed(String, String),
 }
 
 impl Display for Error {
@@ -17,14 +103,237 @@ impl Display for Error {
             Error::MissingArgument(key) => {
                 write!(f, "argument '{}' is required", key)
             }
+            Error::MissingArgumentIf(key, other, value) => {
+                write!(
+                    f,
+                    "argument '{}' is required when '{}' is '{}'",
+                    key, other, value
+                )
+            }
+            Error::MissingArgumentUnless(key, others) => {
+                let others: Vec<String> = others.iter().map(|o| format!("'{}'", o)).collect();
+                write!(
+                    f,
+                    "argument '{}' is required unless one of {} is given",
+                    key,
+                    others.join(", ")
+                )
+            }
+            Error::TooFewOccurrences(key, count, min) => {
+                write!(
+                    f,
+                    "argument '{}' is given {} times but at least {} are required",
+                    key, count, min
+                )
+            }
+            Error::TooManyOccurrences(key, count, max) => {
+                write!(
+                    f,
+                    "argument '{}' is given {} times but at most {} are allowed",
+                    key, count, max
+                )
+            }
+            Error::MissingPositional(name) => {
+                write!(f, "positional argument '{}' is required", name)
+            }
+            Error::TooManyPositionals(declared, given) => {
+                write!(
+                    f,
+                    "expected at most {} positional arguments but {} were given",
+                    declared, given
+                )
+            }
+            Error::InvalidPositional(name, usage, reason) => {
+                write!(
+                    f,
+                    "positional argument '{}' ({}) has an invalid value: {}",
+                    name, usage, reason
+                )
+            }
             Error::MissingValue(key) => {
                 write!(f, "argument '{}' requires a value", key)
             }
+            Error::NotConfirmed(key) => {
+                write!(f, "argument '{}' was not confirmed", key)
+            }
+            Error::UnknownFlag(key) => {
+                write!(f, "argument '{}' is not recognized", key)
+            }
+            Error::UnexpectedArgument(arg) => {
+                write!(f, "unexpected argument '{}'", arg)
+            }
+            Error::UnknownSubcommand(name, None) => {
+                write!(f, "subcommand '{}' is not recognized", name)
+            }
+            Error::UnknownSubcommand(name, Some(suggestion)) => {
+                write!(
+                    f,
+                    "subcommand '{}' is not recognized, did you mean '{}'?",
+                    name, suggestion
+                )
+            }
+            Error::UnknownConfigKey(key, None) => {
+                write!(f, "configuration key '{}' is not recognized", key)
+            }
+            Error::UnknownConfigKey(key, Some(suggestion)) => {
+                write!(
+                    f,
+                    "configuration key '{}' is not recognized, did you mean '{}'?",
+                    key, suggestion
+                )
+            }
+            Error::HelpRequested => {
+                write!(f, "help requested")
+            }
+            Error::VersionRequested => {
+                write!(f, "version requested")
+            }
+            #[cfg(feature = "completions")]
+            Error::CompletionsRequested(shell) => {
+                write!(f, "{} completions requested", shell)
+            }
+            Error::TooManyArgs(max) => {
+                write!(f, "more than {} arguments were given", max)
+            }
+            Error::ArgTooLong(max) => {
+                write!(f, "an argument is longer than {} bytes", max)
+            }
+            Error::TooManyMatches(pattern, max) => {
+                write!(f, "pattern '{}' matches more than {} paths", pattern, max)
+            }
+            Error::UnterminatedQuote(quote) => {
+                write!(f, "unterminated {} quote in command line", quote)
+            }
+            Error::InvalidValue(key, reason) => {
+                write!(f, "argument '{}' has an invalid value: {}", key, reason)
+            }
+            Error::UnknownValueParser(key, name) => {
+                write!(
+                    f,
+                    "argument '{}' uses value parser '{}' which is not registered",
+                    key, name
+                )
+            }
+            Error::CommandFailed(command, reason) => {
+                write!(f, "command '{}' failed: {}", command, reason)
+            }
+            Error::InvalidRecording(path, reason) => {
+                write!(f, "recording '{}' cannot be replayed: {}", path, reason)
+            }
+            Error::RecordingFailed(path, reason) => {
+                write!(f, "recording '{}' cannot be written: {}", path, reason)
+            }
         }
     }
 }
 
 impl std::error::Error for Error {}
 
+/// A list of possible mistakes in the flags declared on a parser, see
+/// [`crate::Parser::validate_definition`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DefinitionError {
+    /// A flag name which is empty, starts with a dash or contains whitespace.
+    InvalidName(String),
+
+    /// A required flag with a default, which can never be missing.
+    RequiredWithDefault(String),
+
+    /// A flag requiring confirmation which is not a boolean flag.
+    ConfirmOnValueFlag(String),
+
+    /// A flag using a value parser name which is not registered, with the name.
+    UnknownValueParser(String, String),
+
+    /// A flag named like the `-no-` form of a tri-state flag, with the tri-state flag.
+    AliasCollision(String, String),
+
+    /// A flag whose condition refers to a flag which is not declared, with the other flag.
+    UnknownFlagReference(String, String),
+
+    /// A required positional declared after an optional one, which could never be left out.
+    RequiredPositionalAfterOptional(String),
+
+    /// A positional taking multiple values declared before another positional.
+    MultiplePositionalNotLast(String),
+
+    /// Two positionals taking multiple values, with the first and the second.
+    SeveralMultiplePositionals(String, String),
+
+    /// A trailing positional declared before another positional.
+    TrailingPositionalNotLast(String),
+}
+
+impl Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DefinitionError::InvalidName(key) => {
+                write!(f, "flag name '{}' is invalid", key)
+            }
+            DefinitionError::RequiredWithDefault(key) => {
+                write!(f, "required flag '{}' has a default", key)
+            }
+            DefinitionError::ConfirmOnValueFlag(key) => {
+                write!(
+                    f,
+                    "flag '{}' requires confirmation but is not a boolean flag",
+                    key
+                )
+            }
+            DefinitionError::UnknownValueParser(key, name) => {
+                write!(
+                    f,
+                    "flag '{}' uses value parser '{}' which is not registered",
+                    key, name
+                )
+            }
+            DefinitionError::AliasCollision(key, negated) => {
+                write!(
+                    f,
+                    "flag '{}' collides with the negation of tri-state flag '{}'",
+                    key, negated
+                )
+            }
+            DefinitionError::UnknownFlagReference(key, other) => {
+                write!(
+                    f,
+                    "flag '{}' refers to flag '{}' which is not declared",
+                    key, other
+                )
+            }
+            DefinitionError::RequiredPositionalAfterOptional(name) => {
+                write!(
+                    f,
+                    "required positional '{}' is declared after an optional one",
+                    name
+                )
+            }
+            DefinitionError::MultiplePositionalNotLast(name) => {
+                write!(
+                    f,
+                    "positional '{}' takes multiple values but is not the last positional declared",
+                    name
+                )
+            }
+            DefinitionError::SeveralMultiplePositionals(first, second) => {
+                write!(
+                    f,
+                    "positionals '{}' and '{}' both take multiple values",
+                    first, second
+                )
+            }
+            DefinitionError::TrailingPositionalNotLast(name) => {
+                write!(
+                    f,
+                    "trailing positional '{}' is not the last positional declared",
+                    name
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
 /// Alias for a [`std::result::Result`] with the error type [`Error`].
 pub type Result<T> = std::result::Result<T, Error>;
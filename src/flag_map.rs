@@ -0,0 +1,156 @@
+//! Defines the map used to look up declared flags by name.
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::ops::Index;
+
+/// A word-at-a-time multiplicative hasher in the style of the one used by rustc, much cheaper
+/// than the default hasher for flag names which are never attacker controlled map keys.
+#[derive(Default)]
+struct FlagHasher(u64);
+
+impl FlagHasher {
+    const SEED: u64 = 0x517c_c1b7_2722_0a95;
+
+    fn add(&mut self, word: u64) {
+        self.0 = (self.0.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+    }
+}
+
+impl Hasher for FlagHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        let mut tail = [0u8; 8];
+        let rest = chunks.remainder();
+        tail[..rest.len()].copy_from_slice(rest);
+        self.add(u64::from_le_bytes(tail));
+    }
+}
+
+/// A map from flag names to values which keeps entries in declaration order.
+///
+/// Entries are stored in a vector and found through an index hashed with [`FlagHasher`], so
+/// lookups by borrowed names while parsing neither allocate nor pay for the default hasher.
+pub(crate) struct FlagMap<V> {
+    entries: Vec<(String, V)>,
+    index: HashMap<String, usize, BuildHasherDefault<FlagHasher>>,
+}
+
+impl<V> FlagMap<V> {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: HashMap::default(),
+        }
+    }
+
+    /// Inserts a value, replacing and returning any previous value for the key.
+    pub(crate) fn insert(&mut self, key: String, value: V) -> Option<V> {
+        match self.index.get(&key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&V> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        match self.index.get(key) {
+            Some(&i) => Some(&mut self.entries[i].1),
+            None => None,
+        }
+    }
+
+    /// Removes and returns the value for a key, keeping the order of the other entries.
+    pub(crate) fn remove(&mut self, key: &str) -> Option<V> {
+        let i = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for (_, j) in self.index.iter_mut().filter(|(_, j)| **j > i) {
+            *j -= 1;
+        }
+        Some(value)
+    }
+
+    pub(crate) fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    #[cfg_attr(not(feature = "help"), allow(dead_code))]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the entries in declaration order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&String, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    /// Iterates mutably over the entries in declaration order.
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut V)> {
+        self.entries.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    /// Returns the entries sorted by name.
+    #[cfg_attr(not(feature = "help"), allow(dead_code))]
+    pub(crate) fn sorted(&self) -> Vec<(&String, &V)> {
+        let mut entries: Vec<(&String, &V)> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+}
+
+impl<V> Index<&str> for FlagMap<V> {
+    type Output = V;
+
+    fn index(&self, key: &str) -> &V {
+        self.get(key).expect("flag is not declared")
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for FlagMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn lookup_and_order() {
+        let mut map = FlagMap::new();
+        assert_eq!(map.insert("verbose".to_string(), 1), None);
+        assert_eq!(map.insert("num".to_string(), 2), None);
+        assert_eq!(map.insert("opt".to_string(), 3), None);
+        assert_eq!(map.insert("num".to_string(), 4), Some(2));
+
+        assert_eq!(map.get("num"), Some(&4));
+        assert_eq!(map.get("missing"), None);
+
+        let declared: Vec<&String> = map.iter().map(|(k, _)| k).collect();
+        assert_eq!(declared, vec!["verbose", "num", "opt"]);
+        let sorted: Vec<&String> = map.sorted().into_iter().map(|(k, _)| k).collect();
+        assert_eq!(sorted, vec!["num", "opt", "verbose"]);
+
+        assert_eq!(map.remove("verbose"), Some(1));
+        assert_eq!(map.remove("verbose"), None);
+        assert_eq!(map.get("opt"), Some(&3));
+        let declared: Vec<&String> = map.iter().map(|(k, _)| k).collect();
+        assert_eq!(declared, vec!["num", "opt"]);
+    }
+}
@@ -0,0 +1,88 @@
+//! Defines [`arbitrary::Arbitrary`] inputs used to fuzz the parser.
+//!
+//! The fuzz targets in the `fuzz` directory build a [`crate::Parser`] from a [`ParserConfig`] and
+//! run it against an [`ArgSequence`], so panics or unexpected exits in the parse loop are caught.
+use arbitrary::Arbitrary;
+
+use crate::Parser;
+
+/// The kind of a fuzzed flag declaration.
+#[derive(Debug, Clone, Copy, Arbitrary)]
+pub enum FlagKind {
+    /// Declared with [`crate::Parser::bool_flag`].
+    Bool,
+    /// Declared with [`crate::Parser::required_flag`].
+    Required,
+    /// Declared with [`crate::Parser::optional_flag`].
+    Optional,
+}
+
+/// A fuzzed flag declaration.
+#[derive(Debug, Clone, Arbitrary)]
+pub struct FlagConfig {
+    /// The flag name.
+    pub name: String,
+    /// The kind of flag.
+    pub kind: FlagKind,
+    /// The flag default, if any.
+    pub default: Option<String>,
+    /// Whether the flag is sensitive.
+    pub sensitive: bool,
+}
+
+/// A fuzzed parser configuration.
+#[derive(Debug, Clone, Arbitrary)]
+pub struct ParserConfig {
+    /// The declared flags.
+    pub flags: Vec<FlagConfig>,
+    /// The configuration layer values.
+    pub config_values: Vec<(String, String)>,
+}
+
+/// A fuzzed sequence of arguments, the first one being the command name.
+#[derive(Debug, Clone, Arbitrary)]
+pub struct ArgSequence(pub Vec<String>);
+
+impl ParserConfig {
+    /// Builds a [`crate::Parser`] with this configuration over the given arguments.
+    pub fn build<'a>(&self, args: &'a ArgSequence) -> Parser<'a> {
+        let mut parser = Parser::from_iter(&args.0);
+        for flag in &self.flags {
+            let mut builder = match flag.kind {
+                FlagKind::Bool => parser.bool_flag(&flag.name, "fuzzed boolean flag"),
+                FlagKind::Required => parser.required_flag(&flag.name, "fuzzed required flag"),
+                FlagKind::Optional => parser.optional_flag(&flag.name, "fuzzed optional flag"),
+            };
+            if let Some(default) = &flag.default {
+                builder = builder.default_value(default);
+            }
+            if flag.sensitive {
+                builder.sensitive();
+            }
+        }
+        for (flag, value) in &self.config_values {
+            parser.config_value(flag, value);
+        }
+        parser
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use arbitrary::Unstructured;
+
+    #[test]
+    fn build_from_bytes() {
+        let bytes: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        let mut u = Unstructured::new(&bytes);
+        let config = ParserConfig::arbitrary(&mut u).unwrap();
+        let args = ArgSequence(vec!["cmd".to_string(), "file.txt".to_string()]);
+
+        let mut parser = config.build(&args);
+        if parser.validate_definition().is_ok() {
+            let _ = parser.finalize();
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Expands glob patterns in arguments for platforms where the shell does not.
+use std::path::{Path, PathBuf};
+
+/// Returns whether an argument contains any glob syntax.
+pub(crate) fn is_pattern(arg: &str) -> bool {
+    arg.contains(['*', '?', '['])
+}
+
+/// Expands a glob pattern into the sorted list of matching paths.
+///
+/// Supports `*`, `?` and `[...]` classes within a path component, wildcards never match a leading
+/// dot. Returns an empty list if nothing matches. Once more than `max` paths match any component,
+/// the expansion stops and returns the paths found so far, so more than `max` paths are returned
+/// even if they would not all match the whole pattern.
+pub(crate) fn expand(pattern: &str, max: Option<usize>) -> Vec<String> {
+    let separators: &[char] = if cfg!(windows) { &['/', '\\'] } else { &['/'] };
+
+    // The directory leading up to the first wildcard is used as is, which keeps roots and drive
+    // prefixes intact.
+    let first = pattern.find(['*', '?', '[']).unwrap_or(pattern.len());
+    let base = pattern[..first].rfind(separators).map_or(0, |i| i + 1);
+    let mut paths = vec![PathBuf::from(&pattern[..base])];
+
+    let components: Vec<&str> = pattern[base..]
+        .split(separators)
+        .filter(|c| !c.is_empty())
+        .collect();
+    let exceeded = |paths: &[PathBuf]| max.is_some_and(|max| paths.len() > max);
+    for component in components {
+        let mut next = Vec::new();
+        for path in &paths {
+            if exceeded(&next) {
+                break;
+            }
+            if !is_pattern(component) {
+                let path = path.join(component);
+                if path.exists() {
+                    next.push(path);
+                }
+                continue;
+            }
+
+            let dir = if path.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                path
+            };
+            let Ok(entries) = dir.read_dir() else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let Some(name) = name.to_str() else {
+                    continue;
+                };
+                if matches(component, name) {
+                    next.push(path.join(name));
+                    if exceeded(&next) {
+                        break;
+                    }
+                }
+            }
+        }
+        paths = next;
+        if exceeded(&paths) {
+            break;
+        }
+    }
+
+    let mut matched: Vec<String> = paths
+        .into_iter()
+        .filter_map(|path| path.to_str().map(str::to_string))
+        .collect();
+    matched.sort();
+    matched
+}
+
+/// Returns whether a name matches a single component glob pattern.
+fn matches(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position to resume from after the last star, trying to consume one more character.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+                continue;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            Some('[') => {
+                if let Some((matched, len)) = match_class(&pattern[p..], name[n]) {
+                    if matched {
+                        p += len;
+                        n += 1;
+                        continue;
+                    }
+                } else if name[n] == '[' {
+                    // An unterminated class is a literal bracket.
+                    p += 1;
+                    n += 1;
+                    continue;
+                }
+            }
+            Some(&c) if c == name[n] => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        match backtrack {
+            Some((star, start)) => {
+                p = star + 1;
+                n = start + 1;
+                backtrack = Some((star, start + 1));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches a character against a `[...]` class at the start of the pattern, returning whether it
+/// matched and the length of the class, or `None` if the class is never closed.
+fn match_class(pattern: &[char], c: char) -> Option<(bool, usize)> {
+    let mut i = 1;
+    let negated = matches!(pattern.get(i), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+    while let Some(&start) = pattern.get(i) {
+        if start == ']' && !first {
+            return Some((matched != negated, i + 1));
+        }
+        first = false;
+        match (pattern.get(i + 1), pattern.get(i + 2)) {
+            (Some('-'), Some(&end)) if end != ']' => {
+                matched |= start <= c && c <= end;
+                i += 3;
+            }
+            _ => {
+                matched |= start == c;
+                i += 1;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn patterns() {
+        assert!(matches("*.txt", "notes.txt"));
+        assert!(!matches("*.txt", "notes.md"));
+        assert!(!matches("*", ".hidden"));
+        assert!(matches(".*", ".hidden"));
+        assert!(matches("a?c", "abc"));
+        assert!(matches("*b*b", "abbcb"));
+        assert!(matches("[a-c]x", "bx"));
+        assert!(!matches("[!a-c]x", "bx"));
+        assert!(matches("[]]", "]"));
+    }
+
+    #[test]
+    fn expansion() {
+        let pattern = concat!(env!("CARGO_MANIFEST_DIR"), "/src/fl*_map.r?");
+        let matched = expand(pattern, None);
+        assert_eq!(
+            matched,
+            vec![concat!(env!("CARGO_MANIFEST_DIR"), "/src/flag_map.rs")]
+        );
+        assert!(expand("does-not-exist/*.txt", None).is_empty());
+
+        let pattern = concat!(env!("CARGO_MANIFEST_DIR"), "/src/*.rs");
+        assert_eq!(expand(pattern, Some(2)).len(), 3);
+
+        // A component before the last one is limited too, without reading the directories below.
+        let dir = std::env::temp_dir().join(format!("yafp-glob-{}", std::process::id()));
+        for name in ["a", "b", "c", "d"] {
+            std::fs::create_dir_all(dir.join(name).join("x")).unwrap();
+        }
+        let pattern = format!("{}/*/*/y", dir.display());
+        assert!(expand(&pattern, None).is_empty());
+        assert_eq!(expand(&pattern, Some(2)).len(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
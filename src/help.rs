@@ -0,0 +1,528 @@
+//! Renders the help of a parser.
+use std::fmt;
+use std::io;
+
+use crate::style::Painter;
+use crate::subcommand::Subcommand;
+use crate::{env_var_names, Flag, FlagEntry, Parser, REDACTED};
+
+impl<'a> Parser<'a> {
+    /// Returns a string with the generated flag information.
+    pub fn help_flags(&self) -> String {
+        let mut help = String::new();
+        // Writing into a String never fails.
+        let _ = self.write_help_flags(&mut help);
+        help
+    }
+
+    /// Writes the generated flag information, as returned by [`crate::Parser::help_flags`], into
+    /// a [`std::fmt::Write`].
+    pub fn write_help_flags(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        self.write_flags(w, Painter::PLAIN)
+    }
+
+    fn write_flags(&self, w: &mut impl fmt::Write, p: Painter<'_>) -> fmt::Result {
+        let flags = self.visible_flags();
+        if flags.is_empty() {
+            return writeln!(w);
+        }
+
+        // Ensure flag help is deterministic by sorting flag names.
+        for (key, flag_entry) in flags {
+            write!(w, "  ")?;
+            write_flag_name(w, p, key, flag_entry)?;
+            if !flag_entry.choices.is_empty() {
+                write!(w, " [possible: {}]", flag_entry.choices.join(", "))?;
+            }
+            writeln!(w)?;
+            writeln!(w, "\t{}", flag_entry.usage)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a string listing the declared subcommands with their descriptions, or an empty
+    /// string if there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// parser.add_subcommand("build", "compile the project");
+    /// parser.add_subcommand("clean", "remove build artifacts");
+    ///
+    /// assert_eq!(
+    ///     "Commands:\n  build\n\tcompile the project\n  clean\n\tremove build artifacts\n",
+    ///     parser.help_commands()
+    /// );
+    /// ```
+    pub fn help_commands(&self) -> String {
+        let mut help = String::new();
+        // Writing into a String never fails.
+        let _ = self.write_help_commands(&mut help);
+        help
+    }
+
+    /// Writes the subcommand list, as returned by [`crate::Parser::help_commands`], into a
+    /// [`std::fmt::Write`].
+    pub fn write_help_commands(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        self.write_commands(w, Painter::PLAIN)
+    }
+
+    fn write_commands(&self, w: &mut impl fmt::Write, p: Painter<'_>) -> fmt::Result {
+        for (category, subcommands) in self.grouped_subcommands() {
+            writeln!(w, "{}", p.heading(format_args!("{}:", category)))?;
+            for sub in subcommands {
+                let url = sub.parser.docs_url.as_deref();
+                writeln!(w, "  {}", p.name(&sub.name).link(url))?;
+                writeln!(w, "\t{}", sub.about)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the visible subcommands grouped by category in the order the category was first
+    /// used, uncategorized ones under `Commands`, each sorted by name so help is deterministic.
+    pub(crate) fn grouped_subcommands(&self) -> Vec<(&str, Vec<&Subcommand<'a>>)> {
+        let mut groups: Vec<(&str, Vec<&Subcommand<'a>>)> = Vec::new();
+        for sub in self.subcommands.iter().filter(|sub| !sub.hidden) {
+            let category = sub.category.as_deref().unwrap_or("Commands");
+            match groups.iter_mut().find(|(name, _)| *name == category) {
+                Some((_, subcommands)) => subcommands.push(sub),
+                None => groups.push((category, vec![sub])),
+            }
+        }
+        for (_, subcommands) in &mut groups {
+            subcommands.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        groups
+    }
+
+    /// Sets the text describing the command, shown above the usage line.
+    ///
+    /// Subcommands use the description given to [`crate::Parser::add_subcommand`] by default.
+    pub fn set_about(&mut self, about: &str) {
+        self.about = Some(about.to_string());
+    }
+
+    /// Sets the arguments part of the usage line, which defaults to `[options...]` followed by
+    /// `<command>` if there are subcommands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// let build = parser.add_subcommand("build", "compile the project");
+    /// build.set_usage("[options...] <target>");
+    /// build.add_example("tool build -release app");
+    ///
+    /// assert_eq!(
+    ///     build.help(),
+    ///     [
+    ///         "compile the project\n",
+    ///         "Usage: tool build [options...] <target>\n",
+    ///         "\n",
+    ///         "Examples:\n",
+    ///         "  tool build -release app\n",
+    ///     ]
+    ///     .concat()
+    /// );
+    /// ```
+    pub fn set_usage(&mut self, usage: &str) {
+        self.usage = Some(usage.to_string());
+    }
+
+    /// Links the name of this subcommand in the colored help of its parent to online
+    /// documentation, see [`crate::FlagBuilder::docs_url`].
+    pub fn set_docs_url(&mut self, url: &str) {
+        self.docs_url = Some(url.to_string());
+    }
+
+    /// Adds an example invocation, listed in an `Examples:` section at the end of the help.
+    pub fn add_example(&mut self, example: &str) {
+        self.examples.push(example.to_string());
+    }
+
+    /// Writes the usage line, preceded by the about text if any.
+    fn write_usage_line(&self, w: &mut impl fmt::Write, p: Painter<'_>) -> fmt::Result {
+        if let Some(about) = &self.about {
+            writeln!(w, "{}", about)?;
+        }
+        self.write_usage(w, p)
+    }
+
+    /// Writes the usage line alone.
+    pub(crate) fn write_usage(&self, w: &mut impl fmt::Write, p: Painter<'_>) -> fmt::Result {
+        write!(w, "{} {}", p.heading("Usage:"), p.name(&self.command))?;
+        match &self.usage {
+            Some(usage) => write!(w, " {}", usage)?,
+            None => {
+                write!(w, " {}", p.placeholder("[options...]"))?;
+                for (name, entry) in self.declared_positionals.iter() {
+                    let (open, close) = match (entry.min_occurrences, entry.multiple) {
+                        (0, false) => ("[", "]"),
+                        (0, true) => ("[", "...]"),
+                        (_, false) => ("<", ">"),
+                        (_, true) => ("<", ">..."),
+                    };
+                    write!(
+                        w,
+                        " {}",
+                        p.placeholder(format_args!("{}{}{}", open, name, close))
+                    )?;
+                }
+                if self.has_subcommands() {
+                    write!(w, " {}", p.placeholder("<command>"))?;
+                }
+            }
+        }
+        writeln!(w)
+    }
+
+    /// Writes the declared positionals in declaration order with their usage indented by
+    /// `indent`, if there are any.
+    fn write_arguments(
+        &self,
+        w: &mut impl fmt::Write,
+        p: Painter<'_>,
+        indent: &str,
+    ) -> fmt::Result {
+        if self.declared_positionals.is_empty() {
+            return Ok(());
+        }
+        writeln!(w, "{}", p.heading("Arguments:"))?;
+        for (name, entry) in self.declared_positionals.iter() {
+            writeln!(w, "  {}", p.name(name))?;
+            writeln!(w, "{}{}", indent, entry.usage)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the examples section if there are any examples.
+    fn write_examples(&self, w: &mut impl fmt::Write, p: Painter<'_>) -> fmt::Result {
+        if self.examples.is_empty() {
+            return Ok(());
+        }
+        writeln!(w, "{}", p.heading("Examples:"))?;
+        for example in &self.examples {
+            writeln!(w, "  {}", example)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a string with the usage string.
+    ///
+    /// If you use positional arguments it might be useful to define a custom function
+    /// which prints the usage line and then prints the string returned by [`crate::Parser::help_flags`].
+    ///
+    /// Once a subcommand is matched by [`crate::Parser::finalize`] this returns the help of the
+    /// subcommand instead, so `-help` given after a subcommand shows the right help.
+    ///
+    /// # Examples
+    ///
+    /// ## Default Help
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["head", "-verbose", "file.txt"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    ///
+    /// /// This must be called before fetching flags and returns any remaining args.
+    /// parser.finalize()?;
+    ///
+    /// /// Using the default help function does not allow you to specify the positional args but let's you get
+    /// /// the basic help working.
+    /// let help: String = parser.help();
+    /// assert_eq!(String::from("Usage: head [options...]\n  -verbose\n\tthis is used to get verbose output\n"), help);
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// ## Custom Help
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["head", "-verbose", "file.txt"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    ///
+    /// let command = parser.command.to_string();
+    /// let help_flags = parser.help_flags();
+    /// parser.set_help_fn(move || {
+    ///   let help_string = format!("Usage: {} [options...] <file>", command);
+    ///   format!("{}\n{}", help_string, help_flags)
+    /// });
+    ///
+    /// /// This must be called before fetching flags and returns any remaining args.
+    /// parser.finalize()?;
+    ///
+    /// /// Using the default help function does not allow you to specify the positional args but let's you get
+    /// /// the basic help working.
+    /// let help: String = parser.help();
+    /// assert_eq!(String::from("Usage: head [options...] <file>\n  -verbose\n\tthis is used to get verbose output\n"), help);
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    pub fn help(&self) -> String {
+        let mut help = String::new();
+        // Writing into a String never fails.
+        let _ = self.write_help(&mut help);
+        help
+    }
+
+    /// Writes the help, as returned by [`crate::Parser::help`], into a [`std::fmt::Write`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["head"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    ///
+    /// let mut help = String::new();
+    /// parser.write_help(&mut help).unwrap();
+    /// assert_eq!(parser.help(), help);
+    /// ```
+    pub fn write_help(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        self.write_help_painted(w, Painter::PLAIN)
+    }
+
+    /// Writes the help, as returned by [`crate::Parser::help`], colored with the theme set with
+    /// [`crate::Parser::set_theme`] using ANSI escape sequences.
+    ///
+    /// A custom help function set with [`crate::Parser::set_help_fn`] or renderer set with
+    /// [`crate::Parser::set_help_renderer`] is written as is. Use
+    /// [`crate::Parser::color_enabled`] to decide whether the stream should get colored help.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["head"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    ///
+    /// let mut help = String::new();
+    /// if parser.color_enabled(&std::io::stdout()) {
+    ///     parser.write_help_colored(&mut help).unwrap();
+    /// } else {
+    ///     parser.write_help(&mut help).unwrap();
+    /// }
+    /// print!("{}", help);
+    /// ```
+    pub fn write_help_colored(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        self.write_help_painted(w, Painter::new(Some(self.theme())))
+    }
+
+    pub(crate) fn write_help_painted(
+        &self,
+        w: &mut impl fmt::Write,
+        p: Painter<'_>,
+    ) -> fmt::Result {
+        if let Some((_, parser)) = self.subcommand() {
+            return parser.write_help_painted(w, p);
+        }
+        match (&self.help_fn, &self.help_renderer) {
+            (Some(f), _) => w.write_str(&f(self)),
+            (None, Some(renderer)) => w.write_str(&renderer.render(&self.help_page())),
+            (None, None) => {
+                self.write_usage_line(w, p)?;
+                self.write_flags(w, p)?;
+                self.write_arguments(w, p, "\t")?;
+                self.write_commands(w, p)?;
+                self.write_examples(w, p)
+            }
+        }
+    }
+
+    /// Writes the help, as returned by [`crate::Parser::help`], into a [`std::io::Write`] such as
+    /// [`std::io::stderr`] or a pager.
+    pub fn write_help_io(&self, w: &mut impl io::Write) -> io::Result<()> {
+        self.write_help_io_painted(w, Painter::PLAIN)
+    }
+
+    pub(crate) fn write_help_io_painted(
+        &self,
+        w: &mut impl io::Write,
+        p: Painter<'_>,
+    ) -> io::Result<()> {
+        let mut adapter = IoAdapter {
+            inner: w,
+            error: None,
+        };
+        match self.write_help_painted(&mut adapter, p) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adapter
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error"))),
+        }
+    }
+
+    /// Returns the full help with every detail of each flag, in a stable format suited for
+    /// snapshot testing with [`crate::testing::assert_snapshot`].
+    ///
+    /// The format is a usage line, a blank line and an `Options:` section listing flags sorted by
+    /// name. Each flag is on its own line indented by two spaces, followed by ` value` for value
+    /// flags or its `-no-` form for tri-state flags and then, when they apply, `[possible: ...]`,
+    /// `(required)`, `(default: ...)` and `[env: ...]`. The usage text follows on the next line
+    /// indented by six spaces. Positionals declared with [`crate::Parser::positional`] are listed
+    /// in the usage line as `<name>`, or `<name>...` if they take multiple values, in brackets
+    /// instead if declared with [`crate::Parser::optional_positional`], and in an
+    /// `Arguments:` section after a blank line, in declaration order and in the same layout. If
+    /// there are subcommands the usage line ends with ` <command>` and a
+    /// blank line and a `Commands:` section follow, listing subcommands sorted by name in the same
+    /// layout, with a section per category set with [`crate::Parser::subcommand_category`]. A
+    /// custom usage set with [`crate::Parser::set_usage`] replaces everything after the command in
+    /// the usage line, the about text precedes it on its own line and examples follow in a final
+    /// `Examples:` section after a blank line. Sensitive defaults are redacted, and neither a custom
+    /// help function set with [`crate::Parser::set_help_fn`] nor a renderer set with
+    /// [`crate::Parser::set_help_renderer`] is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["head"]);
+    /// parser.env_prefix("HEAD");
+    /// parser.required_flag("num", "this is used to set a numeric value");
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    ///
+    /// assert_eq!(
+    ///     parser.render_full_help(),
+    ///     [
+    ///         "Usage: head [options...]\n",
+    ///         "\n",
+    ///         "Options:\n",
+    ///         "  -num value (required) [env: HEAD_NUM]\n",
+    ///         "      this is used to set a numeric value\n",
+    ///         "  -verbose (default: false) [env: HEAD_VERBOSE]\n",
+    ///         "      this is used to get verbose output\n",
+    ///     ]
+    ///     .concat()
+    /// );
+    /// ```
+    pub fn render_full_help(&self) -> String {
+        let mut help = String::new();
+        // Writing into a String never fails.
+        let _ = self.write_full_help(&mut help);
+        help
+    }
+
+    /// Writes the full help, as returned by [`crate::Parser::render_full_help`], into a
+    /// [`std::fmt::Write`].
+    pub fn write_full_help(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        let p = Painter::PLAIN;
+        self.write_usage_line(w, p)?;
+        writeln!(w)?;
+        writeln!(w, "{}", p.heading("Options:"))?;
+        for (key, entry) in self.visible_flags() {
+            write!(w, "  ")?;
+            write_flag_name(w, p, key, entry)?;
+            if !entry.choices.is_empty() {
+                write!(w, " [possible: {}]", entry.choices.join(", "))?;
+            }
+            if self.required.contains(key) {
+                write!(w, " (required)")?;
+            }
+            if let Some(default) = &entry.default {
+                let default = if entry.sensitive { REDACTED } else { default };
+                write!(w, " (default: {})", default)?;
+            }
+            let env_vars = env_var_names(self.env_prefix.as_deref(), key, entry);
+            if !env_vars.is_empty() {
+                write!(w, " [env: {}]", env_vars.join(", "))?;
+            }
+            writeln!(w)?;
+            writeln!(w, "      {}", entry.usage)?;
+        }
+
+        if !self.declared_positionals.is_empty() {
+            writeln!(w)?;
+            self.write_arguments(w, p, "      ")?;
+        }
+        for (category, subcommands) in self.grouped_subcommands() {
+            writeln!(w)?;
+            writeln!(w, "{}", p.heading(format_args!("{}:", category)))?;
+            for sub in subcommands {
+                writeln!(w, "  {}", p.name(&sub.name))?;
+                writeln!(w, "      {}", sub.about)?;
+            }
+        }
+        if !self.examples.is_empty() {
+            writeln!(w)?;
+            self.write_examples(w, p)?;
+        }
+        Ok(())
+    }
+
+    /// Accepts a closure that defines a custom help function, for an example usage check the [custom help example].
+    ///
+    /// [custom help example]: crate::Parser#custom-help
+    pub fn set_help_fn(&mut self, f: impl Fn() -> String + 'static) {
+        self.help_fn = Some(Box::new(move |_| f()));
+    }
+
+    /// Accepts a closure that defines a custom help function receiving the parser, so it reads
+    /// the command and the flags when the help is shown instead of when the closure is set.
+    ///
+    /// The closure must not call [`crate::Parser::help`] on the parser it receives, which would
+    /// call the closure again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["head"]);
+    /// parser.set_help_fn_with_parser(|parser| {
+    ///     format!("Usage: {} [options...] <file>\n{}", parser.command, parser.help_flags())
+    /// });
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    ///
+    /// assert_eq!(
+    ///     "Usage: head [options...] <file>\n  -verbose\n\tthis is used to get verbose output\n",
+    ///     parser.help()
+    /// );
+    /// ```
+    pub fn set_help_fn_with_parser(&mut self, f: impl Fn(&Parser<'_>) -> String + 'static) {
+        self.help_fn = Some(Box::new(f));
+    }
+}
+
+/// Writes the name of a flag, linked to its documentation, with a placeholder for its value or
+/// its `-no-` form.
+fn write_flag_name(
+    w: &mut impl fmt::Write,
+    p: Painter<'_>,
+    key: &str,
+    entry: &FlagEntry,
+) -> fmt::Result {
+    let url = entry.docs_url.as_deref();
+    write!(w, "{}", p.name(format_args!("-{}", key)).link(url))?;
+    match entry.typ {
+        Flag::Value => write!(w, " {}", p.placeholder("value")),
+        Flag::TriState => write!(w, ", {}", p.name(format_args!("-no-{}", key))),
+        Flag::Bool => Ok(()),
+        Flag::Count => w.write_str("..."),
+    }
+}
+
+/// Adapts a [`std::io::Write`] to a [`std::fmt::Write`], keeping the underlying error.
+struct IoAdapter<'w, W: io::Write> {
+    inner: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
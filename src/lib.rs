@@ -2,11 +2,63 @@
 yafp is a non-POSIX cli flag parser with imperative style flag declaration instead of the usual declarative style.
 
 Features:
-- Help generation.
+- Help generation, colored with a customizable theme and linking to online docs on terminals following `NO_COLOR` and `CLICOLOR_FORCE`, behind the default `help` feature.
 - Imperative flag declaration with usage text.
 - Supports boolean flags, `false` by default and `true` if set.
-- Supports required and optional value flags.
+- Supports tri-state boolean flags, unset by default and disabled with a `no-` prefix.
+- Supports required and optional value flags, and flags required when another flag has a given value or unless alternative flags are given.
+- Flags taking multiple values, from repeated flags or split environment and configuration values, replaced or appended across sources, with optional minimum and maximum counts.
+- Subcommands with their own flags, usage line, examples and handlers, implementable as a `Command` trait, and external subcommands for git style plugins.
 - Values parsed to assigned variable type.
+- Named positional arguments, required or optional with a default, checked like flags and read by name once the remaining args are mapped onto them.
+- A trailing positional capturing the rest of the args verbatim, such as a command line to run.
+- Tuple flags with a fixed number of values, such as `800x600` or `-offset 10 20`, read as typed tuples.
+- Values normalized before they are checked, for example trimmed, lowercased or without a trailing slash.
+- Numeric ranges and custom closures validating values.
+- Value parsers registered once by name and shared by any number of flags.
+- Flags restricted to a list of choices, or to the variants of an enum with `value_enum!`.
+- Multi-select flags combining names into bits, for `bitflags` style types.
+- Counted flags, and `-v` and `-quiet` combined into a log level, converted into a `log::LevelFilter` behind the `log` feature.
+- Logger setup from `-v`, `-quiet`, `-log-format` and `-log-color` in one call, behind the `env_logger` and `tracing-subscriber` features.
+- Bash, zsh and fish completion scripts generated from the declarations, and installed into the shell config in one call, behind the default `completions` feature which also suggests close names for mistyped subcommands and configuration keys.
+- An opt-in `completions <shell>` subcommand printing the completion script.
+- Fig autocomplete and Carapace specs exported from the declarations.
+- An audit closure receiving a summary of each parsed invocation, with sensitive values redacted.
+- `-record` and `-replay` flags saving an invocation to a JSON file and reproducing it, behind the `serde_json` feature.
+- An interactive wizard asking for every flag and printing the equivalent command line.
+- Pluggable help renderers receiving the flags, arguments and subcommands as structured data.
+- A command name taken from the file name of the binary, or set with `set_name`, with the full path still available.
+- An optional usage line and `-help` hint after reported usage errors.
+- Numbers written with a locale decimal mark and digit grouping, such as `1.234,56`.
+- Integer flags accepting hexadecimal, octal and binary literals.
+- Byte size flags with SI or IEC unit suffixes.
+- Color flags given as hex, `rgb()` or by name.
+- Base64 flags decoded into bytes.
+- File flags whose value is the contents of the given file.
+- Flags read from stdin when given as `-`.
+- Environment variable fallback with an application prefix, or several variables per flag, also read from the file named by a `_FILE` variable, with booleans written as `1/0`, `true/false`, `yes/no` or `on/off`, and an injectable environment for hermetic tests.
+- Reporting the effective value and source of every flag with `explain()`.
+- Access to the raw args of the invocation and to the tokens each flag value was parsed from, with sensitive values redacted for logging.
+- Injecting or overriding flag values from code with `set_value()`, before or after parsing.
+- Removing, hiding, redeclaring or reconfiguring flags declared elsewhere, such as by another crate.
+- A clap style `Arg` builder with `long`, `takes_value` and `required`, to migrate existing CLIs gradually.
+- Loading `.env` files into the environment fallback, layered by mode with `.local` overrides, behind the `dotenv` feature.
+- Flag defaults, optionally depending on the value of another flag, and a configuration layer, resolved with a configurable source precedence.
+- Loading INI, TOML or YAML configuration files into the configuration layer, with profiles, optionally discovered in standard locations or generated from the flags, behind the `ini`, `toml` and `serde_yaml` features.
+- Path flags checked against the filesystem, with optional `~` and `$VAR` expansion.
+- IP and socket address flags, with an optional default port.
+- CIDR network range flags, behind the `cidr` feature.
+- URL flags with per flag scheme allow-lists, behind the `url` feature.
+- Semantic version flags, behind the `semver` feature.
+- Timestamp and date flags, behind the `chrono` feature.
+- Regular expression flags compiled during parsing, behind the `regex` feature.
+- JSON flags deserialized into any type, behind the `serde_json` feature.
+- A `-version` flag reporting the version of the command, inherited by subcommands.
+- A `run()` entry point reporting help, version and errors and returning the exit code, and a `main!` macro wrapping it.
+- Opt-in glob expansion of remaining args for shells that do not expand them.
+- Opt-in strict mode rejecting positional arguments for commands which only take flags.
+- Limits on the number and length of args and on glob expansion, for parsers given untrusted input.
+- Pluggable token classification for other flag syntaxes, such as `+flag` toggles or bare-word options.
 
 Limitations:
 - Only supports short flag style.
@@ -17,15 +69,70 @@ Limitations:
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
-use errors::{Error, Result};
+use args::{ArgSource, Limits};
+pub use builder::FlagBuilder;
+use builder::CONFIRM_FLAG;
+pub use command::Command;
+use errors::{DefinitionError, Error, Result};
+use flag_map::FlagMap;
+pub use style::ColorChoice;
+use subcommand::{Handler, Hook, Matched, Subcommand};
+use token::{Token, TokenClassifier};
+pub use tuple::TupleValue;
+use value::{ByteDecoder, Normalizer, ValueParser};
+pub use value_enum::ValueEnum;
 
+use std::borrow::Cow;
+use std::cell::{Cell, OnceCell, RefCell};
 use std::collections::HashMap;
-use std::fmt::Display;
+use std::fmt::{self, Display};
 use std::iter::Peekable;
-use std::process::exit;
+use std::rc::Rc;
 use std::str::FromStr;
 
+pub mod args;
+pub mod audit;
+mod builder;
+#[cfg(feature = "cidr")]
+pub mod cidr;
+pub mod clap_compat;
+pub mod color;
+mod command;
+#[cfg(feature = "completions")]
+pub mod completions;
+#[cfg(any(feature = "ini", feature = "toml", feature = "serde_yaml"))]
+mod config;
+#[cfg(feature = "dotenv")]
+mod dotenv;
 pub mod errors;
+mod flag_map;
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
+mod glob;
+#[cfg(feature = "help")]
+mod help;
+#[cfg(any(feature = "env_logger", feature = "tracing-subscriber"))]
+pub mod logging;
+mod positional;
+#[cfg(feature = "help")]
+pub mod render;
+#[cfg(feature = "serde_json")]
+pub mod replay;
+mod run;
+pub mod split;
+pub mod style;
+mod subcommand;
+#[cfg(feature = "completions")]
+mod suggest;
+pub mod testing;
+#[cfg(feature = "proptest")]
+pub mod testkit;
+pub mod token;
+mod tuple;
+mod value;
+mod value_enum;
+pub mod verbosity;
+mod wizard;
 
 /// Represents all possible flag variations.
 #[derive(Debug, Clone, Copy)]
@@ -34,52 +141,508 @@ enum Flag {
     Bool,
     /// A flag which holds a value.
     Value,
+    /// A boolean flag which is unset unless given, and may be disabled with a `no-` prefix.
+    TriState,
+    /// A flag without a value which counts how many times it is given.
+    Count,
 }
 
-#[derive(Debug, Clone)]
-struct FlagEntry {
-    value: Option<String>,
+/// Represents the sources a flag value can come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The command line arguments.
+    Cli,
+    /// The environment, see [`crate::Parser::env_prefix`].
+    Env,
+    /// The configuration layer, see [`crate::Parser::config_value`].
+    Config,
+    /// The flag default, see [`crate::FlagBuilder::default_value`].
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Source::Cli => "cli",
+            Source::Env => "env",
+            Source::Config => "config",
+            Source::Default => "default",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Represents how unit prefixes such as `k` or `M` in byte sizes are interpreted, see
+/// [`crate::FlagBuilder::byte_size`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeUnits {
+    /// Powers of 1000, so `1k` is 1000 bytes.
+    Si,
+    /// Powers of 1024, so `1k` is 1024 bytes.
+    Iec,
+}
+
+/// Represents the character separating the integer and fractional parts of a number, see
+/// [`crate::FlagBuilder::localized_number`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecimalMark {
+    /// A point, with commas grouping digits as in `1,234.56`.
+    Point,
+    /// A comma, with points grouping digits as in `1.234,56`.
+    Comma,
+}
+
+/// Represents how the values of a flag taking multiple values are combined across sources, see
+/// [`crate::FlagBuilder::merge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListMerge {
+    /// Values of the source with the highest precedence replace the others.
+    Replace,
+    /// Values of every source but the default are combined, the ones with lower precedence
+    /// first. A value on the command line may be prefixed with `+` to show it is appended, which
+    /// is removed.
+    Append,
+}
+
+/// Represents the format of a configuration file, see [`crate::Parser::load_config_as`].
+#[cfg(any(feature = "ini", feature = "toml", feature = "serde_yaml"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// An INI file, where sections prefix the keys they contain, behind the `ini` feature.
+    #[cfg(feature = "ini")]
+    Ini,
+    /// A TOML file, behind the `toml` feature.
+    #[cfg(feature = "toml")]
+    Toml,
+    /// A YAML file, behind the `serde_yaml` feature.
+    #[cfg(feature = "serde_yaml")]
+    Yaml,
+}
+
+#[cfg(any(feature = "ini", feature = "toml", feature = "serde_yaml"))]
+impl ConfigFormat {
+    /// Returns the format matching the extension of a path, if it is known and enabled.
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            #[cfg(feature = "ini")]
+            "ini" => Some(ConfigFormat::Ini),
+            #[cfg(feature = "toml")]
+            "toml" => Some(ConfigFormat::Toml),
+            #[cfg(feature = "serde_yaml")]
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// The name of the flag selecting a configuration profile, see [`crate::Parser::config_profiles`].
+const PROFILE_FLAG: &str = "profile";
+
+/// The default order in which sources are consulted when resolving a flag value.
+const DEFAULT_PRECEDENCE: [Source; 4] = [Source::Cli, Source::Env, Source::Config, Source::Default];
+
+/// Separates the values of a flag taking multiple values, see [`crate::FlagBuilder::multiple`].
+const LIST_SEPARATOR: char = '\u{1f}';
+
+/// Replaces the value of sensitive flags in any output produced by the parser.
+const REDACTED: &str = "********";
+
+struct FlagEntry<'a> {
+    value: Option<Cow<'a, str>>,
+    source: Option<Source>,
+    default: Option<String>,
+    confirm: Option<String>,
+    sensitive: bool,
+    /// Leaves the flag out of help, completions and specs while still parsing it.
+    hidden: bool,
+    /// Rewrites the value into its canonical form before the parsers run.
+    normalizers: Vec<Normalizer>,
+    parsers: Vec<ValueParser>,
+    decoder: Option<ByteDecoder>,
+    /// Replaces the value with the decoded bytes as text, instead of keeping it as given.
+    decoded_value: bool,
+    bytes: Option<Vec<u8>>,
+    /// Reads the value from stdin when it is `-`.
+    stdin: bool,
+    choices: Vec<String>,
+    /// Joins the values of repeated occurrences on the command line instead of keeping the last.
+    accumulate: Option<char>,
+    /// Keeps every value, joined with [`LIST_SEPARATOR`], and checks each one on its own.
+    multiple: bool,
+    /// The least and most values a flag taking multiple values accepts.
+    min_occurrences: usize,
+    max_occurrences: Option<usize>,
+    /// The number of values and the delimiter joining them of a tuple flag.
+    tuple: Option<(usize, char)>,
+    /// The command line tokens the value was parsed from, the flag followed by its values.
+    raw: Vec<Cow<'a, str>>,
+    /// Splits an environment value into multiple values.
+    env_delimiter: char,
+    merge: ListMerge,
+    /// Environment variables checked before the one derived from the prefix.
+    env_vars: Vec<String>,
+    /// The environment variable the value came from.
+    env_var: Option<String>,
+    /// The online documentation linked from the flag name in colored help.
+    #[cfg(feature = "help")]
+    docs_url: Option<String>,
+    /// Flags and values which make this flag required when the flag has the value.
+    required_if: Vec<(String, String)>,
+    /// Flags which make this flag required when none of them is present.
+    required_unless: Vec<String>,
+    /// Flags, values and the default used instead of [`FlagEntry::default`] when the flag has
+    /// the value.
+    default_if: Vec<(String, String, String)>,
     usage: String,
     typ: Flag,
 }
 
+impl FlagEntry<'_> {
+    fn new(typ: Flag, usage: &str) -> Self {
+        Self {
+            value: None,
+            source: None,
+            default: None,
+            confirm: None,
+            sensitive: false,
+            hidden: false,
+            normalizers: Vec::new(),
+            parsers: Vec::new(),
+            decoder: None,
+            decoded_value: false,
+            bytes: None,
+            stdin: false,
+            choices: Vec::new(),
+            accumulate: None,
+            multiple: false,
+            min_occurrences: 0,
+            tuple: None,
+            max_occurrences: None,
+            raw: Vec::new(),
+            env_delimiter: ',',
+            merge: ListMerge::Replace,
+            env_vars: Vec::new(),
+            env_var: None,
+            #[cfg(feature = "help")]
+            docs_url: None,
+            required_if: Vec::new(),
+            required_unless: Vec::new(),
+            default_if: Vec::new(),
+            usage: usage.to_string(),
+            typ,
+        }
+    }
+
+    /// Returns whether the flag was given by a source other than its default, which for boolean
+    /// flags means it is set.
+    fn is_present(&self) -> bool {
+        match (&self.value, self.source) {
+            (None, _) | (_, Some(Source::Default)) => false,
+            (Some(value), _) => !matches!(self.typ, Flag::Bool | Flag::TriState) || value == "true",
+        }
+    }
+
+    /// Runs the normalizers and parsers on a value, on each one for a flag taking multiple
+    /// values, returning the value to store or the reason it is invalid.
+    fn check(&self, value: &str) -> std::result::Result<String, String> {
+        let check = |value: &str| {
+            let mut value = value.to_string();
+            for normalizer in &self.normalizers {
+                value = normalizer(&value);
+            }
+            for parser in &self.parsers {
+                value = parser(&value)?;
+            }
+            Ok(value)
+        };
+        if self.multiple {
+            Ok(value
+                .split(LIST_SEPARATOR)
+                .map(check)
+                .collect::<std::result::Result<Vec<_>, String>>()?
+                .join(&LIST_SEPARATOR.to_string()))
+        } else {
+            check(value)
+        }
+    }
+
+    /// Returns the value as it should be shown in output, redacted if the flag is sensitive.
+    fn display_value(&self) -> Option<&str> {
+        match &self.value {
+            Some(_) if self.sensitive => Some(REDACTED),
+            value => value.as_deref(),
+        }
+    }
+}
+
+impl fmt::Debug for FlagEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let default = match &self.default {
+            Some(_) if self.sensitive => Some(REDACTED),
+            default => default.as_deref(),
+        };
+        f.debug_struct("FlagEntry")
+            .field("value", &self.display_value())
+            .field("source", &self.source)
+            .field("default", &default)
+            .field("confirm", &self.confirm)
+            .field("sensitive", &self.sensitive)
+            .field("hidden", &self.hidden)
+            .field("normalizers", &self.normalizers.len())
+            .field("parsers", &self.parsers.len())
+            .field("decoder", &self.decoder.is_some())
+            .field("decoded_value", &self.decoded_value)
+            .field("bytes", &self.bytes.as_ref().map(|b| b.len()))
+            .field("stdin", &self.stdin)
+            .field("choices", &self.choices)
+            .field("accumulate", &self.accumulate)
+            .field("multiple", &self.multiple)
+            .field("min_occurrences", &self.min_occurrences)
+            .field("max_occurrences", &self.max_occurrences)
+            .field("tuple", &self.tuple)
+            .field("raw", &self.raw.len())
+            .field("env_delimiter", &self.env_delimiter)
+            .field("merge", &self.merge)
+            .field("env_vars", &self.env_vars)
+            .field("env_var", &self.env_var)
+            .field("required_if", &self.required_if)
+            .field("required_unless", &self.required_unless)
+            .field("default_if", &self.default_if)
+            .field("usage", &self.usage)
+            .field("typ", &self.typ)
+            .finish()
+    }
+}
+
+/// A closure asking the user to confirm a prompt.
+type ConfirmFn = Box<dyn Fn(&str) -> bool>;
+
+/// A closure reading all of stdin.
+type StdinFn = Box<dyn Fn() -> std::io::Result<Vec<u8>>>;
+
+/// A closure looking up an environment variable.
+type EnvFn = Rc<dyn Fn(&str) -> Option<String>>;
+
+/// A closure receiving a summary of each parsed invocation.
+type AuditFn = Box<dyn Fn(&audit::Invocation)>;
+
+/// A closure returning the help of the parser it receives.
+#[cfg(feature = "help")]
+type HelpFn = Box<dyn Fn(&Parser<'_>) -> String>;
+
+/// A value parser registered by name, shared by every flag using it.
+type NamedParser = Rc<dyn Fn(&str) -> std::result::Result<String, String>>;
+
+/// A value parser referenced by name before it is registered, set during finalize.
+type ParserSlot = Rc<OnceCell<NamedParser>>;
+
 /// The arguments parser.
-pub struct Parser {
-    /// The name of the command used in the help string.
-    pub command: String,
-    flags: HashMap<String, FlagEntry>,
+pub struct Parser<'a> {
+    /// The name of the command used in the help string, the file name of the first argument
+    /// unless set with [`crate::Parser::set_name`].
+    pub command: Cow<'a, str>,
+    /// The first argument as given, usually the path the binary was run from.
+    bin_path: Cow<'a, str>,
+    flags: FlagMap<FlagEntry<'a>>,
     required: Vec<String>,
-    raw_args: Vec<String>,
-    help_fn: Option<Box<dyn Fn() -> String>>,
+    args: Box<dyn ArgSource<'a> + 'a>,
+    #[cfg(feature = "help")]
+    help_fn: Option<HelpFn>,
+    #[cfg(feature = "help")]
+    help_renderer: Option<Rc<dyn render::HelpRenderer>>,
+    #[cfg(feature = "help")]
+    help_hint: bool,
+    #[cfg(feature = "help")]
+    about: Option<String>,
+    #[cfg(feature = "help")]
+    usage: Option<String>,
+    #[cfg(feature = "help")]
+    docs_url: Option<String>,
+    #[cfg(feature = "help")]
+    examples: Vec<String>,
+    confirm_fn: Option<ConfirmFn>,
+    stdin_fn: Option<StdinFn>,
+    /// Shared with the flags expanding paths, which may be declared before it is set.
+    env_fn: Rc<RefCell<Option<EnvFn>>>,
+    audit_fn: Option<AuditFn>,
+    env_prefix: Option<String>,
+    dotenv_vars: HashMap<String, String>,
+    dotenv_override: bool,
+    config_values: HashMap<String, String>,
+    precedence: Vec<Source>,
+    expand_globs: bool,
+    profiles: bool,
+    deny_unknown_config: bool,
+    deny_positionals: bool,
+    limits: Limits,
+    classifier: Option<Rc<dyn TokenClassifier>>,
+    value_parsers: HashMap<String, NamedParser>,
+    /// The ids of arguments declared with [`Parser::arg`] and the long names of their flags.
+    arg_ids: HashMap<String, String>,
+    /// Values set with [`Parser::set_value`] before finalizing, applied over the parsed args.
+    overrides: Vec<(String, String)>,
+    finalized: bool,
+    /// Flags and the names they reference which were not registered value parsers when
+    /// declared, with the slot the parser is looked up into during finalize.
+    unknown_value_parsers: Vec<(String, String, ParserSlot)>,
+    /// Whether the flags declared with [`Parser::replay_flags`] record and replay invocations.
+    #[cfg(feature = "serde_json")]
+    replay: bool,
+    /// Values from the environment or the configuration of a replayed invocation, restored over
+    /// the resolved values with their source.
+    #[cfg(feature = "serde_json")]
+    replayed: Vec<(String, String, Source)>,
+    positionals: Vec<Cow<'a, str>>,
+    raw_args: Vec<Cow<'a, str>>,
+    declared_positionals: FlagMap<FlagEntry<'a>>,
+    /// The positional capturing the rest of the args verbatim, if any.
+    trailing: Option<String>,
+    version: Option<String>,
+    subcommands: Vec<Subcommand<'a>>,
+    /// Whether the subcommand declared with [`Parser::completions_subcommand`] prints scripts.
+    #[cfg(feature = "completions")]
+    completions_command: bool,
+    category: Option<String>,
+    handler: Option<Handler<'a>>,
+    before_hooks: Vec<Hook<'a>>,
+    after_hooks: Vec<Hook<'a>>,
+    color: ColorChoice,
+    theme: style::Theme,
+    matched: Option<usize>,
+    allow_external: bool,
+    external: Option<String>,
 }
 
-impl Parser {
+impl<'a> Parser<'a> {
     /// Initializes a [`Parser`] using [`std::env::args`] as input.
     pub fn from_env() -> Self {
-        let mut raw_args: Vec<String> = std::env::args().collect();
-        let required: Vec<String> = Vec::new();
+        Self::from_source(std::env::args())
+    }
+
+    /// Initializes a [`Parser`] from an [`ArgSource`], the first token being the command name.
+    ///
+    /// The remaining tokens are only consumed when [`crate::Parser::finalize`] is called.
+    pub fn from_source(source: impl ArgSource<'a> + 'a) -> Self {
+        let mut args = Box::new(source);
+        let bin_path = args.next_arg().unwrap_or_default();
         Self {
-            command: raw_args.remove(0),
-            flags: HashMap::new(),
-            raw_args,
-            required,
+            command: basename(&bin_path),
+            bin_path,
+            flags: FlagMap::new(),
+            required: Vec::new(),
+            args,
+            #[cfg(feature = "help")]
             help_fn: None,
+            #[cfg(feature = "help")]
+            help_renderer: None,
+            #[cfg(feature = "help")]
+            help_hint: false,
+            #[cfg(feature = "help")]
+            about: None,
+            #[cfg(feature = "help")]
+            usage: None,
+            #[cfg(feature = "help")]
+            docs_url: None,
+            #[cfg(feature = "help")]
+            examples: Vec::new(),
+            confirm_fn: None,
+            stdin_fn: None,
+            env_fn: Rc::new(RefCell::new(None)),
+            audit_fn: None,
+            env_prefix: None,
+            dotenv_vars: HashMap::new(),
+            dotenv_override: false,
+            config_values: HashMap::new(),
+            precedence: DEFAULT_PRECEDENCE.to_vec(),
+            expand_globs: false,
+            profiles: false,
+            deny_unknown_config: false,
+            deny_positionals: false,
+            limits: Limits::new(),
+            classifier: None,
+            value_parsers: HashMap::new(),
+            arg_ids: HashMap::new(),
+            overrides: Vec::new(),
+            finalized: false,
+            unknown_value_parsers: Vec::new(),
+            #[cfg(feature = "serde_json")]
+            replay: false,
+            #[cfg(feature = "serde_json")]
+            replayed: Vec::new(),
+            positionals: Vec::new(),
+            raw_args: Vec::new(),
+            declared_positionals: FlagMap::new(),
+            trailing: None,
+            version: None,
+            subcommands: Vec::new(),
+            #[cfg(feature = "completions")]
+            completions_command: false,
+            category: None,
+            handler: None,
+            before_hooks: Vec::new(),
+            after_hooks: Vec::new(),
+            color: ColorChoice::Auto,
+            theme: style::Theme::default(),
+            matched: None,
+            allow_external: false,
+            external: None,
         }
     }
 
-    /// Initializes a [`Parser`] using a given vector of strings as input.
-    pub fn from_vec(args: Vec<String>) -> Self {
-        let mut raw_args = args.clone();
-        let required: Vec<String> = Vec::new();
-        Self {
-            command: raw_args.remove(0),
-            flags: HashMap::new(),
-            raw_args,
-            required,
-            help_fn: None,
+    /// Initializes a [`Parser`] from a command line string, split using the rules of
+    /// [`crate::split::split`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::errors::Error::UnterminatedQuote`] if a quote is never closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::parse_str("head -num 3 'my file.txt'")?;
+    /// parser.required_flag("num", "this is used to set a numeric value");
+    ///
+    /// let remaining = parser.finalize()?;
+    /// assert_eq!(vec!["my file.txt"], remaining);
+    /// assert_eq!(Some(3), parser.get_value::<i32>("num"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn parse_str(line: &str) -> Result<Self> {
+        Ok(Self::from_iter(split::split(line)?))
+    }
+
+    /// Sets the name of the command used in the help string and in errors, which defaults to
+    /// the file name of the first argument. Subcommands already added are renamed to match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["/usr/local/bin/head-v2", "-num", "3"]);
+    /// assert_eq!("head-v2", parser.command);
+    ///
+    /// parser.add_subcommand("show", "show the first lines");
+    /// parser.set_name("head");
+    /// assert_eq!("head", parser.command);
+    /// assert_eq!("/usr/local/bin/head-v2", parser.bin_path());
+    /// ```
+    pub fn set_name(&mut self, name: &str) {
+        self.command = Cow::Owned(name.to_string());
+        for sub in &mut self.subcommands {
+            sub.parser.set_name(&format!("{} {}", name, sub.name));
         }
     }
 
+    /// Returns the first argument as given, usually the path the binary was run from, which
+    /// subcommands share with their parent.
+    pub fn bin_path(&self) -> &str {
+        &self.bin_path
+    }
+
     /// Defines a boolean flag.
     ///
     /// # Examples
@@ -89,13 +652,7 @@ impl Parser {
     /// use yafp::Parser;
     /// use yafp::errors::Error;
     ///
-    /// let cmd_args: Vec<String> =
-    ///     vec!["head", "-verbose", "file.txt"]
-    ///         .iter()
-    ///         .map(|x| x.to_string())
-    ///         .collect();
-    ///
-    /// let mut parser = Parser::from_vec(cmd_args);
+    /// let mut parser = Parser::from_iter(["head", "-verbose", "file.txt"]);
     /// parser.bool_flag("verbose", "this is used to get verbose output");
     ///
     /// /// This must be called before fetching flags and returns any remaining args.
@@ -112,13 +669,7 @@ impl Parser {
     /// use yafp::Parser;
     /// use yafp::errors::Error;
     ///
-    /// let cmd_args: Vec<String> =
-    ///     vec!["head", "file.txt"]
-    ///         .iter()
-    ///         .map(|x| x.to_string())
-    ///         .collect();
-    ///
-    /// let mut parser = Parser::from_vec(cmd_args);
+    /// let mut parser = Parser::from_iter(["head", "file.txt"]);
     /// parser.bool_flag("verbose", "this is used to get verbose output");
     ///
     /// /// This must be called before fetching flags and returns any remaining args.
@@ -129,15 +680,60 @@ impl Parser {
     /// assert_eq!(Some(false), verbose);
     /// # Ok::<(), Error>(())
     /// ```
-    pub fn bool_flag(&mut self, flag: &str, usage: &str) {
-        self.flags.insert(
-            flag.to_string(),
-            FlagEntry {
-                value: Some("false".to_string()),
-                usage: usage.to_string(),
-                typ: Flag::Bool,
-            },
-        );
+    pub fn bool_flag(&mut self, flag: &str, usage: &str) -> FlagBuilder<'_, 'a> {
+        self.declare(flag, Flag::Bool, usage).default_value("false")
+    }
+
+    /// Defines a tri-state boolean flag, which is `true` when given, `false` when given with a
+    /// `no-` prefix and unset otherwise.
+    ///
+    /// Unlike [`crate::Parser::bool_flag`] an unset flag has no value, so the environment and
+    /// configuration layers apply unless the flag is explicitly given either way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["build", "-no-color"]);
+    /// parser.tristate_flag("color", "this is used to enable colored output");
+    /// parser.tristate_flag("cache", "this is used to enable the build cache");
+    /// parser.config_value("color", "true");
+    ///
+    /// parser.finalize()?;
+    ///
+    /// /// The command line disables color despite the configuration.
+    /// assert_eq!(Some(false), parser.get_value::<bool>("color"));
+    /// /// The cache flag is not mentioned anywhere.
+    /// assert_eq!(None, parser.get_value::<bool>("cache"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn tristate_flag(&mut self, flag: &str, usage: &str) -> FlagBuilder<'_, 'a> {
+        self.declare(flag, Flag::TriState, usage)
+    }
+
+    /// Defines a flag without a value which counts how many times it is given, such as `-v -v`
+    /// for more verbose output. It is `0` when not given, and the environment and configuration
+    /// layers give the count as a number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["ssh", "-v", "-v", "host"]);
+    /// parser.count_flag("v", "this is used to get more verbose output");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(2), parser.get_value::<u8>("v"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn count_flag(&mut self, flag: &str, usage: &str) -> FlagBuilder<'_, 'a> {
+        self.declare(flag, Flag::Count, usage)
+            .default_value("0")
+            .integer()
     }
 
     /// Defines a required flag that accepts a value.
@@ -155,13 +751,7 @@ impl Parser {
     /// use yafp::Parser;
     /// use yafp::errors::Error;
     ///
-    /// let cmd_args: Vec<String> =
-    ///     vec!["head", "-file", "file.txt"]
-    ///         .iter()
-    ///         .map(|x| x.to_string())
-    ///         .collect();
-    ///
-    /// let mut parser = Parser::from_vec(cmd_args);
+    /// let mut parser = Parser::from_iter(["head", "-file", "file.txt"]);
     /// parser.required_flag("file", "this is used to set the path for a file");
     ///
     /// /// This must be called before fetching flags and returns any remaining args.
@@ -173,41 +763,125 @@ impl Parser {
     /// # Ok::<(), Error>(())
     /// ```
     ///
-    pub fn required_flag(&mut self, flag: &str, usage: &str) {
-        self.required.push(flag.to_string());
-        self.flags.insert(
-            flag.to_string(),
-            FlagEntry {
-                value: None,
-                usage: usage.to_string(),
-                typ: Flag::Value,
-            },
-        );
+    pub fn required_flag(&mut self, flag: &str, usage: &str) -> FlagBuilder<'_, 'a> {
+        let builder = self.declare(flag, Flag::Value, usage);
+        builder.parser.required.push(flag.to_string());
+        builder
     }
 
     /// Defines an optional flag that accepts a value.
     ///
     /// Similar to [`crate::Parser::required_flag`] but [`crate::Parser::finalize`] will not return
     /// an error result if the flag is missing.
-    pub fn optional_flag(&mut self, flag: &str, usage: &str) {
-        self.flags.insert(
-            flag.to_string(),
-            FlagEntry {
-                value: None,
-                usage: usage.to_string(),
-                typ: Flag::Value,
-            },
-        );
+    pub fn optional_flag(&mut self, flag: &str, usage: &str) -> FlagBuilder<'_, 'a> {
+        self.declare(flag, Flag::Value, usage)
+    }
+
+    fn declare(&mut self, flag: &str, typ: Flag, usage: &str) -> FlagBuilder<'_, 'a> {
+        self.forget(flag);
+        self.flags
+            .insert(flag.to_string(), FlagEntry::new(typ, usage));
+        FlagBuilder {
+            parser: self,
+            flag: flag.to_string(),
+            positional: false,
+        }
+    }
+
+    /// Removes a declared flag, returning whether it was declared, so flags declared by another
+    /// crate can be left out of the command. The flag is then rejected like any unknown flag, see
+    /// [`crate::FlagBuilder::hidden`] to keep accepting it without listing it.
+    ///
+    /// Declaring a flag again replaces its definition instead, keeping its place in the help.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// /// Declares the flags shared by every tool of a vendor.
+    /// fn vendor_flags(parser: &mut Parser) {
+    ///     parser.bool_flag("debug", "this is used to get debug output");
+    ///     parser.required_flag("region", "this is used to set the region");
+    /// }
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// vendor_flags(&mut parser);
+    /// assert!(parser.remove_flag("debug"));
+    /// assert!(!parser.remove_flag("trace"));
+    /// parser
+    ///     .optional_flag("region", "this is used to set the region")
+    ///     .default_value("eu-west-1");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some("eu-west-1".to_string()), parser.get_value("region"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn remove_flag(&mut self, flag: &str) -> bool {
+        self.forget(flag);
+        self.flags.remove(flag).is_some()
+    }
+
+    /// Returns the builder of a declared flag, to change its definition without declaring it
+    /// again, such as setting another default. Returns `None` if the flag is not declared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// parser
+    ///     .optional_flag("retries", "this is used to set the number of retries")
+    ///     .integer()
+    ///     .default_value("3");
+    /// if let Some(retries) = parser.configure_flag("retries") {
+    ///     retries.default_value("5");
+    /// }
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(5), parser.get_value::<u32>("retries"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn configure_flag(&mut self, flag: &str) -> Option<FlagBuilder<'_, 'a>> {
+        if !self.flags.contains_key(flag) {
+            return None;
+        }
+        Some(FlagBuilder {
+            parser: self,
+            flag: flag.to_string(),
+            positional: false,
+        })
+    }
+
+    /// Forgets what the parser keeps about a flag outside of its entry.
+    fn forget(&mut self, flag: &str) {
+        self.required.retain(|required| required != flag);
+        self.unknown_value_parsers.retain(|(key, _, _)| key != flag);
+        self.overrides.retain(|(key, _)| key != flag);
+    }
+
+    /// Returns the flags shown in help, completions and specs, sorted by name.
+    #[cfg_attr(not(any(feature = "help", feature = "completions")), allow(dead_code))]
+    fn visible_flags(&self) -> Vec<(&String, &FlagEntry<'a>)> {
+        let mut flags = self.flags.sorted();
+        flags.retain(|(_, entry)| !entry.hidden);
+        flags
     }
 
-    /// Returns the value of a flag.
+    /// Returns the value of a flag, or the last value of a flag taking multiple values.
     pub fn get_value<T>(&self, flag: &str) -> Option<T>
     where
         T: FromStr,
         <T as FromStr>::Err: Display,
     {
-        match self.flags.get(flag) {
+        match self.entry(flag) {
             Some(v) => match &v.value {
+                Some(value) if v.multiple => {
+                    FromStr::from_str(value.rsplit(LIST_SEPARATOR).next()?).ok()
+                }
                 Some(v) => match FromStr::from_str(v) {
                     Ok(v) => Some(v),
                     Err(_) => None,
@@ -218,226 +892,1761 @@ impl Parser {
         }
     }
 
-    /// Returns a string with the generated flag information.
-    pub fn help_flags(&self) -> String {
-        let mut flag_keys: Vec<String> = Vec::new();
-        for (key, _) in self.flags.iter() {
-            flag_keys.push(key.to_string());
+    /// Returns every value of a flag declared with [`crate::FlagBuilder::multiple`], or `None` if
+    /// it is not set or any value fails to parse.
+    ///
+    /// A flag not taking multiple values returns its value as the only item.
+    pub fn get_values<T>(&self, flag: &str) -> Option<Vec<T>>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        let entry = self.entry(flag)?;
+        let value = entry.value.as_ref()?;
+        if !entry.multiple {
+            return FromStr::from_str(value).ok().map(|v| vec![v]);
         }
-        // Ensure flag help is deterministic by sorting flag names.
-        flag_keys.sort();
+        value
+            .split(LIST_SEPARATOR)
+            .map(|v| FromStr::from_str(v).ok())
+            .collect()
+    }
 
-        let mut flag_help_parts: Vec<String> = Vec::new();
-        for key in flag_keys {
-            let flag_entry = self.flags.get(&key).unwrap();
-            match flag_entry.typ {
-                Flag::Value => {
-                    let usage = format!("{} {}", key, "value");
-                    flag_help_parts.push(format!("  -{}", usage));
-                }
-                _ => flag_help_parts.push(format!("  -{}", key)),
-            }
-            flag_help_parts.push(format!("\t{}", flag_entry.usage));
+    /// Returns the value of a flag deserialized from JSON, see [`crate::FlagBuilder::json`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["logs", "-filter", r#"{"level":"error"}"#]);
+    /// parser
+    ///     .optional_flag("filter", "this is used to filter log entries")
+    ///     .json();
+    ///
+    /// parser.finalize()?;
+    /// let filter: HashMap<String, String> = parser.get_json("filter").unwrap();
+    /// assert_eq!(Some("error"), filter.get("level").map(String::as_str));
+    /// # Ok::<(), Error>(())
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn get_json<T>(&self, flag: &str) -> Option<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let value = self.entry(flag)?.value.as_ref()?;
+        serde_json::from_str(value).ok()
+    }
+
+    /// Returns the value of a flag as bytes, decoded if the flag was declared with a decoder such
+    /// as [`crate::FlagBuilder::base64`] and otherwise the bytes of the value as given.
+    pub fn get_bytes(&self, flag: &str) -> Option<Vec<u8>> {
+        let entry = self.entry(flag)?;
+        match &entry.bytes {
+            Some(bytes) => Some(bytes.clone()),
+            None => entry.value.as_ref().map(|v| v.as_bytes().to_vec()),
         }
-        format!("{}\n", flag_help_parts.join("\n"))
     }
 
-    /// Returns a string with the usage string.
+    /// Returns the positional arguments given to this parser, excluding the subcommand name and
+    /// anything given to the subcommand.
     ///
-    /// If you use positional arguments it might be useful to define a custom function
-    /// which prints the usage line and then prints the string returned by [`crate::Parser::help_flags`].
+    /// Positionals declared with [`crate::Parser::positional`] are read by name with
+    /// [`crate::Parser::get_value`] instead.
+    pub fn positionals(&self) -> &[Cow<'a, str>] {
+        &self.positionals
+    }
+
+    /// Returns every token read after the command name, as given and in order, including the
+    /// tokens parsed by a subcommand. Together with [`crate::Parser::command`] this is the exact
+    /// invocation, for example to run it again. It includes the values of sensitive flags, see
+    /// [`crate::Parser::redacted_args`] to log it instead.
     ///
     /// # Examples
     ///
-    /// ## Default Help
     /// ```
     /// use yafp::Parser;
     /// use yafp::errors::Error;
     ///
-    /// let cmd_args: Vec<String> =
-    ///     vec!["head", "-verbose", "file.txt"]
-    ///         .iter()
-    ///         .map(|x| x.to_string())
-    ///         .collect();
-    ///
-    /// let mut parser = Parser::from_vec(cmd_args);
-    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// let mut parser = Parser::from_iter(["head", "-num", "3", "-num", "5", "file.txt"]);
+    /// parser.optional_flag("num", "this is used to set the number of lines");
     ///
-    /// /// This must be called before fetching flags and returns any remaining args.
     /// parser.finalize()?;
-    ///
-    /// /// Using the default help function does not allow you to specify the positional args but let's you get
-    /// /// the basic help working.
-    /// let help: String = parser.help();
-    /// assert_eq!(String::from("Usage: head [options...]\n  -verbose\n\tthis is used to get verbose output\n"), help);
+    /// assert_eq!(vec!["-num", "3", "-num", "5", "file.txt"], parser.raw_args());
+    /// assert_eq!(vec!["-num", "5"], parser.raw_tokens("num"));
     /// # Ok::<(), Error>(())
     /// ```
+    pub fn raw_args(&self) -> &[Cow<'a, str>] {
+        &self.raw_args
+    }
+
+    /// Returns the command line tokens the value of a flag or declared positional was parsed
+    /// from, such as the flag followed by its value, or every occurrence for a flag taking
+    /// multiple values. It is empty when the value did not come from the command line.
+    pub fn raw_tokens(&self, flag: &str) -> &[Cow<'a, str>] {
+        self.entry(flag).map_or(&[], |entry| &entry.raw)
+    }
+
+    /// Returns [`crate::Parser::raw_args`] with the values of flags and positionals declared
+    /// [`crate::FlagBuilder::sensitive`] redacted, here or in the subcommand given, so the
+    /// invocation can be logged.
+    ///
+    /// # Examples
     ///
-    /// ## Custom Help
     /// ```
     /// use yafp::Parser;
     /// use yafp::errors::Error;
     ///
-    /// let cmd_args: Vec<String> =
-    ///     vec!["head", "-verbose", "file.txt"]
-    ///         .iter()
-    ///         .map(|x| x.to_string())
-    ///         .collect();
-    ///
-    /// let mut parser = Parser::from_vec(cmd_args);
-    /// parser.bool_flag("verbose", "this is used to get verbose output");
-    ///
-    /// let command = parser.command.to_string();
-    /// let help_flags = parser.help_flags();
-    /// parser.set_help_fn(move || {
-    ///   let help_string = format!("Usage: {} [options...] <file>", command);
-    ///   format!("{}\n{}", help_string, help_flags)
-    /// });
+    /// let mut parser = Parser::from_iter(["deploy", "-token", "abc", "-env", "prod"]);
+    /// parser
+    ///     .optional_flag("token", "this is used to authenticate")
+    ///     .sensitive();
+    /// parser.optional_flag("env", "this is used to set the environment");
     ///
-    /// /// This must be called before fetching flags and returns any remaining args.
     /// parser.finalize()?;
-    ///
-    /// /// Using the default help function does not allow you to specify the positional args but let's you get
-    /// /// the basic help working.
-    /// let help: String = parser.help();
-    /// assert_eq!(String::from("Usage: head [options...] <file>\n  -verbose\n\tthis is used to get verbose output\n"), help);
+    /// assert_eq!(vec!["-token", "********", "-env", "prod"], parser.redacted_args());
     /// # Ok::<(), Error>(())
     /// ```
-    ///
-    pub fn help(&self) -> String {
-        match &self.help_fn {
-            Some(f) => f(),
-            None => {
-                let help_string = format!("Usage: {} [options...]", self.command);
-                format!("{}\n{}", help_string, self.help_flags())
-            }
-        }
+    pub fn redacted_args(&self) -> Vec<Cow<'a, str>> {
+        self.redact(&self.raw_args)
     }
 
-    /// Accepts a closure that defines a custom help function, for an example usage check the [custom help example].
-    ///
-    /// [custom help example]: crate::Parser#custom-help
-    pub fn set_help_fn(&mut self, f: impl Fn() -> String + 'static) {
-        self.help_fn = Some(Box::new(f));
+    /// Returns [`crate::Parser::raw_tokens`] with the values redacted if the flag is sensitive,
+    /// see [`crate::Parser::redacted_args`].
+    pub fn redacted_tokens(&self, flag: &str) -> Vec<Cow<'a, str>> {
+        self.redact(self.raw_tokens(flag))
     }
 
-    fn consume_flag<I>(&mut self, flag: String, it: &mut Peekable<I>) -> Result<()>
-    where
-        I: Iterator<Item = String>,
-    {
-        let flag = flag[1..].to_string();
-        if self.flags.contains_key(&flag) {
-            let arg = self.flags.get(&flag).unwrap();
-            match arg.typ {
-                Flag::Bool => {
-                    self.flags.insert(
-                        flag.to_string(),
-                        FlagEntry {
-                            value: Some("true".to_string()),
-                            usage: arg.usage.to_string(),
-                            typ: arg.typ,
-                        },
-                    );
-                    Ok(())
-                }
-                Flag::Value => {
-                    let next_token = it.peek();
-                    let next_token = match next_token {
-                        Some(_) => it.next(),
-                        None => None,
-                    };
-                    match next_token {
-                        Some(value) => {
-                            self.flags.insert(
-                                flag.to_string(),
-                                FlagEntry {
-                                    value: Some(value.to_string()),
-                                    usage: arg.usage.to_string(),
-                                    typ: arg.typ,
-                                },
-                            );
-                            Ok(())
-                        }
-                        None => {
-                            return Err(Error::MissingValue(flag));
-                        }
-                    }
+    /// Replaces the tokens given as the value of a sensitive flag or positional, here or in the
+    /// subcommand given, with a placeholder.
+    fn redact(&self, tokens: &[Cow<'a, str>]) -> Vec<Cow<'a, str>> {
+        let mut sensitive: Vec<&str> = Vec::new();
+        let mut parser = Some(self);
+        while let Some(current) = parser {
+            for (key, entry) in current.flags.iter() {
+                if !entry.sensitive || !matches!(entry.typ, Flag::Value) {
+                    continue;
                 }
+                let values = entry.raw.iter().filter(|token| {
+                    !matches!(current.classify(token), Token::Flag(flag) if flag == key.as_str())
+                });
+                sensitive.extend(values.map(|token| token.as_ref()));
             }
-        } else {
-            if flag == "help" {
-                eprintln!("{}", self.help())
+            for (_, entry) in current.declared_positionals.iter() {
+                if entry.sensitive {
+                    sensitive.extend(entry.raw.iter().map(|token| token.as_ref()));
+                }
             }
-            exit(0);
+            parser = current.subcommand().map(|(_, sub)| sub);
         }
+        tokens
+            .iter()
+            .map(|token| match sensitive.contains(&token.as_ref()) {
+                true => Cow::Borrowed(REDACTED),
+                false => token.clone(),
+            })
+            .collect()
     }
 
-    fn parse_next<I>(&mut self, it: &mut Peekable<I>) -> Result<Option<String>>
+    /// Returns a positional argument given to this parser, parsed like
+    /// [`crate::Parser::get_value`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "-verbose", "remote", "add", "origin", "3"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser
+    ///     .add_subcommand("remote", "manage remotes")
+    ///     .add_subcommand("add", "add a remote");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(vec!["remote", "add"], parser.subcommand_path());
+    ///
+    /// let (_, remote) = parser.subcommand().unwrap();
+    /// let (_, add) = remote.subcommand().unwrap();
+    /// assert_eq!(Some("origin".to_string()), add.get_positional(0));
+    /// assert_eq!(Some(3), add.get_positional::<u32>(1));
+    /// assert!(parser.positionals().is_empty());
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn get_positional<T>(&self, index: usize) -> Option<T>
     where
-        I: Iterator<Item = String>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
     {
-        match it.next() {
-            Some(token) => {
-                let result = if token.starts_with("-") {
-                    match self.consume_flag(token.to_string(), it) {
-                        Ok(_) => Ok(None),
-                        Err(e) => Err(e),
-                    }
-                } else {
-                    Ok(Some(token.to_string()))
-                };
-                result
-            }
-            None => Ok(None),
-        }
+        FromStr::from_str(self.positionals.get(index)?).ok()
     }
 
-    /// Parses the arguments taking into account all defined flags and returns any remaining
-    /// non-flag arguments.
+    /// Accepts a closure that asks the user to confirm a prompt, used for flags declared with
+    /// [`crate::FlagBuilder::confirm`].
     ///
-    /// # Errors
-    ///
-    /// Depending on the flags set, it returns a variant of [`crate::errors::Error`].
-    pub fn finalize(&mut self) -> Result<Vec<String>> {
-        let mut remaining: Vec<String> = Vec::new();
-
-        let raw_args = self.raw_args.clone();
-        if raw_args.is_empty() {
-            eprintln!("{}", self.help());
-            exit(0);
-        }
+    /// By default the prompt is written to stderr and a `y` or `yes` answer is read from stdin.
+    pub fn set_confirm_fn(&mut self, f: impl Fn(&str) -> bool + 'static) {
+        self.confirm_fn = Some(Box::new(f));
+    }
 
-        let mut it = raw_args.iter().cloned().peekable();
-        while let Some(_) = it.peek() {
-            match self.parse_next(&mut it) {
-                Ok(value) => {
-                    if let Some(v) = value {
-                        remaining.push(v);
-                    }
-                }
-                Err(e) => return Err(e),
-            }
-        }
+    /// Accepts a closure that reads all of stdin, used for flags declared with
+    /// [`crate::FlagBuilder::stdin`].
+    ///
+    /// By default the process stdin is read, this is mostly useful in tests.
+    pub fn set_stdin_fn(&mut self, f: impl Fn() -> std::io::Result<Vec<u8>> + 'static) {
+        self.stdin_fn = Some(Box::new(f));
+    }
 
-        // Check for required flags.
-        for flag in &self.required {
-            if !self.flags.contains_key(flag) {
-                return Err(Error::MissingArgument(flag.to_string()));
-            }
+    /// Accepts a closure that looks up an environment variable, used for the environment
+    /// fallback of flags, see [`crate::Parser::env_prefix`], the paths searched by
+    /// [`crate::Parser::discover_config`] and [`crate::FlagBuilder::expand_path`]. Subcommands
+    /// without a closure of their own inherit it once matched by [`crate::Parser::finalize`].
+    ///
+    /// By default the process environment is read, this is mostly useful in tests which would
+    /// otherwise change the environment of every test running in parallel.
+    pub fn set_env_fn(&mut self, f: impl Fn(&str) -> Option<String> + 'static) {
+        *self.env_fn.borrow_mut() = Some(Rc::new(f));
+    }
 
-            match self.flags.get(flag) {
-                Some(entry) => {
-                    if let None = entry.value {
+    /// Uses the given variables as the environment instead of the process environment, see
+    /// [`crate::Parser::set_env_fn`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["serve"]);
+    /// parser.env_prefix("MYAPP");
+    /// parser.set_env(HashMap::from([("MYAPP_PORT".to_string(), "8080".to_string())]));
+    /// parser.optional_flag("port", "this is used to set the port");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(8080), parser.get_value::<u16>("port"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn set_env(&mut self, vars: HashMap<String, String>) {
+        self.set_env_fn(move |name| vars.get(name).cloned());
+    }
+
+    /// Sets the version of the command, reported through the `-version` flag.
+    ///
+    /// Subcommands inherit the version unless they set their own, so `tool build -version` works
+    /// without declaring anything on the subcommand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "build", "-version"]);
+    /// parser.set_version("1.2.0");
+    /// parser.add_subcommand("build", "compile the project");
+    ///
+    /// assert_eq!(Err(Error::VersionRequested), parser.finalize());
+    /// assert_eq!(Some("1.2.0"), parser.version());
+    /// ```
+    pub fn set_version(&mut self, version: &str) {
+        self.version = Some(version.to_string());
+    }
+
+    /// Returns the version to report, which is the version of the subcommand given on the
+    /// command line if it sets one and otherwise the version of this parser.
+    pub fn version(&self) -> Option<&str> {
+        self.subcommand()
+            .and_then(|(_, sub)| sub.version())
+            .or(self.version.as_deref())
+    }
+
+    /// Asks for confirmation of every set flag that requires it.
+    fn confirm(&self) -> Result<()> {
+        if self.get_value::<bool>(CONFIRM_FLAG) == Some(true) {
+            return Ok(());
+        }
+
+        // Prompts are asked in declaration order.
+        for (key, entry) in self.flags.iter() {
+            let prompt = match &entry.confirm {
+                Some(prompt) if entry.value.as_deref() == Some("true") => prompt,
+                _ => continue,
+            };
+            let confirmed = match &self.confirm_fn {
+                Some(f) => f(prompt),
+                None => confirm_stdin(prompt),
+            };
+            if !confirmed {
+                return Err(Error::NotConfirmed(key.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets a prefix used to look up flags in the environment when they are not set on the command
+    /// line, see [`crate::Parser::set_precedence`].
+    ///
+    /// Each flag is looked up as `<PREFIX>_<FLAG>`, with the flag name uppercased and dashes
+    /// replaced by underscores, for example `-max-workers` maps to `MYAPP_MAX_WORKERS`. If that
+    /// variable is not set, `MYAPP_MAX_WORKERS_FILE` may name a file whose contents are the value,
+    /// following the convention for container secrets, a file which cannot be read is an error of
+    /// type [`crate::errors::Error::InvalidValue`]. Boolean flags accept `1`, `0`, `true`,
+    /// `false`, `yes`, `no`, `on` and `off` in any case, anything else is also an error of type
+    /// [`crate::errors::Error::InvalidValue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["head", "file.txt"]);
+    /// parser.env_prefix("MYAPP");
+    /// parser.set_env(HashMap::from([("MYAPP_MAX_WORKERS".to_string(), "4".to_string())]));
+    /// parser.required_flag("max-workers", "this is used to set the number of workers");
+    ///
+    /// /// This must be called before fetching flags and returns any remaining args.
+    /// parser.finalize()?;
+    ///
+    /// /// Since the flag is not set on the command line the environment value is used.
+    /// assert_eq!(Some(4), parser.get_value::<u32>("max-workers"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn env_prefix(&mut self, prefix: &str) {
+        self.env_prefix = Some(prefix.to_string());
+    }
+
+    /// Loads variables from a `.env` file into the environment fallback.
+    ///
+    /// Variables already set in the process environment take precedence over the ones loaded from
+    /// the file, matching the usual dotenv behavior, unless [`crate::Parser::dotenv_override`] is
+    /// set. When several files are loaded, a variable from a later file overrides the same one
+    /// from an earlier file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or contains a line that is not a `KEY=VALUE`
+    /// pair.
+    #[cfg(feature = "dotenv")]
+    pub fn load_dotenv(&mut self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let contents = std::fs::read_to_string(path)?;
+        for (key, value) in dotenv::parse(&contents)? {
+            self.dotenv_vars.insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// Loads the layered `.env` files found in a directory into the environment fallback.
+    ///
+    /// The files are loaded in the order `.env`, `.env.local`, `.env.<mode>` and
+    /// `.env.<mode>.local`, the last two only when a mode such as `development` or `test` is
+    /// given, so each file overrides the variables of the ones before it. Files which do not exist
+    /// are skipped and the paths of the loaded ones are returned in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file exists but cannot be read or is not valid, see
+    /// [`crate::Parser::load_dotenv`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_env();
+    /// parser.env_prefix("MYAPP");
+    /// let loaded = parser.load_dotenv_layers(".", Some("development"))?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "dotenv")]
+    pub fn load_dotenv_layers(
+        &mut self,
+        dir: impl AsRef<std::path::Path>,
+        mode: Option<&str>,
+    ) -> std::io::Result<Vec<std::path::PathBuf>> {
+        let mut loaded = Vec::new();
+        for name in dotenv::layers(mode) {
+            let path = dir.as_ref().join(name);
+            match self.load_dotenv(&path) {
+                Ok(()) => loaded.push(path),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Makes variables loaded from `.env` files take precedence over the process environment.
+    ///
+    /// By default a variable set in the process environment wins over the same one loaded from a
+    /// file, this reverses that order like the override option of dotenv tooling.
+    #[cfg(feature = "dotenv")]
+    pub fn dotenv_override(&mut self) {
+        self.dotenv_override = true;
+    }
+
+    /// Sets a value for a flag in the configuration layer.
+    ///
+    /// Configuration values are used when a flag is not given by a source with a higher
+    /// precedence, see [`crate::Parser::set_precedence`].
+    pub fn config_value(&mut self, flag: &str, value: &str) {
+        self.config_values
+            .insert(flag.to_string(), value.to_string());
+    }
+
+    /// Loads flag values from a configuration file into the configuration layer, with the format
+    /// selected by the file extension: `.ini`, `.toml`, or `.yaml` and `.yml`.
+    ///
+    /// Keys of the file are flag names, keys of nested tables and INI sections are joined with a
+    /// `.` and lists are joined with a `,`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if the extension is not a
+    /// known format, and otherwise the errors of [`crate::Parser::load_config_as`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// # #[cfg(feature = "serde_yaml")] {
+    /// let path = std::env::temp_dir().join("yafp-load-config.yaml");
+    /// std::fs::write(&path, "lines: 20\nverbose: true\n")?;
+    ///
+    /// let mut parser = Parser::from_iter(["head", "file.txt"]);
+    /// parser.optional_flag("lines", "this is used to set the number of lines");
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser.load_config(&path)?;
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(20), parser.get_value::<u32>("lines"));
+    /// assert_eq!(Some(true), parser.get_value::<bool>("verbose"));
+    /// # }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(any(feature = "ini", feature = "toml", feature = "serde_yaml"))]
+    pub fn load_config(&mut self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unknown configuration format for '{}'", path.display()),
+            )
+        })?;
+        self.load_config_as(path, format)
+    }
+
+    /// Searches the standard locations for the configuration file of an application and loads the
+    /// first one found into the configuration layer, returning its path.
+    ///
+    /// A `config` file with any enabled extension, such as `config.toml`, is searched in the
+    /// `app` directory of `$XDG_CONFIG_HOME`, `~/.config`, the platform configuration directory
+    /// and each of `$XDG_CONFIG_DIRS`, then an `app` file such as `app.toml` is searched in the
+    /// current directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`crate::Parser::load_config`] for the file found.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_env();
+    /// parser.optional_flag("lines", "this is used to set the number of lines");
+    /// if let Some(path) = parser.discover_config("head")? {
+    ///     eprintln!("using configuration from {}", path.display());
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(any(feature = "ini", feature = "toml", feature = "serde_yaml"))]
+    pub fn discover_config(&mut self, app: &str) -> std::io::Result<Option<std::path::PathBuf>> {
+        for path in config::search_paths(app, &|name| lookup_env(&self.env_fn, name)) {
+            if path.is_file() {
+                self.load_config(&path)?;
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns a configuration file in a format with an entry for every flag, preceded by its
+    /// usage as a comment, so a tool can scaffold a configuration for its users.
+    ///
+    /// Flags are set to their default, flags without a default or with a sensitive one are
+    /// commented out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::{ConfigFormat, Parser};
+    ///
+    /// let mut parser = Parser::from_iter(["head"]);
+    /// parser
+    ///     .optional_flag("lines", "this is used to set the number of lines")
+    ///     .default_value("10");
+    /// parser.optional_flag("name", "this is used to set a name");
+    ///
+    /// # #[cfg(feature = "toml")]
+    /// assert_eq!(
+    ///     parser.dump_default_config(ConfigFormat::Toml),
+    ///     [
+    ///         "# this is used to set the number of lines\n",
+    ///         "lines = \"10\"\n",
+    ///         "\n",
+    ///         "# this is used to set a name\n",
+    ///         "# name = \"\"\n",
+    ///     ]
+    ///     .concat()
+    /// );
+    /// ```
+    #[cfg(any(feature = "ini", feature = "toml", feature = "serde_yaml"))]
+    pub fn dump_default_config(&self, format: ConfigFormat) -> String {
+        let mut out = String::new();
+        for (i, (key, entry)) in self.flags.sorted().into_iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            let default = entry.default.as_deref().filter(|_| !entry.sensitive);
+            config::write_entry(&mut out, format, key, &entry.usage, default);
+        }
+        out
+    }
+
+    /// Loads flag values from a configuration file in the given format into the configuration
+    /// layer, see [`crate::Parser::load_config`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, or an error of kind
+    /// [`std::io::ErrorKind::InvalidData`] if it is not valid in the format.
+    #[cfg(any(feature = "ini", feature = "toml", feature = "serde_yaml"))]
+    pub fn load_config_as(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+        format: ConfigFormat,
+    ) -> std::io::Result<()> {
+        let contents = std::fs::read_to_string(path)?;
+        for (key, value) in config::parse(&contents, format)? {
+            self.config_values.insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// Defines a `-profile` flag selecting a profile of the configuration layer, whose values
+    /// override the base configuration.
+    ///
+    /// The values of a profile are the configuration keys prefixed with `profile.<name>.`, such as
+    /// the keys of a `[profile.staging]` section in a configuration file. Selecting a profile
+    /// which is not defined is an error of type [`crate::errors::Error::InvalidValue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["deploy", "-profile", "staging"]);
+    /// parser.config_profiles();
+    /// parser.optional_flag("host", "this is used to set the host");
+    /// parser.optional_flag("port", "this is used to set the port");
+    /// parser.config_value("host", "prod.example.com");
+    /// parser.config_value("port", "443");
+    /// parser.config_value("profile.staging.host", "staging.example.com");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some("staging.example.com".to_string()), parser.get_value("host"));
+    /// assert_eq!(Some(443), parser.get_value::<u16>("port"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn config_profiles(&mut self) -> FlagBuilder<'_, 'a> {
+        self.profiles = true;
+        self.optional_flag(
+            PROFILE_FLAG,
+            "this is used to select a configuration profile",
+        )
+    }
+
+    /// Returns the sorted configuration keys which do not match any declared flag, such as a
+    /// misspelled key in a configuration file, so the caller can warn about them.
+    ///
+    /// Keys of a profile, see [`crate::Parser::config_profiles`], are matched without their
+    /// `profile.<name>.` prefix. See [`crate::Parser::deny_unknown_config_keys`] to reject them
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["head"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser.config_value("verbos", "true");
+    ///
+    /// assert_eq!(vec!["verbos"], parser.unknown_config_keys());
+    /// ```
+    pub fn unknown_config_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .config_values
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !self.flags.contains_key(self.config_key_flag(key)))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Returns the flag a configuration key sets, without the `profile.<name>.` prefix.
+    fn config_key_flag<'k>(&self, key: &'k str) -> &'k str {
+        match key.strip_prefix("profile.") {
+            Some(rest) if self.profiles => rest.split_once('.').map_or(rest, |(_, flag)| flag),
+            _ => key,
+        }
+    }
+
+    /// Makes [`crate::Parser::finalize`] return an error of type
+    /// [`crate::errors::Error::UnknownConfigKey`] for the first configuration key which does not
+    /// match any declared flag, see [`crate::Parser::unknown_config_keys`].
+    pub fn deny_unknown_config_keys(&mut self) {
+        self.deny_unknown_config = true;
+    }
+
+    /// Sets the order in which sources are consulted when resolving flag values, sources left
+    /// out are ignored.
+    ///
+    /// The default order is command line, environment, configuration and then defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::{Parser, Source};
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["head", "-lines", "5"]);
+    /// parser.optional_flag("lines", "this is used to set the number of lines");
+    /// parser.config_value("lines", "20");
+    ///
+    /// /// Let the configuration win over the command line.
+    /// parser.set_precedence(&[Source::Config, Source::Cli, Source::Env, Source::Default]);
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(20), parser.get_value::<u32>("lines"));
+    /// assert_eq!(Some(Source::Config), parser.value_source("lines"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn set_precedence(&mut self, precedence: &[Source]) {
+        self.precedence = precedence.to_vec();
+    }
+
+    /// Returns the order in which sources are consulted when resolving flag values.
+    pub fn precedence(&self) -> &[Source] {
+        &self.precedence
+    }
+
+    /// Expands glob patterns such as `*.txt` in the remaining args returned by
+    /// [`crate::Parser::finalize`] into the matching paths.
+    ///
+    /// This is meant for platforms where the shell does not expand globs, such as Windows. Patterns
+    /// support `*`, `?` and `[...]` classes and are kept as is when nothing matches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["cat", "src/*.rs"]);
+    /// parser.expand_globs();
+    ///
+    /// let remaining = parser.finalize()?;
+    /// assert!(remaining.iter().any(|arg| arg == "src/lib.rs"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn expand_globs(&mut self) {
+        self.expand_globs = true;
+    }
+
+    /// Makes [`crate::Parser::finalize`] return an error of type
+    /// [`crate::errors::Error::UnexpectedArgument`] for the first positional argument, for
+    /// commands which only take flags.
+    ///
+    /// Args selecting a subcommand are still accepted, the subcommand parsing the rest with its
+    /// own setting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["status", "-verbose", "now"]);
+    /// parser.deny_positionals();
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    ///
+    /// assert_eq!(
+    ///     parser.finalize(),
+    ///     Err(Error::UnexpectedArgument("now".to_string()))
+    /// );
+    /// ```
+    pub fn deny_positionals(&mut self) {
+        self.deny_positionals = true;
+    }
+
+    /// Sets limits on the args consumed by [`crate::Parser::finalize`], which returns an error
+    /// for the first arg exceeding them, see [`crate::args::Limits`].
+    ///
+    /// This is meant for services passing untrusted strings to the parser. The limits also apply
+    /// to the args left to a subcommand, which may set stricter ones.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Registers a value parser under a name, so flags can use it with
+    /// [`crate::FlagBuilder::value_parser`] instead of repeating it. Flags may refer to it before
+    /// it is registered, and subcommands inherit the registered parsers.
+    ///
+    /// The closure returns the value to store, for example in a canonical form, or the reason the
+    /// value is invalid, which should not repeat the value since it may be sensitive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["serve", "-timeout", "30s", "-grace", "5s"]);
+    /// parser.register_value_parser("duration", |v| {
+    ///     v.strip_suffix('s')
+    ///         .and_then(|secs| secs.parse::<u64>().ok())
+    ///         .map(|secs| secs.to_string())
+    ///         .ok_or_else(|| "expected a number of seconds such as 30s".to_string())
+    /// });
+    /// parser
+    ///     .optional_flag("timeout", "this is used to set the request timeout")
+    ///     .value_parser("duration");
+    /// parser
+    ///     .optional_flag("grace", "this is used to set the shutdown grace period")
+    ///     .value_parser("duration");
+    ///
+    /// parser.finalize()?;
+    /// let timeout = parser.get_value::<u64>("timeout").map(Duration::from_secs);
+    /// assert_eq!(Some(Duration::from_secs(30)), timeout);
+    /// assert_eq!(Some(5), parser.get_value::<u64>("grace"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn register_value_parser(
+        &mut self,
+        name: &str,
+        f: impl Fn(&str) -> std::result::Result<String, String> + 'static,
+    ) {
+        self.value_parsers.insert(name.to_string(), Rc::new(f));
+    }
+
+    /// Looks up the value parsers flags referred to before they were registered.
+    fn link_value_parsers(&self) -> Result<()> {
+        for (key, name, slot) in &self.unknown_value_parsers {
+            match self.value_parsers.get(name) {
+                Some(parser) => {
+                    let _ = slot.set(Rc::clone(parser));
+                }
+                None => return Err(Error::UnknownValueParser(key.to_string(), name.to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the declared flags and positionals for mistakes which would otherwise only surface
+    /// when parsing.
+    ///
+    /// This is called automatically by [`crate::Parser::finalize`] in debug builds, which panics
+    /// listing every mistake found.
+    ///
+    /// # Errors
+    ///
+    /// Returns every [`crate::errors::DefinitionError`] found, in declaration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::DefinitionError;
+    ///
+    /// let mut parser = Parser::from_iter(["head"]);
+    /// parser
+    ///     .required_flag("num", "this is used to set a numeric value")
+    ///     .default_value("1");
+    ///
+    /// assert_eq!(
+    ///     parser.validate_definition(),
+    ///     Err(vec![DefinitionError::RequiredWithDefault("num".to_string())])
+    /// );
+    /// ```
+    pub fn validate_definition(&self) -> std::result::Result<(), Vec<DefinitionError>> {
+        let mut errors: Vec<DefinitionError> = Vec::new();
+        for (key, entry) in self.flags.iter() {
+            if key.is_empty() || key.starts_with('-') || key.contains(char::is_whitespace) {
+                errors.push(DefinitionError::InvalidName(key.to_string()));
+            }
+            if self.required.contains(key) && entry.default.is_some() {
+                errors.push(DefinitionError::RequiredWithDefault(key.to_string()));
+            }
+            if entry.confirm.is_some() && !matches!(entry.typ, Flag::Bool) {
+                errors.push(DefinitionError::ConfirmOnValueFlag(key.to_string()));
+            }
+            if let Some(negated) = key.strip_prefix("no-") {
+                if self
+                    .flags
+                    .get(negated)
+                    .is_some_and(|other| matches!(other.typ, Flag::TriState))
+                {
+                    errors.push(DefinitionError::AliasCollision(
+                        key.to_string(),
+                        negated.to_string(),
+                    ));
+                }
+            }
+            let references = entry
+                .required_if
+                .iter()
+                .map(|(other, _)| other)
+                .chain(&entry.required_unless)
+                .chain(entry.default_if.iter().map(|(other, _, _)| other));
+            for other in references {
+                if !self.flags.contains_key(other) {
+                    errors.push(DefinitionError::UnknownFlagReference(
+                        key.to_string(),
+                        other.to_string(),
+                    ));
+                }
+            }
+        }
+
+        let count = self.declared_positionals.iter().count();
+        let mut optional = false;
+        let mut multiple: Option<&String> = None;
+        for (i, (name, entry)) in self.declared_positionals.iter().enumerate() {
+            // The trailing positional only captures once the required ones before it have args.
+            let trailing = self.trailing.as_ref() == Some(name);
+            if entry.min_occurrences == 0 {
+                optional = true;
+            } else if optional && !trailing {
+                errors.push(DefinitionError::RequiredPositionalAfterOptional(
+                    name.to_string(),
+                ));
+            }
+            if entry.multiple {
+                match multiple {
+                    Some(first) => errors.push(DefinitionError::SeveralMultiplePositionals(
+                        first.to_string(),
+                        name.to_string(),
+                    )),
+                    None if i + 1 < count && !trailing => {
+                        errors.push(DefinitionError::MultiplePositionalNotLast(name.to_string()))
+                    }
+                    None => {}
+                }
+                multiple.get_or_insert(name);
+            }
+        }
+        for (key, name, _) in &self.unknown_value_parsers {
+            if !self.value_parsers.contains_key(name) {
+                errors.push(DefinitionError::UnknownValueParser(
+                    key.to_string(),
+                    name.to_string(),
+                ));
+            }
+        }
+        if let Some(trailing) = &self.trailing {
+            let last = self
+                .declared_positionals
+                .iter()
+                .last()
+                .map(|(name, _)| name);
+            if last != Some(trailing) {
+                errors.push(DefinitionError::TrailingPositionalNotLast(
+                    trailing.to_string(),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Sets the value of a flag as if it were given on the command line, overriding the args, so
+    /// wrappers and tests can inject values without building an argument list.
+    ///
+    /// Before [`crate::Parser::finalize`] the value replaces whatever the args give and is checked
+    /// with them. Afterwards it is checked right away and replaces the parsed value, see
+    /// [`crate::FlagBuilder::path_must_exist`]. Boolean flags accept the words read from the
+    /// environment, see [`crate::Parser::env_prefix`]. For a flag taking multiple values it
+    /// replaces them with a single value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`crate::errors::Error::UnknownFlag`] if the flag is not declared,
+    /// or [`crate::errors::Error::InvalidValue`] if the value fails its checks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["serve", "-port", "80"]);
+    /// parser.optional_flag("port", "this is used to set the port").integer();
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser.set_value("port", "8080")?;
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(8080), parser.get_value::<u16>("port"));
+    ///
+    /// parser.set_value("verbose", "yes")?;
+    /// assert_eq!(Some(true), parser.get_value("verbose"));
+    /// assert!(parser.set_value("port", "http").is_err());
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn set_value(&mut self, flag: &str, value: &str) -> Result<()> {
+        let Some(entry) = self.flags.get_mut(flag) else {
+            return Err(Error::UnknownFlag(flag.to_string()));
+        };
+        let invalid = |reason| Error::InvalidValue(flag.to_string(), reason);
+        let value = match entry.typ {
+            Flag::Bool | Flag::TriState => value::boolean(value).map_err(invalid)?,
+            Flag::Value | Flag::Count => value.to_string(),
+        };
+        if !self.finalized {
+            self.overrides.push((flag.to_string(), value));
+            return Ok(());
+        }
+
+        let value = entry.check(&value).map_err(invalid)?;
+        entry.bytes = match &entry.decoder {
+            Some(decoder) => Some(decoder(&value).map_err(invalid)?),
+            None => None,
+        };
+        entry.value = match (entry.decoded_value, &entry.bytes) {
+            (true, Some(bytes)) => Some(Cow::Owned(String::from_utf8_lossy(bytes).into_owned())),
+            _ => Some(Cow::Owned(value)),
+        };
+        entry.source = Some(Source::Cli);
+        entry.raw.clear();
+        Ok(())
+    }
+
+    /// Returns the source the value of a flag came from, if it has a value.
+    pub fn value_source(&self, flag: &str) -> Option<Source> {
+        self.entry(flag).and_then(|entry| entry.source)
+    }
+
+    /// Returns the environment variable the value of a flag came from, if its source is
+    /// [`Source::Env`].
+    pub fn value_env_var(&self, flag: &str) -> Option<&str> {
+        self.flags
+            .get(flag)
+            .and_then(|entry| entry.env_var.as_deref())
+    }
+
+    /// Returns a table of every flag with its resolved value and the source it came from, to help
+    /// find out why a flag has a given value.
+    ///
+    /// Flags are listed in declaration order, the values of sensitive flags are redacted, and the
+    /// environment variable is shown next to values from [`Source::Env`]. Flags without a value
+    /// show `-` in both columns. This should be called after [`crate::Parser::finalize`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["serve", "-port", "8080", "-token", "abc"]);
+    /// parser.required_flag("port", "this is used to set the port");
+    /// parser.optional_flag("host", "this is used to set the host").default_value("localhost");
+    /// parser.optional_flag("token", "this is used to authenticate").sensitive();
+    /// parser.finalize()?;
+    ///
+    /// assert_eq!(
+    ///     parser.explain(),
+    ///     "FLAG   VALUE      SOURCE\n\
+    ///      port   8080       cli\n\
+    ///      host   localhost  default\n\
+    ///      token  ********   cli\n"
+    /// );
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn explain(&self) -> String {
+        let mut rows = vec![("FLAG", "VALUE".to_string(), "SOURCE".to_string())];
+        for (key, entry) in self.flags.iter() {
+            let value = match entry.display_value() {
+                Some(value) => value.replace(LIST_SEPARATOR, ","),
+                None => "-".to_string(),
+            };
+            let source = match (entry.source, &entry.env_var) {
+                (Some(Source::Env), Some(var)) => format!("env ({})", var),
+                (Some(source), _) => source.to_string(),
+                (None, _) => "-".to_string(),
+            };
+            rows.push((key.as_str(), value, source));
+        }
+
+        let flag_width = rows.iter().map(|(f, _, _)| f.len()).max().unwrap_or(0);
+        let value_width = rows.iter().map(|(_, v, _)| v.len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for (flag, value, source) in rows {
+            out.push_str(&format!(
+                "{:flag_width$}  {:value_width$}  {}\n",
+                flag, value, source
+            ));
+        }
+        out
+    }
+
+    /// Resolves the value of every flag by consulting each source in order of precedence.
+    fn resolve(&mut self) -> Result<()> {
+        self.resolve_sources()?;
+        self.resolve_conditional_defaults();
+        Ok(())
+    }
+
+    /// Resolves the value of every flag from the sources, with the profile selected if profiles
+    /// are enabled.
+    fn resolve_sources(&mut self) -> Result<()> {
+        if !self.profiles {
+            return self.resolve_with(None, None);
+        }
+
+        // The profile is resolved on its own first, since it selects the other values.
+        self.resolve_with(None, Some(PROFILE_FLAG))?;
+        let Some(profile) = self.get_value::<String>(PROFILE_FLAG) else {
+            return self.resolve_with(None, None);
+        };
+        let prefix = format!("profile.{}.", profile);
+        if !self
+            .config_values
+            .keys()
+            .any(|key| key.starts_with(&prefix))
+        {
+            return Err(Error::InvalidValue(
+                PROFILE_FLAG.to_string(),
+                "the profile is not defined in the configuration".to_string(),
+            ));
+        }
+        self.resolve_with(Some(&prefix), None)
+    }
+
+    /// Replaces the default of each flag left to its default with the first conditional default
+    /// whose condition holds on the resolved values. Every condition is checked before any
+    /// conditional default is applied, so the result does not depend on the declaration order.
+    fn resolve_conditional_defaults(&mut self) {
+        if !self.precedence.contains(&Source::Default) {
+            return;
+        }
+
+        let mut defaults = Vec::new();
+        for (key, entry) in self.flags.iter() {
+            if !matches!(entry.source, None | Some(Source::Default)) {
+                continue;
+            }
+            let default = entry.default_if.iter().find(|(other, value, _)| {
+                let Some(other) = self.flags.get(other) else {
+                    return false;
+                };
+                // Both values are compared as they are stored once normalized and parsed.
+                match other.value.as_deref().map(|current| other.check(current)) {
+                    Some(Ok(current)) => other.check(value).is_ok_and(|value| value == current),
+                    _ => false,
+                }
+            });
+            if let Some((_, _, default)) = default {
+                defaults.push((key.to_string(), default.to_string()));
+            }
+        }
+        for (key, default) in defaults {
+            let entry = self.flags.get_mut(&key).unwrap();
+            entry.value = Some(Cow::Owned(default));
+            entry.source = Some(Source::Default);
+        }
+    }
+
+    /// Resolves every flag, or only the given one, looking up configuration values with the
+    /// profile prefix first.
+    fn resolve_with(&mut self, profile: Option<&str>, only: Option<&str>) -> Result<()> {
+        let env_fn = Rc::clone(&self.env_fn);
+        let env = |name: &str| lookup_env(&env_fn, name);
+        for (key, entry) in self.flags.iter_mut() {
+            if only.is_some_and(|only| only != key) {
+                continue;
+            }
+            let cli = match entry.source {
+                Some(Source::Cli) => entry.value.take(),
+                _ => None,
+            };
+
+            entry.value = None;
+            entry.source = None;
+            entry.env_var = None;
+            // Values found so far, from the highest precedence source down.
+            let mut found: Vec<(Source, String)> = Vec::new();
+            for source in &self.precedence {
+                let value = match source {
+                    Source::Cli => cli.clone(),
+                    Source::Env => {
+                        let mut found = None;
+                        for name in env_var_names(self.env_prefix.as_deref(), key, entry) {
+                            found = env_value(&name, &self.dotenv_vars, self.dotenv_override, &env)
+                                .map_err(|e| Error::InvalidValue(key.clone(), e.to_string()))?;
+                            if found.is_some() {
+                                break;
+                            }
+                        }
+                        found.map(|(name, value)| {
+                            entry.env_var = Some(name);
+                            Cow::Owned(value)
+                        })
+                    }
+                    Source::Config => profile
+                        .and_then(|prefix| self.config_values.get(&format!("{}{}", prefix, key)))
+                        .or_else(|| self.config_values.get(key))
+                        .cloned()
+                        .map(Cow::Owned),
+                    Source::Default => entry.default.clone().map(Cow::Owned),
+                };
+                let Some(mut value) = value else {
+                    continue;
+                };
+                if *source == Source::Env && matches!(entry.typ, Flag::Bool | Flag::TriState) {
+                    let boolean = value::boolean(&value)
+                        .map_err(|reason| Error::InvalidValue(key.clone(), reason))?;
+                    value = Cow::Owned(boolean);
+                }
+                if !entry.multiple {
+                    entry.value = Some(value);
+                    entry.source = Some(*source);
+                    break;
+                }
+
+                // Environment and configuration values hold every value of a list at once.
+                let delimiter = match source {
+                    Source::Env => Some(entry.env_delimiter),
+                    Source::Config => Some(','),
+                    _ => None,
+                };
+                let value = match delimiter {
+                    Some(delimiter) => value
+                        .split(delimiter)
+                        .collect::<Vec<_>>()
+                        .join(&LIST_SEPARATOR.to_string()),
+                    None => value.into_owned(),
+                };
+                // Defaults only apply when no other source gives values.
+                if *source == Source::Default && !found.is_empty() {
+                    break;
+                }
+                found.push((*source, value));
+                if entry.merge == ListMerge::Replace {
+                    break;
+                }
+            }
+
+            if let Some((source, _)) = found.first() {
+                entry.source = Some(*source);
+                let merge = entry.merge;
+                // Lower precedence values come first, as if they were given earlier.
+                let values: Vec<&str> = found
+                    .iter()
+                    .rev()
+                    .flat_map(|(source, value)| {
+                        value.split(LIST_SEPARATOR).map(move |v| match source {
+                            Source::Cli if merge == ListMerge::Append => {
+                                v.strip_prefix('+').unwrap_or(v)
+                            }
+                            _ => v,
+                        })
+                    })
+                    .collect();
+                entry.value = Some(Cow::Owned(values.join(&LIST_SEPARATOR.to_string())));
+            }
+        }
+        Ok(())
+    }
+
+    /// Normalizes the resolved value of each flag and runs the checks declared on it, then decodes
+    /// it into bytes if the flag has a decoder.
+    fn parse_values(&mut self) -> Result<()> {
+        let mut stdin_read = false;
+        let flags = self
+            .flags
+            .iter_mut()
+            .map(|(key, entry)| (key, entry, false));
+        let positionals = self.declared_positionals.iter_mut();
+        let entries = flags.chain(positionals.map(|(key, entry)| (key, entry, true)));
+        for (key, entry, positional) in entries {
+            let usage = entry.usage.clone();
+            let invalid = |reason| {
+                if positional {
+                    Error::InvalidPositional(key.clone(), usage.clone(), reason)
+                } else {
+                    Error::InvalidValue(key.clone(), reason)
+                }
+            };
+
+            // Bytes read from stdin replace the contents a decoder would otherwise produce.
+            let mut stdin_bytes = None;
+            if entry.stdin && entry.value.as_deref() == Some("-") {
+                if stdin_read {
+                    return Err(invalid("stdin is already read by another flag".to_string()));
+                }
+                stdin_read = true;
+                let bytes = match &self.stdin_fn {
+                    Some(f) => f(),
+                    None => read_stdin(),
+                }
+                .map_err(|e| invalid(format!("stdin could not be read: {}", e)))?;
+
+                if entry.decoded_value {
+                    stdin_bytes = Some(bytes);
+                } else {
+                    let text = String::from_utf8(bytes)
+                        .map_err(|_| invalid("stdin is not valid UTF-8".to_string()))?;
+                    let text = text.strip_suffix('\n').unwrap_or(&text);
+                    let text = text.strip_suffix('\r').unwrap_or(text);
+                    entry.value = Some(Cow::Owned(text.to_string()));
+                }
+            }
+
+            let rewrites = !entry.normalizers.is_empty() || !entry.parsers.is_empty();
+            if rewrites && stdin_bytes.is_none() {
+                if let Some(value) = &entry.value {
+                    entry.value = Some(Cow::Owned(entry.check(value).map_err(invalid)?));
+                }
+            }
+            entry.bytes = match (stdin_bytes, &entry.decoder, &entry.value) {
+                (Some(bytes), _, _) => Some(bytes),
+                (None, Some(decoder), Some(value)) => Some(decoder(value).map_err(invalid)?),
+                _ => None,
+            };
+            if let (true, Some(bytes)) = (entry.decoded_value, &entry.bytes) {
+                entry.value = Some(Cow::Owned(String::from_utf8_lossy(bytes).into_owned()));
+            }
+        }
+        Ok(())
+    }
+
+    fn consume_flag<I>(
+        &mut self,
+        token: Cow<'a, str>,
+        flag: &str,
+        it: &mut Peekable<I>,
+    ) -> Result<()>
+    where
+        I: Iterator<Item = Cow<'a, str>>,
+    {
+        if let Some(entry) = self.flags.get_mut(flag) {
+            // Only the tokens of the occurrences the value is made of are kept.
+            if entry.accumulate.is_none() && !matches!(entry.typ, Flag::Count) {
+                entry.raw.clear();
+            }
+            entry.raw.push(token);
+            match entry.typ {
+                Flag::Bool | Flag::TriState => {
+                    entry.value = Some(Cow::Borrowed("true"));
+                    entry.source = Some(Source::Cli);
+                    Ok(())
+                }
+                Flag::Count => {
+                    let count = match (entry.source, entry.value.as_deref()) {
+                        (Some(Source::Cli), Some(count)) => count.parse::<usize>().unwrap_or(0),
+                        _ => 0,
+                    };
+                    entry.value = Some(Cow::Owned((count + 1).to_string()));
+                    entry.source = Some(Source::Cli);
+                    Ok(())
+                }
+                Flag::Value => match it.next() {
+                    Some(mut value) => {
+                        entry.raw.push(value.clone());
+                        // A tuple given as consecutive tokens is joined with its delimiter.
+                        if let Some((arity, delimiter)) = entry.tuple {
+                            if !value.contains(delimiter) {
+                                let mut values = vec![value.into_owned()];
+                                for _ in 1..arity {
+                                    match it.next() {
+                                        Some(value) => {
+                                            entry.raw.push(value.clone());
+                                            values.push(value.into_owned());
+                                        }
+                                        None => return Err(Error::MissingValue(flag.to_string())),
+                                    }
+                                }
+                                value = Cow::Owned(values.join(&delimiter.to_string()));
+                            }
+                        }
+                        entry.value = match (entry.accumulate, entry.value.take()) {
+                            (Some(separator), Some(previous)) => {
+                                Some(Cow::Owned(format!("{}{}{}", previous, separator, value)))
+                            }
+                            _ => Some(value),
+                        };
+                        entry.source = Some(Source::Cli);
+                        Ok(())
+                    }
+                    None => Err(Error::MissingValue(flag.to_string())),
+                },
+            }
+        } else if let Some(entry) = flag
+            .strip_prefix("no-")
+            .and_then(|negated| self.flags.get_mut(negated))
+            .filter(|entry| matches!(entry.typ, Flag::TriState))
+        {
+            entry.raw = vec![token];
+            entry.value = Some(Cow::Borrowed("false"));
+            entry.source = Some(Source::Cli);
+            Ok(())
+        } else if flag == "help" {
+            Err(Error::HelpRequested)
+        } else if flag == "version" && self.version.is_some() {
+            Err(Error::VersionRequested)
+        } else {
+            Err(Error::UnknownFlag(flag.to_string()))
+        }
+    }
+
+    fn parse_next<I>(&mut self, it: &mut Peekable<I>) -> Result<Option<Cow<'a, str>>>
+    where
+        I: Iterator<Item = Cow<'a, str>>,
+    {
+        match it.next() {
+            Some(token) => match self.classify(&token) {
+                Token::Flag(flag) => match self.consume_flag(token.clone(), &flag, it) {
+                    Ok(_) => Ok(None),
+                    Err(e) => Err(e),
+                },
+                Token::Positional => Ok(Some(token)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the arguments taking into account all defined flags and returns any remaining
+    /// non-flag arguments.
+    ///
+    /// The arguments are consumed by parsing, so this is meant to be called once. The process is
+    /// never exited, when `-help` is given [`crate::errors::Error::HelpRequested`] is returned so
+    /// the caller can print [`crate::Parser::help`]. Likewise, when a version is set and
+    /// `-version` is given [`crate::errors::Error::VersionRequested`] is returned so the caller
+    /// can print [`crate::Parser::version`], and the subcommand declared with
+    /// [`crate::Parser::completions_subcommand`] returns
+    /// [`crate::errors::Error::CompletionsRequested`].
+    ///
+    /// # Errors
+    ///
+    /// Depending on the flags set, it returns a variant of [`crate::errors::Error`].
+    pub fn finalize(&mut self) -> Result<Vec<Cow<'a, str>>> {
+        self.link_value_parsers()?;
+        #[cfg(debug_assertions)]
+        if let Err(errors) = self.validate_definition() {
+            let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            panic!("invalid flag definitions: {}", errors.join(", "));
+        }
+        self.finalized = true;
+
+        let mut remaining: Vec<Cow<'a, str>> = Vec::new();
+        let mut trailing: Vec<Cow<'a, str>> = Vec::new();
+
+        // The argument source is consumed so each token is moved out exactly once. An arg past
+        // the limits ends it, leaving the error to be returned instead of any error it causes.
+        let mut source = std::mem::replace(&mut self.args, Box::new(std::iter::empty::<&str>()));
+        let limits = self.limits;
+        let exceeded = Rc::new(Cell::new(None));
+        let read = Rc::new(RefCell::new(Vec::new()));
+        let mut position = 0;
+        let mut it = std::iter::from_fn({
+            let exceeded = Rc::clone(&exceeded);
+            let read = Rc::clone(&read);
+            move || {
+                let arg = source.next_arg()?;
+                position += 1;
+                match limits.check_arg(position, &arg) {
+                    Ok(()) => {
+                        read.borrow_mut().push(arg.clone());
+                        Some(arg)
+                    }
+                    Err(e) => {
+                        exceeded.set(Some(e));
+                        None
+                    }
+                }
+            }
+        })
+        .fuse()
+        .peekable();
+
+        let trailing_start = self.trailing_start();
+        let mut subcommand = None;
+        while let Some(_) = it.peek() {
+            if trailing_start.is_some() && it.peek().is_some_and(|token| token == "--") {
+                it.next();
+                trailing.extend(it.by_ref());
+                break;
+            }
+            let next = self.parse_next(&mut it);
+            if let Some(e) = exceeded.take() {
+                return Err(e);
+            }
+            let value = match next? {
+                Some(value) => value,
+                None => {
+                    // Replaying parses the recorded args instead, ignoring the rest.
+                    #[cfg(feature = "serde_json")]
+                    if let Some(path) = self.replay_path() {
+                        self.load_replay(path)?;
+                        return self.finalize();
+                    }
+                    continue;
+                }
+            };
+
+            // The first positional argument selects the subcommand, which parses the rest.
+            if remaining.is_empty() && self.has_subcommands() {
+                match self.match_subcommand(&value)? {
+                    Matched::Declared(i) => subcommand = Some(i),
+                    Matched::External => remaining.extend(it.by_ref()),
+                }
+                break;
+            }
+
+            // The trailing positional captures its first arg and every token after it.
+            if trailing_start.is_some_and(|start| remaining.len() >= start) {
+                trailing.push(value);
+                trailing.extend(it.by_ref());
+                break;
+            }
+            if self.deny_positionals {
+                return Err(Error::UnexpectedArgument(value.into_owned()));
+            }
+            remaining.push(value);
+        }
+        self.raw_args = read.take();
+        if let Some(e) = exceeded.take() {
+            return Err(e);
+        }
+
+        // Like -help and -version, the completions subcommand skips checking the other flags.
+        #[cfg(feature = "completions")]
+        if let Some(i) = subcommand.filter(|i| self.is_completions_subcommand(*i)) {
+            let parser = &mut self.subcommands[i].parser;
+            parser.args = Box::new(it);
+            let parsed = parser.finalize();
+            self.raw_args.extend(read.take());
+            if let Some(e) = exceeded.take() {
+                return Err(e);
+            }
+            parsed?;
+            return match self.requested_completions() {
+                Some(shell) => Err(Error::CompletionsRequested(shell)),
+                None => Ok(remaining),
+            };
+        }
+        for (flag, value) in std::mem::take(&mut self.overrides) {
+            let Some(entry) = self.flags.get_mut(&flag) else {
+                return Err(Error::UnknownFlag(flag));
+            };
+            entry.value = Some(Cow::Owned(value));
+            entry.source = Some(Source::Cli);
+            entry.raw.clear();
+        }
+
+        if self.expand_globs {
+            let mut expanded = Vec::new();
+            for arg in remaining {
+                let matched = if glob::is_pattern(&arg) {
+                    glob::expand(&arg, self.limits.glob_matches())
+                } else {
+                    Vec::new()
+                };
+                self.limits.check_glob(&arg, matched.len())?;
+                if matched.is_empty() {
+                    expanded.push(arg);
+                } else {
+                    expanded.extend(matched.into_iter().map(Cow::Owned));
+                }
+            }
+            remaining = expanded;
+        }
+
+        if self.deny_unknown_config {
+            if let Some(key) = self.unknown_config_keys().first() {
+                #[cfg(feature = "completions")]
+                let suggestion = {
+                    let flags = self.flags.iter().map(|(flag, _)| flag.as_str());
+                    suggest::closest(self.config_key_flag(key), flags).map(str::to_string)
+                };
+                #[cfg(not(feature = "completions"))]
+                let suggestion = None;
+                return Err(Error::UnknownConfigKey(key.to_string(), suggestion));
+            }
+        }
+        self.assign_positionals(&remaining, &trailing)?;
+        remaining.extend(trailing);
+        self.resolve()?;
+        #[cfg(feature = "serde_json")]
+        self.restore_replayed();
+        self.parse_values()?;
+        self.confirm()?;
+
+        // Check for required flags.
+        for flag in &self.required {
+            if !self.flags.contains_key(flag) {
+                return Err(Error::MissingArgument(flag.to_string()));
+            }
+
+            match self.flags.get(flag) {
+                Some(entry) => {
+                    if let None = entry.value {
                         return Err(Error::MissingArgument(flag.to_string()));
                     }
                 }
                 None => return Err(Error::MissingArgument(flag.to_string())),
             }
         }
+        for (key, entry) in self.flags.iter() {
+            // Only values which were given count, a default is never an occurrence.
+            let explicit = entry
+                .value
+                .as_ref()
+                .filter(|_| entry.source != Some(Source::Default));
+            let count = match explicit {
+                Some(value) if entry.multiple => value.split(LIST_SEPARATOR).count(),
+                Some(_) => 1,
+                None => 0,
+            };
+            if count < entry.min_occurrences {
+                return Err(Error::TooFewOccurrences(
+                    key.to_string(),
+                    count,
+                    entry.min_occurrences,
+                ));
+            }
+            if let Some(max) = entry.max_occurrences.filter(|max| count > *max) {
+                return Err(Error::TooManyOccurrences(key.to_string(), count, max));
+            }
+
+            // Boolean and counted flags always have a value, which is a default unless given.
+            let given = match entry.typ {
+                Flag::Bool | Flag::TriState | Flag::Count => {
+                    entry.source.is_some_and(|source| source != Source::Default)
+                }
+                Flag::Value => entry.value.is_some(),
+            };
+            if given {
+                continue;
+            }
+            for (other, value) in &entry.required_if {
+                if self.flags.get(other).and_then(|e| e.value.as_deref()) == Some(value) {
+                    return Err(Error::MissingArgumentIf(
+                        key.to_string(),
+                        other.to_string(),
+                        value.to_string(),
+                    ));
+                }
+            }
+            let present = |other: &String| self.flags.get(other).is_some_and(|e| e.is_present());
+            if !entry.required_unless.is_empty() && !entry.required_unless.iter().any(present) {
+                return Err(Error::MissingArgumentUnless(
+                    key.to_string(),
+                    entry.required_unless.clone(),
+                ));
+            }
+        }
+        self.check_positionals()?;
+        self.positionals = remaining.clone();
+        if let Some(i) = subcommand {
+            let parser = &mut self.subcommands[i].parser;
+            parser.args = Box::new(it);
+            if parser.version.is_none() {
+                parser.version = self.version.clone();
+            }
+            if parser.classifier.is_none() {
+                parser.classifier = self.classifier.clone();
+            }
+            if parser.env_fn.borrow().is_none() {
+                *parser.env_fn.borrow_mut() = self.env_fn.borrow().clone();
+            }
+            for (name, value_parser) in &self.value_parsers {
+                parser
+                    .value_parsers
+                    .entry(name.to_string())
+                    .or_insert_with(|| Rc::clone(value_parser));
+            }
+            #[cfg(feature = "help")]
+            if parser.help_renderer.is_none() {
+                parser.help_renderer = self.help_renderer.clone();
+            }
+            let parsed = parser.finalize();
+            self.raw_args.extend(read.take());
+            if let Some(e) = exceeded.take() {
+                return Err(e);
+            }
+            remaining.extend(parsed?);
+        }
+        #[cfg(feature = "serde_json")]
+        if self.replay {
+            self.write_record()?;
+        }
+        if let Some(audit) = &self.audit_fn {
+            audit(&self.invocation());
+        }
         Ok(remaining)
     }
 }
 
+/// Writes a prompt to stderr and reads a yes or no answer from stdin.
+fn confirm_stdin(prompt: &str) -> bool {
+    eprint!("{} [y/N] ", prompt);
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Reads all of stdin.
+fn read_stdin() -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    std::io::Read::read_to_end(&mut std::io::stdin(), &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Returns the environment variable name of a flag for a given prefix.
+fn env_var_name(prefix: &str, flag: &str) -> String {
+    format!("{}_{}", prefix, flag.to_uppercase().replace('-', "_"))
+}
+
+/// Looks up an environment variable with a closure set with [`Parser::set_env_fn`], or else in
+/// the process environment.
+pub(crate) fn lookup_env(env_fn: &RefCell<Option<EnvFn>>, name: &str) -> Option<String> {
+    match &*env_fn.borrow() {
+        Some(f) => f(name),
+        None => std::env::var(name).ok(),
+    }
+}
+
+/// Returns the name and value of an environment variable, set directly or loaded from `.env`
+/// files, or else the contents of the file named by the variable with a `_FILE` suffix.
+///
+/// The process environment is checked before the `.env` values unless `dotenv_override` is set.
+fn env_value(
+    name: &str,
+    dotenv_vars: &HashMap<String, String>,
+    dotenv_override: bool,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> std::result::Result<Option<(String, String)>, String> {
+    let lookup = |name: &str| {
+        let dotenv = dotenv_vars.get(name).cloned();
+        if dotenv_override {
+            dotenv.or_else(|| env(name))
+        } else {
+            env(name).or(dotenv)
+        }
+    };
+    if let Some(value) = lookup(name) {
+        return Ok(Some((name.to_string(), value)));
+    }
+
+    let file_var = format!("{}_FILE", name);
+    let Some(path) = lookup(&file_var) else {
+        return Ok(None);
+    };
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("the file named by {} could not be read: {}", file_var, e))?;
+    let contents = contents.strip_suffix('\n').unwrap_or(&contents);
+    let contents = contents.strip_suffix('\r').unwrap_or(contents);
+    Ok(Some((file_var, contents.to_string())))
+}
+
+/// Returns the file name of a path, or the path itself if it has none.
+fn basename<'a>(path: &Cow<'a, str>) -> Cow<'a, str> {
+    fn file_name(path: &str) -> &str {
+        std::path::Path::new(path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(path)
+    }
+
+    match path {
+        Cow::Borrowed(path) => Cow::Borrowed(file_name(path)),
+        Cow::Owned(path) => Cow::Owned(file_name(path).to_string()),
+    }
+}
+
+/// Returns the environment variables of a flag in the order they are checked, the ones declared
+/// with [`crate::FlagBuilder::env`] followed by the one derived from the prefix.
+fn env_var_names(prefix: Option<&str>, flag: &str, entry: &FlagEntry) -> Vec<String> {
+    let mut names = entry.env_vars.clone();
+    if let Some(prefix) = prefix {
+        names.push(env_var_name(prefix, flag));
+    }
+    names
+}
+
+impl fmt::Debug for Parser<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Parser")
+            .field("command", &self.command)
+            .field("flags", &self.flags)
+            .field("required", &self.required)
+            .field(
+                "subcommands",
+                &self
+                    .subcommands
+                    .iter()
+                    .map(|sub| &sub.parser)
+                    .collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, S> FromIterator<S> for Parser<'a>
+where
+    S: Into<Cow<'a, str>>,
+{
+    /// Initializes a [`Parser`] from any iterator of string-like items, the first item being the
+    /// command name.
+    ///
+    /// Borrowed items such as `&str` or `&String` are not copied, flag values and remaining
+    /// arguments borrow from them instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let parser = Parser::from_iter(["head", "-num", "3"]);
+    /// assert_eq!("head", parser.command);
+    ///
+    /// /// Borrow a pre-tokenized list of arguments.
+    /// let args: Vec<String> = vec!["head".to_string(), "file.txt".to_string()];
+    /// let parser = Parser::from_iter(&args);
+    /// assert_eq!("head", parser.command);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let args: Vec<Cow<'a, str>> = iter.into_iter().map(Into::into).collect();
+        Self::from_source(args.into_iter())
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -445,13 +2654,8 @@ mod tests {
 
     #[test]
     fn simple() {
-        let cmd_args: Vec<String> =
-            vec!["head", "-verbose", "-num", "1", "-opt", "val", "file.txt"]
-                .iter()
-                .map(|x| x.to_string())
-                .collect();
-
-        let mut parser = Parser::from_vec(cmd_args);
+        let mut parser =
+            Parser::from_iter(["head", "-verbose", "-num", "1", "-opt", "val", "file.txt"]);
         parser.bool_flag("verbose", "this is used to get verbose output");
         parser.required_flag("num", "this is used to set a numeric value");
         parser.required_flag("opt", "this is an optional flag (optional)");
@@ -475,14 +2679,14 @@ mod tests {
         assert_eq!(Some(String::from("val")), opt);
 
         // Check help text generation.
-        let help = parser.help();
+        #[cfg(feature = "help")]
         assert_eq!(
-            help,
+            parser.help(),
             [
                 "Usage: head [options...]\n",
-                " -num\tthis is used to set a numeric value\n",
-                " -opt\tthis is an optional flag (optional)\n",
-                " -verbose\tthis is used to get verbose output\n",
+                "  -num value\n\tthis is used to set a numeric value\n",
+                "  -opt value\n\tthis is an optional flag (optional)\n",
+                "  -verbose\n\tthis is used to get verbose output\n",
             ]
             .concat(),
         )
@@ -490,9 +2694,7 @@ mod tests {
 
     #[test]
     fn optional_not_given() {
-        let cmd_args: Vec<String> = vec!["head"].iter().map(|x| x.to_string()).collect();
-
-        let mut parser = Parser::from_vec(cmd_args);
+        let mut parser = Parser::from_iter(["head"]);
         parser.optional_flag("num", "this is used to set a numeric value (optional)");
 
         // This must be called before fetching flags and returns any remaining args.
@@ -507,12 +2709,7 @@ mod tests {
 
     #[test]
     fn required_not_given() {
-        let cmd_args: Vec<String> = vec!["head", "file.txt"]
-            .iter()
-            .map(|x| x.to_string())
-            .collect();
-
-        let mut parser = Parser::from_vec(cmd_args);
+        let mut parser = Parser::from_iter(["head", "file.txt"]);
         parser.required_flag("num", "this is used to set a numeric value");
 
         // This must be called before fetching flags and returns any remaining args.
@@ -523,4 +2720,943 @@ mod tests {
         let num: Option<i32> = parser.get_value("num");
         assert_eq!(None, num);
     }
+
+    #[test]
+    fn env_fallback() {
+        let mut parser = Parser::from_iter(["head", "-opt", "cli"]);
+        parser.env_prefix("YAFP_TEST_ENV");
+        parser.set_env(HashMap::from([
+            ("YAFP_TEST_ENV_NUM".to_string(), "3".to_string()),
+            ("YAFP_TEST_ENV_VERBOSE".to_string(), "true".to_string()),
+            ("YAFP_TEST_ENV_OPT".to_string(), "env".to_string()),
+        ]));
+        parser.required_flag("num", "this is used to set a numeric value");
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.optional_flag("opt", "this is an optional flag");
+
+        parser.finalize().unwrap();
+
+        // Check the environment is used for flags not set on the command line.
+        assert_eq!(Some(3), parser.get_value::<i32>("num"));
+        assert_eq!(Some(true), parser.get_value::<bool>("verbose"));
+
+        // Check the command line takes precedence over the environment.
+        assert_eq!(Some(String::from("cli")), parser.get_value::<String>("opt"));
+
+        // Check subcommands and path expansion use the environment of the parser.
+        let mut parser = Parser::from_iter(["tool", "build", "-out", "$OUT_DIR/bin"]);
+        let build = parser.add_subcommand("build", "compile the project");
+        build.env_prefix("YAFP_TEST_ENV");
+        build
+            .optional_flag("out", "this is used to set the output")
+            .expand_path();
+        build.optional_flag("opt", "this is an optional flag");
+        parser.set_env_fn(|name| match name {
+            "OUT_DIR" => Some("/tmp/out".to_string()),
+            "YAFP_TEST_ENV_OPT" => Some("env".to_string()),
+            _ => None,
+        });
+        parser.finalize().unwrap();
+        let (_, build) = parser.subcommand().unwrap();
+        assert_eq!(
+            build.get_value::<String>("out").as_deref(),
+            Some("/tmp/out/bin")
+        );
+        assert_eq!(build.get_value::<String>("opt").as_deref(), Some("env"));
+    }
+
+    #[test]
+    fn confirmation() {
+        let mut parser = Parser::from_iter(["rm", "-force-delete"]);
+        parser
+            .bool_flag("force-delete", "this is used to delete without a backup")
+            .confirm("Delete all files without a backup?");
+        parser.set_confirm_fn(|prompt| {
+            assert_eq!(prompt, "Delete all files without a backup?");
+            false
+        });
+
+        // Check finalize fails when the user does not confirm.
+        let result = parser.finalize();
+        assert!(matches!(result, Err(Error::NotConfirmed(flag)) if flag == "force-delete"));
+    }
+
+    #[test]
+    fn borrowed_args() {
+        let args = vec![
+            "head".to_string(),
+            "-num".to_string(),
+            "1".to_string(),
+            "file.txt".to_string(),
+        ];
+
+        let mut parser = Parser::from_iter(&args);
+        parser.required_flag("num", "this is used to set a numeric value");
+
+        // Check remaining args and values borrow from the given args.
+        let remaining = parser.finalize().unwrap();
+        assert!(matches!(remaining[0], Cow::Borrowed("file.txt")));
+        assert!(matches!(
+            parser.flags["num"].value,
+            Some(Cow::Borrowed("1"))
+        ));
+    }
+
+    #[test]
+    fn path_checks() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let mut parser = Parser::from_iter(["build", "-out", dir]);
+        parser
+            .optional_flag("out", "this is used to set the output file")
+            .must_be_file();
+        parser
+            .optional_flag("src", "this is used to set the sources directory")
+            .default_value(dir)
+            .must_be_dir();
+
+        let result = parser.finalize();
+        assert_eq!(
+            result,
+            Err(Error::InvalidValue(
+                "out".to_string(),
+                "path is not a file".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn file_contents() {
+        let mut parser = Parser::from_iter(["check", "-manifest", "Missing.toml"]);
+        parser
+            .required_flag("manifest", "this is used to set the manifest to check")
+            .file_contents();
+
+        let result = parser.finalize();
+        assert!(matches!(
+            result,
+            Err(Error::InvalidValue(flag, reason))
+                if flag == "manifest" && reason.starts_with("file could not be read")
+        ));
+    }
+
+    #[test]
+    fn stdin() {
+        let mut parser = Parser::from_iter(["send", "-payload", "-", "-token", "-"]);
+        parser
+            .required_flag("payload", "this is used to set the payload")
+            .stdin();
+        parser
+            .optional_flag("token", "this is used to set the token")
+            .stdin();
+        parser.set_stdin_fn(|| Ok(b"hello\n".to_vec()));
+
+        let result = parser.finalize();
+        assert_eq!(
+            result,
+            Err(Error::InvalidValue(
+                "token".to_string(),
+                "stdin is already read by another flag".to_string()
+            ))
+        );
+        assert_eq!(
+            parser.get_value::<String>("payload"),
+            Some("hello".to_string())
+        );
+    }
+
+    #[test]
+    fn choices() {
+        let mut parser = Parser::from_iter(["ls", "-sort", "time"]);
+        parser
+            .optional_flag("sort", "this is used to set the sort order")
+            .choices(&["name", "time"]);
+
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<String>("sort"), Some("time".to_string()));
+
+        #[cfg(feature = "help")]
+        assert_eq!(
+            parser.help_flags(),
+            "  -sort value [possible: name, time]\n\tthis is used to set the sort order\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "dotenv")]
+    fn dotenv_layers() {
+        let dir = std::env::temp_dir().join(format!("yafp-dotenv-layers-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(".env"), "YAFP_LAYERS_A=env\nYAFP_LAYERS_B=env\n").unwrap();
+        std::fs::write(dir.join(".env.local"), "YAFP_LAYERS_B=local\n").unwrap();
+        std::fs::write(dir.join(".env.test"), "YAFP_LAYERS_C=test\n").unwrap();
+        let env = |name: &str| (name == "YAFP_LAYERS_C").then(|| "process".to_string());
+
+        let mut parser = Parser::from_iter(["ls"]);
+        parser.env_prefix("YAFP_LAYERS");
+        parser.set_env_fn(env);
+        parser.optional_flag("a", "this is the first flag");
+        parser.optional_flag("b", "this is the second flag");
+        parser.optional_flag("c", "this is the third flag");
+        let loaded = parser.load_dotenv_layers(&dir, Some("test")).unwrap();
+        assert_eq!(
+            loaded,
+            vec![
+                dir.join(".env"),
+                dir.join(".env.local"),
+                dir.join(".env.test")
+            ]
+        );
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<String>("a").as_deref(), Some("env"));
+        assert_eq!(parser.get_value::<String>("b").as_deref(), Some("local"));
+        assert_eq!(parser.get_value::<String>("c").as_deref(), Some("process"));
+
+        let mut parser = Parser::from_iter(["ls"]);
+        parser.env_prefix("YAFP_LAYERS");
+        parser.set_env_fn(env);
+        parser.optional_flag("c", "this is the third flag");
+        parser.load_dotenv_layers(&dir, Some("test")).unwrap();
+        parser.dotenv_override();
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<String>("c").as_deref(), Some("test"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn explain() {
+        let mut parser = Parser::from_iter(["ls"]);
+        parser.env_prefix("YAFP_EXPLAIN");
+        parser.set_env_fn(|name| (name == "YAFP_EXPLAIN_TAGS").then(|| "a,b".to_string()));
+        parser
+            .optional_flag("tags", "this is used to tag")
+            .multiple();
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.optional_flag("output", "this is used to set the output");
+        parser.config_value("output", "out.txt");
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.explain(),
+            "FLAG     VALUE    SOURCE\n\
+             tags     a,b      env (YAFP_EXPLAIN_TAGS)\n\
+             verbose  false    default\n\
+             output   out.txt  config\n"
+        );
+    }
+
+    #[test]
+    fn required_if() {
+        let mut parser = Parser::from_iter(["serve", "-tls"]);
+        parser.bool_flag("tls", "this is used to enable tls");
+        parser
+            .optional_flag("key-file", "this is used to set the key file")
+            .required_if("tls", "true");
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::MissingArgumentIf(
+                "key-file".to_string(),
+                "tls".to_string(),
+                "true".to_string()
+            ))
+        );
+
+        let mut parser = Parser::from_iter(["serve", "-tls", "-key-file", "key.pem"]);
+        parser.bool_flag("tls", "this is used to enable tls");
+        parser
+            .optional_flag("key-file", "this is used to set the key file")
+            .required_if("tls", "true");
+        assert!(parser.finalize().is_ok());
+
+        // Any of the conditions makes the flag required.
+        let mut parser = Parser::from_iter(["serve", "-mode", "server"]);
+        parser.optional_flag("mode", "this is used to set the mode");
+        parser.bool_flag("tls", "this is used to enable tls");
+        parser
+            .optional_flag("key-file", "this is used to set the key file")
+            .required_if("tls", "true")
+            .required_if("mode", "server");
+        assert!(matches!(
+            parser.finalize(),
+            Err(Error::MissingArgumentIf(_, other, _)) if other == "mode"
+        ));
+
+        let mut parser = Parser::from_iter(["serve", "-mode", "client"]);
+        parser.optional_flag("mode", "this is used to set the mode");
+        parser
+            .optional_flag("key-file", "this is used to set the key file")
+            .required_if("mode", "server");
+        assert!(parser.finalize().is_ok());
+
+        // A boolean flag is required to be set, its default does not count.
+        let mut parser = Parser::from_iter(["deploy", "-env", "prod"]);
+        parser.optional_flag("env", "this is used to set the environment");
+        parser
+            .bool_flag("confirm", "this is used to confirm the deploy")
+            .required_if("env", "prod");
+        assert!(matches!(
+            parser.finalize(),
+            Err(Error::MissingArgumentIf(flag, _, _)) if flag == "confirm"
+        ));
+
+        let mut parser = Parser::from_iter(["deploy", "-env", "prod", "-confirm"]);
+        parser.optional_flag("env", "this is used to set the environment");
+        parser
+            .bool_flag("confirm", "this is used to confirm the deploy")
+            .required_if("env", "prod");
+        assert!(parser.finalize().is_ok());
+    }
+
+    #[test]
+    fn required_unless() {
+        let mut parser = Parser::from_iter(["login"]);
+        parser.optional_flag("password-file", "this is used to read the password");
+        parser.bool_flag("anonymous", "this is used to log in anonymously");
+        parser
+            .optional_flag("password", "this is used to set the password")
+            .required_unless(&["password-file", "anonymous"]);
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::MissingArgumentUnless(
+                "password".to_string(),
+                vec!["password-file".to_string(), "anonymous".to_string()]
+            ))
+        );
+
+        let mut parser = Parser::from_iter(["login", "-password", "hunter2"]);
+        parser
+            .optional_flag("password", "this is used to set the password")
+            .required_unless(&["anonymous"]);
+        parser.bool_flag("anonymous", "this is used to log in anonymously");
+        assert!(parser.finalize().is_ok());
+
+        let mut parser = Parser::from_iter(["login", "-password-file", "secret.txt"]);
+        parser.optional_flag("password-file", "this is used to read the password");
+        parser
+            .optional_flag("password", "this is used to set the password")
+            .required_unless(&["password-file"]);
+        assert!(parser.finalize().is_ok());
+
+        let mut parser = Parser::from_iter(["login", "-anonymous"]);
+        parser.bool_flag("anonymous", "this is used to log in anonymously");
+        parser
+            .optional_flag("password", "this is used to set the password")
+            .required_unless(&["anonymous"]);
+        assert!(parser.finalize().is_ok());
+    }
+
+    #[test]
+    fn default_value_if() {
+        let mut parser = Parser::from_iter(["serve", "-tls"]);
+        parser.bool_flag("tls", "this is used to enable tls");
+        parser
+            .optional_flag("port", "this is used to set the port")
+            .default_value("80")
+            .default_value_if("tls", "true", "443");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<u16>("port"), Some(443));
+        assert_eq!(parser.value_source("port"), Some(Source::Default));
+
+        let mut parser = Parser::from_iter(["serve"]);
+        parser.bool_flag("tls", "this is used to enable tls");
+        parser
+            .optional_flag("port", "this is used to set the port")
+            .default_value("80")
+            .default_value_if("tls", "true", "443");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<u16>("port"), Some(80));
+
+        let mut parser = Parser::from_iter(["serve", "-tls", "-port", "8443"]);
+        parser.bool_flag("tls", "this is used to enable tls");
+        parser
+            .optional_flag("port", "this is used to set the port")
+            .default_value("80")
+            .default_value_if("tls", "true", "443");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<u16>("port"), Some(8443));
+
+        let mut parser = Parser::from_iter(["serve", "-tls"]);
+        parser.bool_flag("tls", "this is used to enable tls");
+        parser
+            .optional_flag("port", "this is used to set the port")
+            .default_value("80")
+            .default_value_if("tls", "true", "443");
+        parser.config_value("port", "9443");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<u16>("port"), Some(9443));
+
+        // Conditions compare normalized values, before any conditional default is applied.
+        let mut parser = Parser::from_iter(["serve", "-mode", " Secure "]);
+        parser
+            .optional_flag("port", "this is used to set the port")
+            .default_value_if("mode", "SECURE", "443")
+            .default_value_if("tls", "true", "8443");
+        parser
+            .optional_flag("mode", "this is used to set the mode")
+            .trim()
+            .lowercase();
+        parser
+            .bool_flag("tls", "this is used to enable tls")
+            .default_value_if("port", "443", "true");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<u16>("port"), Some(443));
+        assert_eq!(parser.get_value::<bool>("tls"), Some(false));
+    }
+
+    #[test]
+    fn occurrences() {
+        let mut parser = Parser::from_iter(["sync"]);
+        parser
+            .optional_flag("input", "this is used to add an input")
+            .multiple()
+            .min_occurrences(1);
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::TooFewOccurrences("input".to_string(), 0, 1))
+        );
+
+        let mut parser = Parser::from_iter(["sync"]);
+        parser
+            .optional_flag("input", "this is used to add an input")
+            .multiple()
+            .default_value("a")
+            .min_occurrences(1);
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::TooFewOccurrences("input".to_string(), 0, 1))
+        );
+
+        let args = ["sync", "-replica", "x", "-replica", "y"];
+        let mut parser = Parser::from_iter(args);
+        parser
+            .optional_flag("replica", "this is used to add a replica")
+            .multiple()
+            .max_occurrences(2);
+        assert!(parser.finalize().is_ok());
+
+        let mut parser = Parser::from_iter(["sync"]);
+        parser
+            .optional_flag("replica", "this is used to add a replica")
+            .multiple()
+            .default_value("x,y,z")
+            .max_occurrences(2);
+        assert!(parser.finalize().is_ok());
+
+        let mut parser = Parser::from_iter(["sync"]);
+        parser
+            .optional_flag("replica", "this is used to add a replica")
+            .multiple()
+            .max_occurrences(2);
+        parser.config_value("replica", "x,y,z");
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::TooManyOccurrences("replica".to_string(), 3, 2))
+        );
+    }
+
+    #[test]
+    fn deny_positionals() {
+        let mut parser = Parser::from_iter(["status", "-short"]);
+        parser.deny_positionals();
+        parser.bool_flag("short", "this is used to get short output");
+        assert_eq!(parser.finalize(), Ok(Vec::new()));
+
+        let mut parser = Parser::from_iter(["status", "file.txt", "-short"]);
+        parser.deny_positionals();
+        parser.bool_flag("short", "this is used to get short output");
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::UnexpectedArgument("file.txt".to_string()))
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let limits = Limits::new().max_args(3).max_arg_len(8);
+
+        let mut parser = Parser::from_iter(["head", "-n", "10", "file.txt"]);
+        parser.set_limits(limits);
+        parser.optional_flag("n", "this is used to set the number of lines");
+        assert_eq!(parser.finalize().unwrap(), vec!["file.txt"]);
+
+        // The limit is reported rather than the missing value it cuts off.
+        let mut parser = Parser::from_iter(["head", "a", "b", "-n", "10"]);
+        parser.set_limits(limits);
+        parser.optional_flag("n", "this is used to set the number of lines");
+        assert_eq!(parser.finalize(), Err(Error::TooManyArgs(3)));
+
+        let mut parser = Parser::from_iter(["head", "-n", "1234567890"]);
+        parser.set_limits(limits);
+        parser.optional_flag("n", "this is used to set the number of lines");
+        assert_eq!(parser.finalize(), Err(Error::ArgTooLong(8)));
+
+        let dir = std::env::temp_dir().join(format!("yafp-limits-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let pattern = format!("{}/*.txt", dir.display());
+        let mut parser = Parser::from_iter(["cat", pattern.as_str()]);
+        parser.set_limits(Limits::new().max_glob_matches(2));
+        parser.expand_globs();
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::TooManyMatches(pattern.clone(), 2))
+        );
+
+        let mut parser = Parser::from_iter(["cat", pattern.as_str()]);
+        parser.set_limits(Limits::new().max_glob_matches(3));
+        parser.expand_globs();
+        assert_eq!(parser.finalize().unwrap().len(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn value_parsers() {
+        let mut parser = Parser::from_iter(["tool", "fetch", "-size", "big"]);
+        parser.register_value_parser("size", |v| match v {
+            "small" => Ok("1".to_string()),
+            "large" => Ok("3".to_string()),
+            _ => Err("expected small or large".to_string()),
+        });
+        let fetch = parser.add_subcommand("fetch", "fetches a page");
+        fetch
+            .optional_flag("size", "this is used to set the page size")
+            .value_parser("size");
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::InvalidValue(
+                "size".to_string(),
+                "expected small or large".to_string()
+            ))
+        );
+
+        let mut parser = Parser::from_iter(["tool"]);
+        parser
+            .optional_flag("timeout", "this is used to set the timeout")
+            .value_parser("duration");
+        assert_eq!(
+            parser.validate_definition(),
+            Err(vec![DefinitionError::UnknownValueParser(
+                "timeout".to_string(),
+                "duration".to_string()
+            )])
+        );
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::UnknownValueParser(
+                "timeout".to_string(),
+                "duration".to_string()
+            ))
+        );
+
+        // A parser registered after the flag refers to it, by the parent of a subcommand.
+        let mut parser = Parser::from_iter(["tool", "fetch", "-size", "big"]);
+        parser
+            .add_subcommand("fetch", "fetches a page")
+            .optional_flag("size", "this is used to set the page size")
+            .value_parser("size");
+        parser.register_value_parser("size", |_| Err("expected small or large".to_string()));
+        assert!(parser.validate_definition().is_ok());
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::InvalidValue(
+                "size".to_string(),
+                "expected small or large".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn raw_tokens() {
+        let args = [
+            "tool", "-v", "build", "-tag", "a", "-size", "1", "2", "-tag", "b",
+        ];
+        let mut parser = Parser::from_iter(args);
+        parser.bool_flag("v", "this is used to get verbose output");
+        let build = parser.add_subcommand("build", "builds the project");
+        build
+            .optional_flag("tag", "this is used to add a tag")
+            .multiple();
+        build
+            .optional_flag("size", "this is used to set the size")
+            .tuple(2, 'x');
+        build
+            .optional_flag("jobs", "this is used to set the number of jobs")
+            .default_value("4");
+        parser.finalize().unwrap();
+
+        assert_eq!(parser.raw_args(), &args[1..]);
+        assert_eq!(parser.raw_tokens("v"), ["-v"]);
+        let (_, build) = parser.subcommand().unwrap();
+        assert_eq!(build.raw_args(), &args[3..]);
+        assert_eq!(build.raw_tokens("tag"), ["-tag", "a", "-tag", "b"]);
+        assert_eq!(build.raw_tokens("size"), ["-size", "1", "2"]);
+        assert!(build.raw_tokens("jobs").is_empty());
+
+        let mut parser = Parser::from_iter(["tool", "-token", "abc", "build", "-key", "1", "2"]);
+        parser
+            .optional_flag("token", "this is used to authenticate")
+            .sensitive();
+        parser
+            .add_subcommand("build", "builds the project")
+            .optional_flag("key", "this is used to set the signing key")
+            .tuple(2, ',')
+            .sensitive();
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.redacted_args(),
+            ["-token", REDACTED, "build", "-key", REDACTED, REDACTED]
+        );
+        assert_eq!(parser.redacted_tokens("token"), ["-token", REDACTED]);
+        assert_eq!(parser.raw_tokens("token"), ["-token", "abc"]);
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    fn sensitive_json() {
+        let mut parser = Parser::from_iter(["login", "-token", "\"hunter2-secret\""]);
+        parser
+            .required_flag("token", "this is used to authenticate")
+            .sensitive()
+            .json_as::<Vec<String>>();
+        let error = parser.finalize().unwrap_err();
+        assert!(matches!(error, Error::InvalidValue(..)));
+        assert!(!error.to_string().contains("hunter2"));
+    }
+
+    #[test]
+    fn set_value() {
+        let mut parser = Parser::from_iter(["deploy", "-tag", "a", "-tag", "b"]);
+        parser.required_flag("env", "this is used to set the environment");
+        parser
+            .optional_flag("tag", "this is used to add a tag")
+            .multiple();
+        assert_eq!(
+            parser.set_value("region", "eu"),
+            Err(Error::UnknownFlag("region".to_string()))
+        );
+        parser.set_value("env", "staging").unwrap();
+        parser.set_value("tag", "c").unwrap();
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.get_value::<String>("env").as_deref(),
+            Some("staging")
+        );
+        assert_eq!(
+            parser.get_values::<String>("tag"),
+            Some(vec!["c".to_string()])
+        );
+        assert_eq!(parser.value_source("env"), Some(Source::Cli));
+
+        let mut parser = Parser::from_iter(["deploy"]);
+        parser.bool_flag("force", "this is used to skip checks");
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.set_value("force", "maybe"),
+            Err(Error::InvalidValue(
+                "force".to_string(),
+                "expected one of: 1, 0, true, false, yes, no, on, off".to_string()
+            ))
+        );
+        assert_eq!(parser.get_value::<bool>("force"), Some(false));
+    }
+
+    #[test]
+    fn remove_flag() {
+        let mut parser = Parser::from_iter(["tool", "-debug"]);
+        parser.bool_flag("debug", "this is used to get debug output");
+        parser.required_flag("token", "this is used to authenticate");
+        assert!(parser.remove_flag("debug"));
+        assert!(parser.remove_flag("token"));
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::UnknownFlag("debug".to_string()))
+        );
+
+        let mut parser = Parser::from_iter(["tool"]);
+        parser.required_flag("token", "this is used to authenticate");
+        parser.optional_flag("token", "this is used to authenticate");
+        assert!(parser.finalize().is_ok());
+
+        // A value set before finalizing is dropped along with the flag.
+        let mut parser = Parser::from_iter(["tool"]);
+        parser.bool_flag("debug", "this is used to get debug output");
+        parser.set_value("debug", "1").unwrap();
+        assert!(parser.remove_flag("debug"));
+        assert_eq!(parser.finalize(), Ok(Vec::new()));
+        assert_eq!(parser.get_value::<bool>("debug"), None);
+    }
+
+    #[test]
+    fn hidden_flag() {
+        let mut parser = Parser::from_iter(["tool", "-trace"]);
+        parser
+            .bool_flag("trace", "this is used to trace every call")
+            .hidden();
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<bool>("trace"), Some(true));
+
+        #[cfg(feature = "help")]
+        {
+            assert!(!parser.help().contains("-trace"));
+            assert!(!parser.render_full_help().contains("-trace"));
+            assert_eq!(parser.help_page().flags.len(), 1);
+        }
+        #[cfg(feature = "completions")]
+        {
+            assert!(!parser
+                .completions(completions::Shell::Bash)
+                .contains("-trace"));
+            assert!(!parser.fig_spec().contains("-trace"));
+            assert!(!parser.carapace_spec().contains("-trace"));
+        }
+    }
+
+    #[test]
+    fn env_booleans() {
+        let env = |name: &str| match name {
+            "YAFP_ENV_BOOL_COLOR" => Some("Yes".to_string()),
+            "YAFP_ENV_BOOL_VERBOSE" => Some("0".to_string()),
+            "YAFP_ENV_BOOL_QUIET" => Some("sometimes".to_string()),
+            _ => None,
+        };
+
+        let mut parser = Parser::from_iter(["ls"]);
+        parser.env_prefix("YAFP_ENV_BOOL");
+        parser.set_env_fn(env);
+        parser.tristate_flag("color", "this is used to colorize the output");
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<bool>("color"), Some(true));
+        assert_eq!(parser.get_value::<bool>("verbose"), Some(false));
+
+        let mut parser = Parser::from_iter(["ls"]);
+        parser.env_prefix("YAFP_ENV_BOOL");
+        parser.set_env_fn(env);
+        parser.bool_flag("quiet", "this is used to silence the output");
+        assert!(matches!(
+            parser.finalize(),
+            Err(Error::InvalidValue(flag, _)) if flag == "quiet"
+        ));
+    }
+
+    #[test]
+    fn env_file() {
+        let path = std::env::temp_dir().join("yafp-env-file-token");
+        std::fs::write(&path, "secret\n").unwrap();
+        let env = HashMap::from([
+            (
+                "YAFP_ENV_FILE_TOKEN_FILE".to_string(),
+                path.display().to_string(),
+            ),
+            (
+                "YAFP_ENV_FILE_MISSING_FILE".to_string(),
+                path.with_extension("missing").display().to_string(),
+            ),
+        ]);
+
+        let mut parser = Parser::from_iter(["deploy"]);
+        parser.env_prefix("YAFP_ENV_FILE");
+        parser.set_env(env.clone());
+        parser.optional_flag("token", "this is used to set the API token");
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.get_value::<String>("token"),
+            Some("secret".to_string())
+        );
+        assert_eq!(
+            parser.value_env_var("token"),
+            Some("YAFP_ENV_FILE_TOKEN_FILE")
+        );
+
+        let mut parser = Parser::from_iter(["deploy"]);
+        parser.env_prefix("YAFP_ENV_FILE");
+        parser.set_env(env);
+        parser.optional_flag("missing", "this is used to set a missing value");
+        assert!(matches!(
+            parser.finalize(),
+            Err(Error::InvalidValue(flag, _)) if flag == "missing"
+        ));
+    }
+
+    #[test]
+    fn unknown_config_keys() {
+        let mut parser = Parser::from_iter(["deploy"]);
+        parser.config_profiles();
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.config_value("profile.staging.verbose", "true");
+        parser.config_value("profile.staging.verbos", "true");
+        parser.deny_unknown_config_keys();
+
+        // The suggestion is the flag the key would set under the profile.
+        let suggestion = cfg!(feature = "completions").then(|| "verbose".to_string());
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::UnknownConfigKey(
+                "profile.staging.verbos".to_string(),
+                suggestion
+            ))
+        );
+
+        let mut parser = Parser::from_iter(["deploy"]);
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.config_value("verbos", "true");
+        parser.deny_unknown_config_keys();
+
+        let result = parser.finalize();
+        #[cfg(feature = "completions")]
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "configuration key 'verbos' is not recognized, did you mean 'verbose'?"
+        );
+        #[cfg(not(feature = "completions"))]
+        assert_eq!(
+            result,
+            Err(Error::UnknownConfigKey("verbos".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn multiple() {
+        let mut parser = Parser::from_iter(["serve"]);
+        parser
+            .optional_flag("ports", "this is used to set the ports to listen on")
+            .multiple()
+            .integer();
+        parser.config_value("ports", "80,0x1bb");
+
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_values::<u16>("ports"), Some(vec![80, 443]));
+        assert_eq!(parser.get_value::<u16>("ports"), Some(443));
+
+        let mut parser = Parser::from_iter(["serve", "-ports", "80", "-ports", "http"]);
+        parser
+            .optional_flag("ports", "this is used to set the ports to listen on")
+            .multiple()
+            .integer();
+        assert!(matches!(
+            parser.finalize(),
+            Err(Error::InvalidValue(flag, _)) if flag == "ports"
+        ));
+    }
+
+    #[test]
+    fn list_merge() {
+        let mut parser = Parser::from_iter(["cc", "-include", "/b"]);
+        parser
+            .optional_flag("include", "this is used to add include paths")
+            .multiple()
+            .merge(ListMerge::Append);
+        parser.config_value("include", "/a");
+
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.get_values::<String>("include"),
+            Some(vec!["/a".to_string(), "/b".to_string()])
+        );
+        assert_eq!(parser.value_source("include"), Some(Source::Cli));
+
+        let args = ["cc", "-include", "/b", "-include", "+/c"];
+        let mut parser = Parser::from_iter(args);
+        parser
+            .optional_flag("include", "this is used to add include paths")
+            .multiple()
+            .default_value("/usr/include")
+            .merge(ListMerge::Append);
+        parser.config_value("include", "/a");
+
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.get_values::<String>("include"),
+            Some(vec!["/a".to_string(), "/b".to_string(), "/c".to_string()])
+        );
+
+        // Values are kept as given when replacing.
+        let mut parser = Parser::from_iter(["clock", "-tz", "+02:00"]);
+        parser
+            .optional_flag("tz", "this is used to add a time zone")
+            .multiple()
+            .default_value("+00:00");
+        parser.config_value("tz", "+01:00");
+
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.get_values::<String>("tz"),
+            Some(vec!["+02:00".to_string()])
+        );
+    }
+
+    #[test]
+    fn profiles() {
+        let mut parser = Parser::from_iter(["deploy", "-profile", "qa"]);
+        parser.config_profiles();
+        parser.optional_flag("host", "this is used to set the host");
+        parser.config_value("profile.staging.host", "staging.example.com");
+
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::InvalidValue(
+                "profile".to_string(),
+                "the profile is not defined in the configuration".to_string()
+            ))
+        );
+
+        // The profile itself can come from the configuration.
+        let mut parser = Parser::from_iter(["deploy"]);
+        parser.config_profiles();
+        parser.optional_flag("host", "this is used to set the host");
+        parser.config_value("profile", "staging");
+        parser.config_value("profile.staging.host", "staging.example.com");
+
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.get_value::<String>("host"),
+            Some("staging.example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn flag_collisions() {
+        let mut parser = Parser::from_iter(["ls"]);
+        parser.tristate_flag("color", "this is used to color output");
+        parser.bool_flag("no-color", "this is used to disable colors");
+        parser
+            .optional_flag("key-file", "this is used to set the key file")
+            .required_if("tls", "true");
+        assert_eq!(
+            parser.validate_definition(),
+            Err(vec![
+                DefinitionError::AliasCollision("no-color".to_string(), "color".to_string()),
+                DefinitionError::UnknownFlagReference("key-file".to_string(), "tls".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid flag definitions: flag name '-num' is invalid")]
+    fn invalid_definition() {
+        let mut parser = Parser::from_iter(["head", "file.txt"]);
+        parser.optional_flag("-num", "this is used to set a numeric value");
+
+        // Check finalize panics in debug builds.
+        let _ = parser.finalize();
+    }
+
+    #[test]
+    fn name() {
+        let mut parser = Parser::from_iter([String::from("./target/debug/tool"), "build".into()]);
+        assert_eq!(parser.command, "tool");
+        parser
+            .add_subcommand("build", "compile the project")
+            .add_subcommand("docs", "compile the documentation");
+        assert_eq!(parser.subcommands[0].parser.command, "tool build");
+
+        parser.set_name("app");
+        parser.finalize().unwrap();
+        let (_, build) = parser.subcommand().unwrap();
+        assert_eq!(build.command, "app build");
+        assert_eq!(build.subcommands[0].parser.command, "app build docs");
+        assert_eq!(build.bin_path(), "./target/debug/tool");
+    }
 }
@@ -0,0 +1,143 @@
+//! Initializes `env_logger` or `tracing-subscriber` from the parsed flags, see
+//! [`crate::Parser::logging_flags`].
+use crate::value_enum;
+use crate::verbosity::Verbosity;
+use crate::Parser;
+
+/// Name of the flag which sets the format of log lines.
+const FORMAT_FLAG: &str = "log-format";
+
+/// Name of the flag which sets when log lines are colored.
+const COLOR_FLAG: &str = "log-color";
+
+value_enum! {
+    /// The format of log lines, set with the `-log-format` flag.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum LogFormat {
+        /// Lines with a timestamp, the level and the target of each record.
+        #[default]
+        Full => "full",
+        /// Lines with only the level and the message.
+        Compact => "compact",
+    }
+}
+
+impl Parser<'_> {
+    /// Declares the flags read when initializing a logger: the verbosity flags, see
+    /// [`crate::Parser::verbosity_flags`], `-log-format` taking a [`LogFormat`] and `-log-color`
+    /// taking `auto`, `always` or `never`.
+    ///
+    /// With `auto` or without `-log-color`, log lines are colored following
+    /// [`crate::Parser::set_color`].
+    pub fn logging_flags(&mut self) {
+        self.verbosity_flags();
+        self.optional_flag(FORMAT_FLAG, "this is used to set the format of log lines")
+            .value_enum::<LogFormat>();
+        self.optional_flag(COLOR_FLAG, "this is used to set when log lines are colored")
+            .choices(&["auto", "always", "never"]);
+    }
+
+    /// Returns the format set with `-log-format`, see [`crate::Parser::logging_flags`].
+    pub fn log_format(&self) -> LogFormat {
+        self.get_value(FORMAT_FLAG).unwrap_or_default()
+    }
+
+    /// Returns whether log lines written to standard error are colored.
+    fn log_color(&self) -> bool {
+        match self.get_value::<String>(COLOR_FLAG).as_deref() {
+            Some("always") => true,
+            Some("never") => false,
+            _ => self.color_enabled(&std::io::stderr()),
+        }
+    }
+
+    /// Installs `env_logger` as the global logger, configured by the flags declared with
+    /// [`crate::Parser::logging_flags`] and starting at a default level. Call it right after
+    /// [`crate::Parser::finalize`].
+    ///
+    /// Fails if a global logger is already installed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::verbosity::Verbosity;
+    ///
+    /// let mut parser = Parser::from_iter(["serve", "-v", "-log-format", "compact"]);
+    /// parser.logging_flags();
+    ///
+    /// parser.finalize().unwrap();
+    /// parser.init_env_logger(Verbosity::Warn).unwrap();
+    /// assert_eq!(log::max_level(), log::LevelFilter::Info);
+    /// ```
+    #[cfg(feature = "env_logger")]
+    pub fn init_env_logger(&self, default: Verbosity) -> Result<(), log::SetLoggerError> {
+        let mut builder = env_logger::Builder::new();
+        builder.filter_level(self.verbosity(default).into());
+        if self.log_color() {
+            builder.write_style(env_logger::WriteStyle::Always);
+        } else {
+            builder.write_style(env_logger::WriteStyle::Never);
+        }
+        if let LogFormat::Compact = self.log_format() {
+            builder.format_timestamp(None).format_target(false);
+        }
+        builder.try_init()
+    }
+
+    /// Installs a `tracing-subscriber` formatter as the global subscriber, configured by the
+    /// flags declared with [`crate::Parser::logging_flags`] and starting at a default level.
+    /// Call it right after [`crate::Parser::finalize`].
+    ///
+    /// Fails if a global subscriber is already installed.
+    #[cfg(feature = "tracing-subscriber")]
+    pub fn init_tracing(
+        &self,
+        default: Verbosity,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let builder = tracing_subscriber::fmt()
+            .with_max_level(self.verbosity(default))
+            .with_ansi(self.log_color())
+            .with_writer(std::io::stderr);
+        match self.log_format() {
+            LogFormat::Full => builder.try_init(),
+            LogFormat::Compact => builder.compact().without_time().try_init(),
+        }
+    }
+}
+
+#[cfg(feature = "tracing-subscriber")]
+impl From<Verbosity> for tracing_subscriber::filter::LevelFilter {
+    fn from(verbosity: Verbosity) -> Self {
+        use tracing_subscriber::filter::LevelFilter;
+
+        match verbosity {
+            Verbosity::Off => LevelFilter::OFF,
+            Verbosity::Error => LevelFilter::ERROR,
+            Verbosity::Warn => LevelFilter::WARN,
+            Verbosity::Info => LevelFilter::INFO,
+            Verbosity::Debug => LevelFilter::DEBUG,
+            Verbosity::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn flags() {
+        let mut parser =
+            Parser::from_iter(["serve", "-log-format", "compact", "-log-color", "never"]);
+        parser.logging_flags();
+        parser.finalize().unwrap();
+        assert_eq!(parser.log_format(), LogFormat::Compact);
+        assert!(!parser.log_color());
+
+        let mut parser = Parser::from_iter(["serve", "-log-format", "json"]);
+        parser.logging_flags();
+        assert!(parser.finalize().is_err());
+    }
+}
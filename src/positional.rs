@@ -0,0 +1,402 @@
+//! Declares positional arguments and maps the remaining args onto them.
+use std::borrow::Cow;
+
+use crate::errors::{Error, Result};
+use crate::{Flag, FlagBuilder, FlagEntry, Parser, Source, LIST_SEPARATOR};
+
+impl<'a> Parser<'a> {
+    /// Declares a required positional argument, read by name like a flag once
+    /// [`crate::Parser::finalize`] maps the remaining args onto the declared positionals in order.
+    ///
+    /// The returned builder configures the positional like a flag, including checks such as
+    /// [`crate::FlagBuilder::range`] which fail with an error result of type
+    /// [`crate::errors::Error::InvalidPositional`]. The last positional may be declared with
+    /// [`crate::FlagBuilder::multiple`] to take every arg left, and required positionals come
+    /// before optional ones, which [`crate::Parser::validate_definition`] checks. Positionals are
+    /// listed in the usage line and the help. Once
+    /// positionals are declared, [`crate::Parser::finalize`] returns an error result of type
+    /// [`crate::errors::Error::MissingPositional`] if one is missing, or
+    /// [`crate::errors::Error::TooManyPositionals`] if more args are given than declared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tar", "-verbose", "backup.tar", "a.txt", "b.txt"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser.positional("archive", "the archive to create");
+    /// parser.positional("files", "the files to archive").multiple();
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(PathBuf::from("backup.tar")), parser.get_value::<PathBuf>("archive"));
+    /// assert_eq!(
+    ///     Some(vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]),
+    ///     parser.get_values::<PathBuf>("files")
+    /// );
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn positional(&mut self, name: &str, usage: &str) -> FlagBuilder<'_, 'a> {
+        let mut entry = FlagEntry::new(Flag::Value, usage);
+        entry.min_occurrences = 1;
+        self.declare_positional(name, entry)
+    }
+
+    /// Declares an optional positional argument, which is only assigned an arg once every
+    /// required positional has one and otherwise takes its default, if any.
+    ///
+    /// Optional positionals are listed in the usage line as `[name]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["ls"]);
+    /// parser
+    ///     .optional_positional("dir", "the directory to list")
+    ///     .default_value(".");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(".".to_string()), parser.get_value::<String>("dir"));
+    /// # #[cfg(feature = "help")]
+    /// assert!(parser.help().starts_with("Usage: ls [options...] [dir]\n"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn optional_positional(&mut self, name: &str, usage: &str) -> FlagBuilder<'_, 'a> {
+        self.declare_positional(name, FlagEntry::new(Flag::Value, usage))
+    }
+
+    /// Declares a positional argument taking every arg after the positionals declared before it
+    /// verbatim, including args starting with `-`, so the tail can be another command line. The
+    /// capture starts at the first arg once the required positionals declared before it have
+    /// one, or after `--`, so optional positionals before it are left to their default.
+    ///
+    /// This must be the last positional declared, which
+    /// [`crate::Parser::validate_definition`] checks. It takes multiple values and, like
+    /// [`crate::Parser::positional`], is required. Captured args are not expanded by
+    /// [`crate::Parser::expand_globs`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let args = ["tool", "-verbose", "--", "env", "FOO=1", "bash", "-c", "echo $FOO"];
+    /// let mut parser = Parser::from_iter(args);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser.trailing_positional("command", "the command to run");
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some(true), parser.get_value("verbose"));
+    /// let command: Vec<String> = parser.get_values("command").unwrap_or_default();
+    /// assert_eq!(command, ["env", "FOO=1", "bash", "-c", "echo $FOO"]);
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn trailing_positional(&mut self, name: &str, usage: &str) -> FlagBuilder<'_, 'a> {
+        self.trailing = Some(name.to_string());
+        self.positional(name, usage).multiple()
+    }
+
+    /// Returns the number of args the required positionals declared before the trailing
+    /// positional take, after which it starts capturing, if there is a trailing positional.
+    pub(crate) fn trailing_start(&self) -> Option<usize> {
+        let trailing = self.trailing.as_deref()?;
+        let start = self
+            .declared_positionals
+            .iter()
+            .take_while(|(name, _)| *name != trailing)
+            .map(|(_, entry)| entry.min_occurrences)
+            .sum();
+        Some(start)
+    }
+
+    fn declare_positional(&mut self, name: &str, entry: FlagEntry<'a>) -> FlagBuilder<'_, 'a> {
+        self.declared_positionals.insert(name.to_string(), entry);
+        FlagBuilder {
+            parser: self,
+            flag: name.to_string(),
+            positional: true,
+        }
+    }
+
+    /// Returns the entry of a flag, or of a declared positional if no flag has the name, or of
+    /// the flag of an argument declared with [`crate::Parser::arg`] with the name as its id.
+    pub(crate) fn entry(&self, name: &str) -> Option<&FlagEntry<'a>> {
+        self.flags
+            .get(name)
+            .or_else(|| self.declared_positionals.get(name))
+            .or_else(|| self.flags.get(self.arg_ids.get(name)?))
+    }
+
+    /// Assigns the remaining args to the declared positionals in order, keeping enough args for
+    /// the required positionals after each one, and the captured args to the trailing
+    /// positional. A positional taking multiple values takes every arg left, and an optional
+    /// positional without an arg takes its default.
+    pub(crate) fn assign_positionals(
+        &mut self,
+        remaining: &[Cow<'a, str>],
+        captured: &[Cow<'a, str>],
+    ) -> Result<()> {
+        if self.declared_positionals.is_empty() {
+            return Ok(());
+        }
+
+        let trailing = self.trailing.as_deref();
+        let mins: Vec<usize> = self
+            .declared_positionals
+            .iter()
+            .map(|(name, entry)| match Some(name.as_str()) == trailing {
+                true if !captured.is_empty() => 0,
+                _ => entry.min_occurrences,
+            })
+            .collect();
+        let mut args = remaining.iter();
+        for (i, (name, entry)) in self.declared_positionals.iter_mut().enumerate() {
+            let reserved: usize = mins[i + 1..].iter().sum();
+            let available = args.len().saturating_sub(reserved);
+            let count = match (entry.multiple, entry.min_occurrences) {
+                (true, _) => available,
+                (false, 0) => available.min(1),
+                (false, _) => 1,
+            };
+            let mut values: Vec<Cow<'a, str>> = args.by_ref().take(count).cloned().collect();
+            if Some(name.as_str()) == trailing {
+                values.extend(captured.iter().cloned());
+            }
+            if !values.is_empty() {
+                entry.value = Some(Cow::Owned(values.join(&LIST_SEPARATOR.to_string())));
+                entry.source = Some(Source::Cli);
+                entry.raw = values;
+            } else {
+                entry.value = entry.default.clone().map(Cow::Owned);
+                entry.source = entry.value.as_ref().map(|_| Source::Default);
+            }
+        }
+        if args.len() > 0 {
+            return Err(Error::TooManyPositionals(mins.len(), remaining.len()));
+        }
+        Ok(())
+    }
+
+    /// Returns an error for the first required positional without a value.
+    pub(crate) fn check_positionals(&self) -> Result<()> {
+        match self
+            .declared_positionals
+            .iter()
+            .find(|(_, entry)| entry.value.is_none() && entry.min_occurrences > 0)
+        {
+            Some((name, _)) => Err(Error::MissingPositional(name.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::errors::DefinitionError;
+
+    #[test]
+    fn assignment() {
+        let mut parser = Parser::from_iter(["mv", "dir", "a", "b", "c"]);
+        parser.positional("dest", "the directory to move to");
+        parser.positional("source", "the files to move").multiple();
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<String>("dest").as_deref(), Some("dir"));
+        assert_eq!(parser.value_source("dest"), Some(Source::Cli));
+        assert_eq!(
+            parser.get_values::<String>("source"),
+            Some(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+
+        let mut parser = Parser::from_iter(["mv", "dir"]);
+        parser.positional("dest", "the directory to move to");
+        parser.positional("source", "the files to move").multiple();
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::MissingPositional("source".to_string()))
+        );
+
+        let mut parser = Parser::from_iter(["cat", "a", "b"]);
+        parser.positional("file", "the file to print");
+        assert_eq!(parser.finalize(), Err(Error::TooManyPositionals(1, 2)));
+
+        let mut parser = Parser::from_iter(["cat"]);
+        parser.positional("file", "the file to print");
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::MissingPositional("file".to_string()))
+        );
+    }
+
+    #[test]
+    fn optional() {
+        let mut parser = Parser::from_iter(["sync", "b"]);
+        parser.positional("dest", "the destination");
+        parser
+            .optional_positional("mode", "the mode")
+            .default_value("copy");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<String>("dest").as_deref(), Some("b"));
+        assert_eq!(parser.get_value::<String>("mode").as_deref(), Some("copy"));
+        assert_eq!(parser.value_source("mode"), Some(Source::Default));
+
+        let mut parser = Parser::from_iter(["sync", "b", "move"]);
+        parser.positional("dest", "the destination");
+        parser.optional_positional("mode", "the mode");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<String>("mode").as_deref(), Some("move"));
+        assert_eq!(parser.value_source("mode"), Some(Source::Cli));
+
+        let mut parser = Parser::from_iter(["sync"]);
+        parser.positional("dest", "the destination");
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::MissingPositional("dest".to_string()))
+        );
+    }
+
+    #[test]
+    fn ordering() {
+        let mut parser = Parser::from_iter(["cp"]);
+        parser.optional_positional("mode", "the copy mode");
+        parser.positional("source", "the files to copy").multiple();
+        parser
+            .positional("dest", "the directory to copy to")
+            .multiple();
+        assert_eq!(
+            parser.validate_definition(),
+            Err(vec![
+                DefinitionError::RequiredPositionalAfterOptional("source".to_string()),
+                DefinitionError::MultiplePositionalNotLast("source".to_string()),
+                DefinitionError::RequiredPositionalAfterOptional("dest".to_string()),
+                DefinitionError::SeveralMultiplePositionals(
+                    "source".to_string(),
+                    "dest".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help() {
+        let mut parser = Parser::from_iter(["cp"]);
+        parser.positional("dest", "the directory to copy to");
+        parser.positional("source", "the files to copy").multiple();
+        assert_eq!(
+            parser.help(),
+            [
+                "Usage: cp [options...] <dest> <source>...\n",
+                "\n",
+                "Arguments:\n",
+                "  dest\n",
+                "\tthe directory to copy to\n",
+                "  source\n",
+                "\tthe files to copy\n",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn trailing() {
+        let args = ["ssh", "host", "-verbose", "ls", "-la", "*.txt", "--", "-x"];
+        let mut parser = Parser::from_iter(args);
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.bool_flag("x", "this is used to forward X11");
+        parser.positional("host", "the host to connect to");
+        parser.trailing_positional("command", "the command to run");
+        parser.expand_globs();
+        assert_eq!(
+            parser.finalize().unwrap(),
+            vec!["host", "ls", "-la", "*.txt", "--", "-x"]
+        );
+        assert_eq!(parser.get_value::<bool>("verbose"), Some(true));
+        assert_eq!(parser.get_value::<bool>("x"), Some(false));
+        assert_eq!(
+            parser.get_values::<String>("command"),
+            Some(vec![
+                "ls".to_string(),
+                "-la".to_string(),
+                "*.txt".to_string(),
+                "--".to_string(),
+                "-x".to_string()
+            ])
+        );
+
+        let mut parser = Parser::from_iter(["ssh", "host"]);
+        parser.positional("host", "the host to connect to");
+        parser.trailing_positional("command", "the command to run");
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::MissingPositional("command".to_string()))
+        );
+
+        // Optional positionals before it do not delay the capture.
+        let mut parser = Parser::from_iter(["tool", "ls", "-la"]);
+        parser.optional_positional("dir", "the directory to run in");
+        parser.trailing_positional("command", "the command to run");
+        parser.finalize().unwrap();
+        assert_eq!(parser.get_value::<String>("dir"), None);
+        assert_eq!(
+            parser.get_values::<String>("command"),
+            Some(vec!["ls".to_string(), "-la".to_string()])
+        );
+
+        let mut parser = Parser::from_iter(["tool"]);
+        parser.trailing_positional("command", "the command to run");
+        parser.positional("host", "the host to connect to");
+        assert_eq!(
+            parser.validate_definition(),
+            Err(vec![
+                crate::errors::DefinitionError::TrailingPositionalNotLast("command".to_string())
+            ])
+        );
+    }
+
+    #[test]
+    fn checks() {
+        let mut parser = Parser::from_iter(["head", "ten"]);
+        parser.positional("lines", "the number of lines").integer();
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::InvalidPositional(
+                "lines".to_string(),
+                "the number of lines".to_string(),
+                "not a valid integer".to_string()
+            ))
+        );
+
+        let mut parser = Parser::from_iter(["sort", "size"]);
+        parser
+            .optional_positional("key", "the sort key")
+            .choices(&["name", "time"]);
+        assert!(matches!(
+            parser.finalize(),
+            Err(Error::InvalidPositional(name, _, _)) if name == "key"
+        ));
+
+        let mut parser = Parser::from_iter(["touch", "a.txt", "b"]);
+        parser
+            .positional("files", "the files to touch")
+            .multiple()
+            .validate(|v| {
+                if v.contains('.') {
+                    Ok(())
+                } else {
+                    Err("expected an extension".to_string())
+                }
+            });
+        assert!(matches!(
+            parser.finalize(),
+            Err(Error::InvalidPositional(_, _, reason)) if reason == "expected an extension"
+        ));
+    }
+}
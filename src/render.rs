@@ -0,0 +1,262 @@
+//! Renders help from structured data with a pluggable renderer, see
+//! [`crate::Parser::set_help_renderer`].
+use std::rc::Rc;
+
+use crate::{env_var_names, Flag, Parser, REDACTED};
+
+/// The kind of a flag listed in a [`HelpPage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagKind {
+    /// A boolean flag, declared with [`crate::Parser::bool_flag`].
+    Bool,
+    /// A flag taking a value.
+    Value,
+    /// A flag which may be disabled with its `-no-` form, declared with
+    /// [`crate::Parser::tristate_flag`].
+    TriState,
+    /// A flag counting how many times it is given, declared with [`crate::Parser::count_flag`].
+    Count,
+}
+
+/// A flag listed in a [`HelpPage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpFlag {
+    /// The name of the flag, without the leading `-`.
+    pub name: String,
+    /// The kind of the flag.
+    pub kind: FlagKind,
+    /// The usage text.
+    pub usage: String,
+    /// The values the flag accepts, empty if it accepts any.
+    pub choices: Vec<String>,
+    /// The default value, redacted if the flag is sensitive.
+    pub default: Option<String>,
+    /// Whether the flag is required.
+    pub required: bool,
+    /// The environment variables the flag is read from.
+    pub env: Vec<String>,
+}
+
+/// A positional argument listed in a [`HelpPage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpArgument {
+    /// The name of the argument.
+    pub name: String,
+    /// The usage text.
+    pub usage: String,
+    /// Whether the argument is required.
+    pub required: bool,
+    /// Whether the argument takes multiple values.
+    pub multiple: bool,
+}
+
+/// A subcommand listed in a [`HelpPage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpCommand {
+    /// The name of the subcommand.
+    pub name: String,
+    /// The description of the subcommand.
+    pub about: String,
+    /// The category of the subcommand, `Commands` unless set with
+    /// [`crate::Parser::subcommand_category`].
+    pub category: String,
+}
+
+/// Everything shown in the help of a parser, as passed to a [`HelpRenderer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpPage {
+    /// The command, prefixed with its parent commands for a subcommand.
+    pub command: String,
+    /// The text describing the command, see [`crate::Parser::set_about`].
+    pub about: Option<String>,
+    /// The custom arguments part of the usage line, see [`crate::Parser::set_usage`].
+    pub usage: Option<String>,
+    /// The flags, sorted by name.
+    pub flags: Vec<HelpFlag>,
+    /// The positional arguments, in declaration order.
+    pub arguments: Vec<HelpArgument>,
+    /// The visible subcommands, grouped by category and sorted by name within each.
+    pub commands: Vec<HelpCommand>,
+    /// The example invocations, see [`crate::Parser::add_example`].
+    pub examples: Vec<String>,
+}
+
+/// Renders the help of a parser from a [`HelpPage`].
+///
+/// A renderer may hold state such as a width, a theme or translated headings, and one renderer
+/// can be shared by several parsers through an [`std::rc::Rc`].
+///
+/// # Examples
+///
+/// ```
+/// use yafp::Parser;
+/// use yafp::render::{HelpPage, HelpRenderer};
+///
+/// struct Compact {
+///     heading: &'static str,
+/// }
+///
+/// impl HelpRenderer for Compact {
+///     fn render(&self, page: &HelpPage) -> String {
+///         let flags: Vec<String> = page.flags.iter().map(|flag| format!("-{}", flag.name)).collect();
+///         format!("{} {}: {}\n", self.heading, page.command, flags.join(" "))
+///     }
+/// }
+///
+/// let mut parser = Parser::from_iter(["head"]);
+/// parser.bool_flag("verbose", "this is used to get verbose output");
+/// parser.optional_flag("num", "this is used to set the number of lines");
+/// parser.set_help_renderer(Compact { heading: "Uso" });
+///
+/// assert_eq!("Uso head: -num -verbose\n", parser.help());
+/// ```
+pub trait HelpRenderer {
+    /// Returns the help for a page.
+    fn render(&self, page: &HelpPage) -> String;
+}
+
+impl<R: HelpRenderer + ?Sized> HelpRenderer for Rc<R> {
+    fn render(&self, page: &HelpPage) -> String {
+        (**self).render(page)
+    }
+}
+
+impl Parser<'_> {
+    /// Sets the renderer used for the help instead of the default layout. Subcommands without a
+    /// renderer of their own inherit it once matched by [`crate::Parser::finalize`].
+    ///
+    /// A custom help function set with [`crate::Parser::set_help_fn`] or
+    /// [`crate::Parser::set_help_fn_with_parser`] takes precedence.
+    pub fn set_help_renderer(&mut self, renderer: impl HelpRenderer + 'static) {
+        self.help_renderer = Some(Rc::new(renderer));
+    }
+
+    /// Returns everything shown in the help, as passed to the renderer set with
+    /// [`crate::Parser::set_help_renderer`].
+    pub fn help_page(&self) -> HelpPage {
+        let flags = self
+            .visible_flags()
+            .into_iter()
+            .map(|(key, entry)| HelpFlag {
+                name: key.to_string(),
+                kind: match entry.typ {
+                    Flag::Bool => FlagKind::Bool,
+                    Flag::Value => FlagKind::Value,
+                    Flag::TriState => FlagKind::TriState,
+                    Flag::Count => FlagKind::Count,
+                },
+                usage: entry.usage.clone(),
+                choices: entry.choices.clone(),
+                default: entry.default.as_ref().map(|default| match entry.sensitive {
+                    true => REDACTED.to_string(),
+                    false => default.to_string(),
+                }),
+                required: self.required.contains(key),
+                env: env_var_names(self.env_prefix.as_deref(), key, entry),
+            })
+            .collect();
+        let arguments = self
+            .declared_positionals
+            .iter()
+            .map(|(name, entry)| HelpArgument {
+                name: name.to_string(),
+                usage: entry.usage.clone(),
+                required: entry.min_occurrences > 0,
+                multiple: entry.multiple,
+            })
+            .collect();
+        let commands = self
+            .grouped_subcommands()
+            .into_iter()
+            .flat_map(|(category, subcommands)| {
+                subcommands.into_iter().map(move |sub| HelpCommand {
+                    name: sub.name.clone(),
+                    about: sub.about.clone(),
+                    category: category.to_string(),
+                })
+            })
+            .collect();
+        HelpPage {
+            command: self.command.to_string(),
+            about: self.about.clone(),
+            usage: self.usage.clone(),
+            flags,
+            arguments,
+            commands,
+            examples: self.examples.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    struct Names;
+
+    impl HelpRenderer for Names {
+        fn render(&self, page: &HelpPage) -> String {
+            let flags: Vec<&str> = page.flags.iter().map(|flag| flag.name.as_str()).collect();
+            format!("{}: {}\n", page.command, flags.join(" "))
+        }
+    }
+
+    #[test]
+    fn help_page() {
+        let mut parser = Parser::from_iter(["tool"]);
+        parser.env_prefix("TOOL");
+        parser
+            .optional_flag("token", "this is used to authenticate")
+            .default_value("abc")
+            .sensitive();
+        parser.required_flag("level", "this is used to set the level");
+        parser.positional("files", "the files to read").multiple();
+        parser.add_subcommand("build", "compile the project");
+
+        let page = parser.help_page();
+        assert_eq!(
+            page.flags[1],
+            HelpFlag {
+                name: "token".to_string(),
+                kind: FlagKind::Value,
+                usage: "this is used to authenticate".to_string(),
+                choices: Vec::new(),
+                default: Some(REDACTED.to_string()),
+                required: false,
+                env: vec!["TOOL_TOKEN".to_string()],
+            }
+        );
+        assert!(page.flags[0].required);
+        assert_eq!(
+            page.arguments,
+            [HelpArgument {
+                name: "files".to_string(),
+                usage: "the files to read".to_string(),
+                required: true,
+                multiple: true,
+            }]
+        );
+        assert_eq!(page.commands[0].category, "Commands");
+    }
+
+    #[test]
+    fn shared_renderer() {
+        let renderer = Rc::new(Names);
+        let mut parser = Parser::from_iter(["tool", "build"]);
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.set_help_renderer(Rc::clone(&renderer));
+        parser
+            .add_subcommand("build", "compile the project")
+            .bool_flag("release", "this is used to build in release mode");
+        assert_eq!(parser.help(), "tool: verbose\n");
+
+        parser.finalize().unwrap();
+        assert_eq!(parser.help(), "tool build: release\n");
+
+        let mut other = Parser::from_iter(["other"]);
+        other.set_help_renderer(renderer);
+        other.set_help_fn(|| "custom\n".to_string());
+        assert_eq!(other.help(), "custom\n");
+    }
+}
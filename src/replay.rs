@@ -0,0 +1,296 @@
+//! Records parsed invocations to a file and replays them, see [`crate::Parser::replay_flags`].
+use std::borrow::Cow;
+
+use serde_json::{json, Map, Value};
+
+use crate::errors::{Error, Result};
+use crate::token::Token;
+use crate::{Flag, Parser, Source};
+
+/// Name of the flag which records the invocation to a file.
+const RECORD_FLAG: &str = "record";
+
+/// Name of the flag which replays an invocation recorded to a file.
+const REPLAY_FLAG: &str = "replay";
+
+/// Returns the source named as by its [`std::fmt::Display`] implementation.
+fn parse_source(name: &str) -> Option<Source> {
+    match name {
+        "cli" => Some(Source::Cli),
+        "env" => Some(Source::Env),
+        "config" => Some(Source::Config),
+        "default" => Some(Source::Default),
+        _ => None,
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Declares `-record <file>`, which writes the invocation to a JSON file once
+    /// [`crate::Parser::finalize`] succeeds, and `-replay <file>`, which parses the args of a
+    /// recorded invocation instead of the ones given.
+    ///
+    /// A recording holds the args and the resolved value and source of every flag, including
+    /// those of the subcommand given. Replaying also restores the values which came from the
+    /// environment or the configuration, so the run is reproduced on another machine. Sensitive
+    /// flags are left out of both, so a replay takes them from its own environment. Args given
+    /// with `-replay` are ignored, and a `-replay` taken as the value of another flag or by a
+    /// trailing positional, see [`crate::Parser::trailing_positional`], is not replayed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let path = std::env::temp_dir().join("yafp-replay-example.json");
+    /// let path = path.to_str().unwrap();
+    ///
+    /// let mut parser = Parser::from_iter(["head", "-num", "3", "file.txt", "-record", path]);
+    /// parser.replay_flags();
+    /// parser.optional_flag("num", "this is used to set the number of lines");
+    /// parser.finalize()?;
+    ///
+    /// let mut parser = Parser::from_iter(["head", "-replay", path]);
+    /// parser.replay_flags();
+    /// parser.optional_flag("num", "this is used to set the number of lines");
+    /// assert_eq!(vec!["file.txt"], parser.finalize()?);
+    /// assert_eq!(Some(3), parser.get_value::<u32>("num"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn replay_flags(&mut self) {
+        self.optional_flag(
+            RECORD_FLAG,
+            "this is used to record the invocation to a file",
+        );
+        self.optional_flag(
+            REPLAY_FLAG,
+            "this is used to replay an invocation from a file",
+        );
+        self.replay = true;
+    }
+
+    /// Returns the file given to `-replay` on the command line, if replaying is enabled.
+    pub(crate) fn replay_path(&self) -> Option<String> {
+        if !self.replay {
+            return None;
+        }
+        self.flags
+            .get(REPLAY_FLAG)
+            .filter(|entry| entry.source == Some(Source::Cli))
+            .and_then(|entry| entry.value.as_deref())
+            .map(str::to_string)
+    }
+
+    /// Replaces the args with the ones recorded in a file, discarding the flags parsed so far,
+    /// and queues the recorded values to restore.
+    pub(crate) fn load_replay(&mut self, path: String) -> Result<()> {
+        let invalid = |reason: String| Error::InvalidRecording(path.clone(), reason);
+        let contents = std::fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+        let recording: Value =
+            serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        let args = recording["args"]
+            .as_array()
+            .and_then(|args| {
+                args.iter()
+                    .map(|arg| Some(Cow::Owned(arg.as_str()?.to_string())))
+                    .collect::<Option<Vec<Cow<'a, str>>>>()
+            })
+            .ok_or_else(|| invalid("the args are not a list of strings".to_string()))?;
+        self.queue_recorded(&recording).map_err(invalid)?;
+
+        for (_, entry) in self.flags.iter_mut() {
+            if entry.source == Some(Source::Cli) {
+                entry.value = None;
+                entry.source = None;
+                entry.raw.clear();
+            }
+        }
+        self.args = Box::new(args.into_iter());
+        // The recorded args are parsed as given, so they cannot replay another recording.
+        self.replay = false;
+        Ok(())
+    }
+
+    /// Queues the recorded values which came from the environment or the configuration, on this
+    /// parser and on the recorded subcommand.
+    fn queue_recorded(&mut self, recording: &Value) -> std::result::Result<(), String> {
+        for (flag, recorded) in recording["values"].as_object().into_iter().flatten() {
+            let source = recorded["source"].as_str().and_then(parse_source);
+            let (Some(value), Some(source)) = (recorded["value"].as_str(), source) else {
+                return Err(format!("the value of '{}' is invalid", flag));
+            };
+            if !self.flags.contains_key(flag) {
+                return Err(format!("flag '{}' is not declared", flag));
+            }
+            if let Source::Env | Source::Config = source {
+                self.replayed
+                    .push((flag.to_string(), value.to_string(), source));
+            }
+        }
+
+        let recorded = &recording["subcommand"];
+        if recorded.is_null() {
+            return Ok(());
+        }
+        let name = recorded["name"].as_str().unwrap_or_default();
+        match self.subcommands.iter_mut().find(|sub| sub.name == name) {
+            Some(sub) => sub.parser.queue_recorded(recorded),
+            None => Err(format!("subcommand '{}' is not declared", name)),
+        }
+    }
+
+    /// Restores the replayed values over the resolved ones, so they do not depend on the
+    /// environment or the configuration of the replay.
+    pub(crate) fn restore_replayed(&mut self) {
+        for (flag, value, source) in std::mem::take(&mut self.replayed) {
+            if let Some(entry) = self.flags.get_mut(&flag) {
+                entry.value = Some(Cow::Owned(value));
+                entry.source = Some(source);
+                entry.env_var = None;
+            }
+        }
+    }
+
+    /// Writes the invocation to the file given to `-record`, if any.
+    pub(crate) fn write_record(&self) -> Result<()> {
+        let Some(path) = self.get_value::<String>(RECORD_FLAG) else {
+            return Ok(());
+        };
+        let mut recording = self.recorded_values();
+        recording["command"] = json!(self.command);
+        recording["args"] = json!(self.recorded_args());
+        // Serializing a JSON value never fails.
+        let contents = serde_json::to_string_pretty(&recording).unwrap_or_default();
+        std::fs::write(&path, contents + "\n")
+            .map_err(|e| Error::RecordingFailed(path, e.to_string()))
+    }
+
+    /// Returns the args read, without the recording flags and the sensitive flags with their
+    /// values.
+    fn recorded_args(&self) -> Vec<&str> {
+        let mut skipped = vec![RECORD_FLAG, REPLAY_FLAG];
+        let mut parsers = vec![self];
+        while let Some(parser) = parsers.pop() {
+            for (key, entry) in parser.flags.iter() {
+                if entry.sensitive && matches!(entry.typ, Flag::Value) {
+                    skipped.push(key);
+                }
+            }
+            parsers.extend(parser.subcommands.iter().map(|sub| &sub.parser));
+        }
+
+        let mut args = Vec::new();
+        let mut it = self.raw_args.iter();
+        while let Some(arg) = it.next() {
+            match self.classify(arg) {
+                Token::Flag(flag) if skipped.contains(&&*flag) => {
+                    it.next();
+                }
+                _ => args.push(arg.as_ref()),
+            }
+        }
+        args
+    }
+
+    /// Returns the value and source of every flag which is not sensitive, along with those of
+    /// the subcommand given.
+    fn recorded_values(&self) -> Value {
+        let mut values = Map::new();
+        for (key, entry) in self.flags.iter() {
+            let (Some(value), Some(source)) = (&entry.value, entry.source) else {
+                continue;
+            };
+            if entry.sensitive || key == RECORD_FLAG || key == REPLAY_FLAG {
+                continue;
+            }
+            values.insert(
+                key.to_string(),
+                json!({ "value": value, "source": source.to_string() }),
+            );
+        }
+
+        let mut recording = json!({ "values": values });
+        if let Some((name, sub)) = self.subcommand() {
+            let mut recorded = sub.recorded_values();
+            recorded["name"] = json!(name);
+            recording["subcommand"] = recorded;
+        }
+        recording
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn define(parser: &mut Parser) {
+        parser.replay_flags();
+        parser.env_prefix("TOOL");
+        parser.optional_flag("jobs", "this is used to set the number of jobs");
+        parser
+            .optional_flag("token", "this is used to authenticate")
+            .sensitive();
+        parser
+            .add_subcommand("build", "compile the project")
+            .optional_flag("target", "this is used to set the target")
+            .default_value("x86");
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let path = std::env::temp_dir().join(format!("yafp-replay-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let args = ["tool", "-token", "abc", "-record", path, "build", "app"];
+        let mut parser = Parser::from_iter(args);
+        define(&mut parser);
+        parser.set_env([("TOOL_JOBS".to_string(), "4".to_string())].into());
+        parser.finalize().unwrap();
+
+        let recording: Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(recording["args"], json!(["build", "app"]));
+        assert_eq!(
+            recording["values"]["jobs"],
+            json!({ "value": "4", "source": "env" })
+        );
+        assert!(recording["values"]["token"].is_null());
+        assert_eq!(recording["subcommand"]["name"], "build");
+
+        let mut parser = Parser::from_iter(["tool", "-replay", path]);
+        define(&mut parser);
+        parser.set_env([("TOOL_TOKEN".to_string(), "def".to_string())].into());
+        assert_eq!(parser.finalize().unwrap(), vec!["app"]);
+        assert_eq!(parser.get_value::<u32>("jobs"), Some(4));
+        assert_eq!(parser.value_source("jobs"), Some(Source::Env));
+        assert_eq!(parser.get_value::<String>("token").as_deref(), Some("def"));
+        let (name, build) = parser.subcommand().unwrap();
+        assert_eq!(name, "build");
+        assert_eq!(build.value_source("target"), Some(Source::Default));
+        std::fs::remove_file(path).unwrap();
+
+        // Only a `-replay` parsed as a flag replays.
+        let mut parser = Parser::from_iter(["tool", "-jobs", "-replay", "build"]);
+        define(&mut parser);
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.get_value::<String>("jobs").as_deref(),
+            Some("-replay")
+        );
+        let mut parser = Parser::from_iter(["tool", "-verbose", "--", "git", "-replay", "x"]);
+        parser.replay_flags();
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.trailing_positional("command", "the command to run");
+        parser.finalize().unwrap();
+        let command: Vec<String> = parser.get_values("command").unwrap();
+        assert_eq!(command, ["git", "-replay", "x"]);
+
+        let mut parser = Parser::from_iter(["tool", "-replay", "does-not-exist.json"]);
+        define(&mut parser);
+        assert!(matches!(
+            parser.finalize(),
+            Err(Error::InvalidRecording(path, _)) if path == "does-not-exist.json"
+        ));
+    }
+}
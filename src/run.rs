@@ -0,0 +1,279 @@
+//! Runs a parser as the whole entry point of a program.
+use std::io::{self, IsTerminal};
+use std::process::ExitCode;
+
+use crate::errors::Error;
+use crate::style::Painter;
+use crate::Parser;
+
+/// The exit code when a handler fails.
+const FAILURE: u8 = 1;
+
+/// The exit code when the command line is invalid.
+const USAGE: u8 = 2;
+
+impl Parser<'_> {
+    /// Dispatches the command line with [`crate::Parser::dispatch`], reporting the outcome on
+    /// stdout and stderr, and returns the exit code of the process.
+    ///
+    /// See [`crate::Parser::run_with`] for what is written and which exit codes are used. Help
+    /// and errors are colored according to [`crate::Parser::set_color`], depending on whether
+    /// stdout and stderr are terminals.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::process::ExitCode;
+    /// use yafp::Parser;
+    ///
+    /// fn main() -> ExitCode {
+    ///     let mut parser = Parser::from_env();
+    ///     parser.set_version("1.2.0");
+    ///     parser
+    ///         .add_subcommand("build", "compile the project")
+    ///         .set_handler(|_| Ok(()));
+    ///     parser.run()
+    /// }
+    /// ```
+    pub fn run(&mut self) -> ExitCode {
+        let (mut out, mut err) = (io::stdout(), io::stderr());
+        let colors = (
+            self.color_for(out.is_terminal()),
+            self.color_for(err.is_terminal()),
+        );
+        ExitCode::from(self.run_colored(&mut out, &mut err, colors))
+    }
+
+    /// Dispatches the command line with [`crate::Parser::dispatch`], writing the outcome to the
+    /// given writers, and returns the exit code of the process.
+    ///
+    /// Help, version and completion scripts are written to `out` with exit code 0. Errors are
+    /// written to `err` prefixed with the command name, with exit code 1 if a handler failed and
+    /// 2 if the command line is invalid, see [`crate::Parser::set_help_hint`] to follow the
+    /// latter with a hint. Without the `help` feature, `-help` is reported as an invalid command
+    /// line. The writers are not treated as terminals, so output is only colored if
+    /// [`crate::Parser::set_color`] or the environment asks for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "-verbose"]);
+    /// let (mut out, mut err) = (Vec::new(), Vec::new());
+    ///
+    /// assert_eq!(2, parser.run_with(&mut out, &mut err));
+    /// assert_eq!(b"tool: argument 'verbose' is not recognized\n", &err[..]);
+    /// ```
+    pub fn run_with(&mut self, out: &mut impl io::Write, err: &mut impl io::Write) -> u8 {
+        let colors = (self.color_for(false), self.color_for(false));
+        self.run_colored(out, err, colors)
+    }
+
+    /// Follows errors about an invalid command line reported by [`crate::Parser::run`] and
+    /// [`crate::Parser::run_with`] with the usage line and a hint to run `-help`, both for the
+    /// subcommand given if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "-verbose"]);
+    /// parser.set_help_hint(true);
+    /// let (mut out, mut err) = (Vec::new(), Vec::new());
+    ///
+    /// assert_eq!(2, parser.run_with(&mut out, &mut err));
+    /// assert_eq!(
+    ///     String::from_utf8(err).unwrap(),
+    ///     [
+    ///         "tool: argument 'verbose' is not recognized\n",
+    ///         "Usage: tool [options...]\n",
+    ///         "Run 'tool -help' for more information.\n",
+    ///     ]
+    ///     .concat()
+    /// );
+    /// ```
+    #[cfg(feature = "help")]
+    pub fn set_help_hint(&mut self, enabled: bool) {
+        self.help_hint = enabled;
+    }
+
+    /// Writes the usage line and the hint to run `-help` of the innermost subcommand given.
+    #[cfg(feature = "help")]
+    fn write_help_hint(&self, err: &mut impl io::Write, p: Painter<'_>) -> io::Result<()> {
+        let mut parser: &Parser = self;
+        while let Some((_, sub)) = parser.subcommand() {
+            parser = sub;
+        }
+        let mut usage = String::new();
+        // Writing into a String never fails.
+        let _ = parser.write_usage(&mut usage, p);
+        write!(err, "{}", usage)?;
+        writeln!(err, "Run '{} -help' for more information.", parser.command)
+    }
+
+    /// Dispatches the command line, writing the outcome to each writer colored or not.
+    fn run_colored(
+        &mut self,
+        out: &mut impl io::Write,
+        err: &mut impl io::Write,
+        (out_color, err_color): (bool, bool),
+    ) -> u8 {
+        // The theme is cloned as dispatching borrows the parser mutably.
+        let theme = self.theme().clone();
+        let out_painter = Painter::new(out_color.then_some(&theme));
+        let err_painter = Painter::new(err_color.then_some(&theme));
+        // Nothing sensible can be done when writing the outcome fails, the exit code still is.
+        match self.dispatch() {
+            Ok(()) => 0,
+            #[cfg(feature = "help")]
+            Err(Error::HelpRequested) => {
+                let _ = self.write_help_io_painted(out, out_painter);
+                0
+            }
+            Err(Error::VersionRequested) => {
+                let _ = writeln!(
+                    out,
+                    "{} {}",
+                    out_painter.name(&self.command),
+                    self.version().unwrap_or_default()
+                );
+                0
+            }
+            #[cfg(feature = "completions")]
+            Err(Error::CompletionsRequested(shell)) => {
+                let _ = write!(out, "{}", self.completions(shell));
+                0
+            }
+            Err(e) => {
+                let prefix = format!("{}:", self.command);
+                let _ = writeln!(err, "{} {}", err_painter.error(&prefix), e);
+                match e {
+                    Error::CommandFailed(..) => FAILURE,
+                    _ => {
+                        #[cfg(feature = "help")]
+                        if self.help_hint {
+                            let _ = self.write_help_hint(err, err_painter);
+                        }
+                        USAGE
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Defines a `main` function which declares the flags with a setup block, then runs a body with
+/// the parsed parser through [`crate::Parser::run`].
+///
+/// The parser is initialized with [`crate::Parser::from_env`]. The body is a handler, see
+/// [`crate::Parser::set_handler`], which returns a `Result` and can read the remaining args with
+/// [`crate::Parser::positionals`]. Help, version and errors are reported by
+/// [`crate::Parser::run_with`], which also picks the exit code.
+///
+/// # Examples
+///
+/// ```no_run
+/// yafp::main!(
+///     |parser| {
+///         parser.bool_flag("verbose", "this is used to get verbose output");
+///         parser.required_flag("num", "this is a required flag");
+///     },
+///     |parser| {
+///         let verbose: bool = parser.get_value("verbose").unwrap();
+///         let num: u32 = parser.get_value("num").ok_or("num must be a number")?;
+///         println!("verbose: {}, num: {}", verbose, num);
+///         println!("remaining: {}", parser.positionals().join(", "));
+///         Ok(())
+///     }
+/// );
+/// ```
+#[macro_export]
+macro_rules! main {
+    (|$parser:ident| $setup:block, |$matches:ident| $body:block $(,)?) => {
+        fn main() -> ::std::process::ExitCode {
+            let mut $parser = $crate::Parser::from_env();
+            $setup
+            $parser.set_handler(
+                |$matches| -> ::std::result::Result<(), ::std::boxed::Box<dyn ::std::error::Error>> {
+                    $body
+                },
+            );
+            $parser.run()
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ColorChoice;
+
+    #[test]
+    fn outcomes() {
+        let mut parser = Parser::from_iter(["tool", "build", "-version"]);
+        parser.set_version("1.2.0");
+        parser.add_subcommand("build", "compile the project");
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        assert_eq!(parser.run_with(&mut out, &mut err), 0);
+        assert_eq!(out, b"tool 1.2.0\n");
+        assert!(err.is_empty());
+
+        let mut parser = Parser::from_iter(["tool", "build"]);
+        parser
+            .add_subcommand("build", "compile the project")
+            .set_handler(|_| Err("no sources".into()));
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        assert_eq!(parser.run_with(&mut out, &mut err), 1);
+        assert!(out.is_empty());
+        assert_eq!(err, b"tool: command 'tool build' failed: no sources\n");
+    }
+
+    #[test]
+    fn colored() {
+        let mut parser = Parser::from_iter(["tool", "-verbose"]);
+        parser.set_color(ColorChoice::Always);
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        assert_eq!(parser.run_with(&mut out, &mut err), 2);
+        assert_eq!(
+            err,
+            b"\x1b[1;31mtool:\x1b[0m argument 'verbose' is not recognized\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help() {
+        let mut parser = Parser::from_iter(["tool", "-help"]);
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        assert_eq!(parser.run_with(&mut out, &mut err), 0);
+        assert_eq!(out, parser.help().as_bytes());
+        assert!(err.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help_hint() {
+        let mut parser = Parser::from_iter(["tool", "build", "-jobs"]);
+        parser.set_help_hint(true);
+        parser
+            .add_subcommand("build", "compile the project")
+            .optional_flag("jobs", "this is used to set the number of jobs");
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        assert_eq!(parser.run_with(&mut out, &mut err), 2);
+        assert!(String::from_utf8(err).unwrap().ends_with(
+            "\nUsage: tool build [options...]\nRun 'tool build -help' for more information.\n"
+        ));
+
+        // Failed handlers are not usage errors.
+        let mut parser = Parser::from_iter(["tool"]);
+        parser.set_help_hint(true);
+        parser.set_handler(|_| Err("no sources".into()));
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        assert_eq!(parser.run_with(&mut out, &mut err), 1);
+        assert_eq!(err, b"tool: command 'tool' failed: no sources\n");
+    }
+}
@@ -0,0 +1,133 @@
+//! Splits a command line string into arguments using shell-like quoting rules, and joins
+//! arguments back into one.
+use std::borrow::Cow;
+
+use crate::errors::{Error, Result};
+
+/// Splits a command line string into arguments.
+///
+/// Arguments are separated by whitespace. Single quotes preserve everything up to the closing
+/// quote, double quotes allow `\"` and `\\` escapes and a backslash outside of quotes escapes the
+/// next character.
+///
+/// # Errors
+///
+/// Returns [`crate::errors::Error::UnterminatedQuote`] if a quote is never closed.
+///
+/// # Examples
+///
+/// ```
+/// use yafp::split::split;
+/// use yafp::errors::Error;
+///
+/// let args = split("head -num 3 'my file.txt'")?;
+/// assert_eq!(vec!["head", "-num", "3", "my file.txt"], args);
+/// # Ok::<(), Error>(())
+/// ```
+pub fn split(line: &str) -> Result<Vec<String>> {
+    let mut args: Vec<String> = Vec::new();
+    let mut current: Option<String> = None;
+
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err(Error::UnterminatedQuote('\'')),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) if c == '"' || c == '\\' => arg.push(c),
+                            Some(c) => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                            None => return Err(Error::UnterminatedQuote('"')),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(Error::UnterminatedQuote('"')),
+                    }
+                }
+            }
+            '\\' => {
+                let arg = current.get_or_insert_with(String::new);
+                arg.push(chars.next().unwrap_or('\\'));
+            }
+            c if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if let Some(arg) = current {
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+/// Joins arguments into a command line string, quoting those which need it so [`split`] and
+/// POSIX shells return the arguments unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use yafp::split::join;
+///
+/// assert_eq!("head -num 3 'my file.txt'", join(["head", "-num", "3", "my file.txt"]));
+/// ```
+pub fn join<S: AsRef<str>>(args: impl IntoIterator<Item = S>) -> String {
+    args.into_iter()
+        .map(|arg| quote(arg.as_ref()).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns an argument as is if it only has characters which are never special to a shell, or
+/// else in single quotes.
+fn quote(arg: &str) -> Cow<'_, str> {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        Cow::Borrowed(arg)
+    } else {
+        Cow::Owned(format!("'{}'", arg.replace('\'', r"'\''")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn quoting() {
+        let args = split(r#"cmd  -msg "say \"hi\"" '' a\ b"#).unwrap();
+        assert_eq!(args, vec!["cmd", "-msg", "say \"hi\"", "", "a b"]);
+    }
+
+    #[test]
+    fn joined() {
+        let args = ["cmd", "-msg", "it's \"here\"", "", "a\\b", "$HOME"];
+        let line = join(args);
+        assert_eq!(line, r#"cmd -msg 'it'\''s "here"' '' 'a\b' '$HOME'"#);
+        assert_eq!(split(&line).unwrap(), args);
+    }
+
+    #[test]
+    fn unterminated() {
+        let result = split("cmd 'oops");
+        assert!(matches!(result, Err(Error::UnterminatedQuote('\''))));
+    }
+}
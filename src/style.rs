@@ -0,0 +1,402 @@
+//! Decides when output is colored and defines the styles used to color it.
+use std::fmt;
+use std::io::IsTerminal;
+
+use crate::color::Rgb;
+use crate::Parser;
+
+/// Represents when help and errors are colored, see [`crate::Parser::set_color`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Colors output written to a terminal, unless `NO_COLOR` is set, or any output when
+    /// `CLICOLOR_FORCE` is set to a value other than `0`.
+    #[default]
+    Auto,
+    /// Always colors output.
+    Always,
+    /// Never colors output.
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns whether output written to a stream is colored, checking the environment when
+    /// the choice is [`ColorChoice::Auto`].
+    fn enabled(self, terminal: bool) -> bool {
+        let var = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+        self.resolve(terminal, var("NO_COLOR"), var("CLICOLOR_FORCE"))
+    }
+
+    /// Returns whether output is colored given the values of `NO_COLOR` and `CLICOLOR_FORCE`.
+    fn resolve(self, terminal: bool, no_color: Option<String>, force: Option<String>) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto if no_color.is_some() => false,
+            ColorChoice::Auto if force.is_some_and(|force| force != "0") => true,
+            ColorChoice::Auto => terminal,
+        }
+    }
+}
+
+/// The escape sequence resetting every style.
+const RESET: &str = "\x1b[0m";
+
+/// Represents a terminal color, either one of the basic colors whose exact shade is picked by the
+/// terminal palette, or an exact RGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// The basic black color.
+    Black,
+    /// The basic red color.
+    Red,
+    /// The basic green color.
+    Green,
+    /// The basic yellow color.
+    Yellow,
+    /// The basic blue color.
+    Blue,
+    /// The basic magenta color.
+    Magenta,
+    /// The basic cyan color.
+    Cyan,
+    /// The basic white color.
+    White,
+    /// An exact color, shown by terminals supporting 24-bit colors.
+    Rgb(Rgb),
+}
+
+impl Color {
+    /// Writes the parameters of the escape sequence setting the color as foreground.
+    fn write_code(self, f: &mut fmt::Formatter) -> fmt::Result {
+        let basic = match self {
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::Rgb(Rgb { r, g, b }) => return write!(f, "38;2;{};{};{}", r, g, b),
+        };
+        write!(f, "{}", basic)
+    }
+}
+
+/// Represents how a piece of text is styled, see [`Theme`].
+///
+/// # Examples
+///
+/// ```
+/// use yafp::color::Rgb;
+/// use yafp::style::{Color, Style};
+///
+/// let style = Style::new().bold().fg(Color::Rgb(Rgb::new(255, 128, 0)));
+/// assert_eq!(style.to_string(), "\x1b[1;38;2;255;128;0m");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    fg: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    /// Creates a style leaving text as is.
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bold: false,
+            dimmed: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    /// Sets the color of the text.
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Makes the text bold.
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Makes the text dimmed.
+    pub const fn dimmed(mut self) -> Self {
+        self.dimmed = true;
+        self
+    }
+
+    /// Makes the text italic.
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Underlines the text.
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Returns whether the style leaves text as is.
+    fn is_plain(&self) -> bool {
+        *self == Style::new()
+    }
+}
+
+/// Writes the escape sequence starting the style, or nothing if the style is plain.
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_plain() {
+            return Ok(());
+        }
+
+        f.write_str("\x1b[")?;
+        let mut separator = "";
+        for (set, code) in [
+            (self.bold, "1"),
+            (self.dimmed, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+        ] {
+            if set {
+                write!(f, "{}{}", separator, code)?;
+                separator = ";";
+            }
+        }
+        if let Some(color) = self.fg {
+            f.write_str(separator)?;
+            color.write_code(f)?;
+        }
+        f.write_str("m")
+    }
+}
+
+/// The styles used for colored help and errors, see [`crate::Parser::set_theme`].
+///
+/// # Examples
+///
+/// ```
+/// use yafp::Parser;
+/// use yafp::style::{Color, Style, Theme};
+///
+/// let mut parser = Parser::from_iter(["tool"]);
+/// parser.set_theme(Theme {
+///     heading: Style::new().bold().fg(Color::Magenta),
+///     ..Theme::default()
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Section headings such as `Usage:` and `Options:`, bold and underlined by default.
+    pub heading: Style,
+    /// Flag, subcommand and command names, bold by default.
+    pub name: Style,
+    /// Placeholders such as the `value` of value flags, italic by default.
+    pub placeholder: Style,
+    /// The command name prefixing error messages, bold and red by default.
+    pub error: Style,
+    /// Whether names with online documentation are written as OSC 8 terminal hyperlinks, see
+    /// [`crate::FlagBuilder::docs_url`], enabled by default.
+    pub hyperlinks: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            heading: Style::new().bold().underline(),
+            name: Style::new().bold(),
+            placeholder: Style::new().italic(),
+            error: Style::new().bold().fg(Color::Red),
+            hyperlinks: true,
+        }
+    }
+}
+
+/// Wraps text in the escape sequences of a style of the theme, if any.
+///
+/// The text may be any [`std::fmt::Display`], such as [`std::format_args`], so names with a
+/// prefix are written without allocating.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Painter<'t> {
+    theme: Option<&'t Theme>,
+}
+
+impl<'t> Painter<'t> {
+    /// A painter which never adds escape sequences.
+    #[cfg_attr(not(feature = "help"), allow(dead_code))]
+    pub(crate) const PLAIN: Painter<'static> = Painter { theme: None };
+
+    pub(crate) fn new(theme: Option<&'t Theme>) -> Self {
+        Self { theme }
+    }
+
+    /// Styles section headings such as `Usage:` and `Options:`.
+    #[cfg_attr(not(feature = "help"), allow(dead_code))]
+    pub(crate) fn heading<'s, T: fmt::Display>(&self, text: T) -> Painted<'s, 't, T> {
+        self.paint(text, |theme| &theme.heading)
+    }
+
+    /// Styles flag, subcommand and command names.
+    pub(crate) fn name<'s, T: fmt::Display>(&self, text: T) -> Painted<'s, 't, T> {
+        self.paint(text, |theme| &theme.name)
+    }
+
+    /// Styles placeholders such as the `value` of value flags.
+    #[cfg_attr(not(feature = "help"), allow(dead_code))]
+    pub(crate) fn placeholder<'s, T: fmt::Display>(&self, text: T) -> Painted<'s, 't, T> {
+        self.paint(text, |theme| &theme.placeholder)
+    }
+
+    /// Styles the prefix of error messages.
+    pub(crate) fn error<'s, T: fmt::Display>(&self, text: T) -> Painted<'s, 't, T> {
+        self.paint(text, |theme| &theme.error)
+    }
+
+    fn paint<'s, T>(&self, text: T, style: fn(&Theme) -> &Style) -> Painted<'s, 't, T> {
+        Painted {
+            text,
+            style: self.theme.map(style).filter(|style| !style.is_plain()),
+            hyperlinks: self.theme.is_some_and(|theme| theme.hyperlinks),
+            url: None,
+        }
+    }
+}
+
+/// Text displayed with the escape sequences of a style and a hyperlink, if any.
+pub(crate) struct Painted<'s, 't, T> {
+    text: T,
+    style: Option<&'t Style>,
+    hyperlinks: bool,
+    url: Option<&'s str>,
+}
+
+impl<'s, T> Painted<'s, '_, T> {
+    /// Links the text to a URL, if hyperlinks are enabled.
+    #[cfg_attr(not(feature = "help"), allow(dead_code))]
+    pub(crate) fn link(mut self, url: Option<&'s str>) -> Self {
+        self.url = url.filter(|_| self.hyperlinks);
+        self
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Painted<'_, '_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(url) = self.url {
+            write!(f, "\x1b]8;;{}\x1b\\", url)?;
+        }
+        match self.style {
+            Some(style) => write!(f, "{}{}{}", style, self.text, RESET)?,
+            None => write!(f, "{}", self.text)?,
+        }
+        if self.url.is_some() {
+            f.write_str("\x1b]8;;\x1b\\")?;
+        }
+        Ok(())
+    }
+}
+
+impl Parser<'_> {
+    /// Sets when help and errors written by [`crate::Parser::run`] are colored, which defaults to
+    /// [`ColorChoice::Auto`].
+    ///
+    /// With [`ColorChoice::Auto`] output is colored when the stream it is written to is a
+    /// terminal, following the `NO_COLOR` and `CLICOLOR_FORCE` conventions: a non-empty
+    /// `NO_COLOR` disables color and a `CLICOLOR_FORCE` other than `0` enables it even when the
+    /// stream is not a terminal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::{ColorChoice, Parser};
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// parser.set_color(ColorChoice::Never);
+    ///
+    /// assert!(!parser.color_enabled(&std::io::stdout()));
+    /// ```
+    pub fn set_color(&mut self, choice: ColorChoice) {
+        self.color = choice;
+    }
+
+    /// Returns whether output written to a stream such as [`std::io::stdout`] is colored,
+    /// according to the choice set with [`crate::Parser::set_color`].
+    pub fn color_enabled(&self, stream: &impl IsTerminal) -> bool {
+        self.color.enabled(stream.is_terminal())
+    }
+
+    /// Sets the styles used for colored help and errors, which default to [`Theme::default`].
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Returns the styles used for colored help and errors.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Returns whether output written to a stream which may be a terminal is colored.
+    pub(crate) fn color_for(&self, terminal: bool) -> bool {
+        self.color.enabled(terminal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn choice() {
+        let set = || Some("1".to_string());
+        assert!(ColorChoice::Auto.resolve(true, None, None));
+        assert!(!ColorChoice::Auto.resolve(false, None, None));
+        assert!(!ColorChoice::Auto.resolve(true, set(), None));
+        assert!(!ColorChoice::Auto.resolve(true, set(), set()));
+        assert!(ColorChoice::Auto.resolve(false, None, set()));
+        assert!(!ColorChoice::Auto.resolve(false, None, Some("0".to_string())));
+        assert!(ColorChoice::Always.resolve(false, set(), None));
+        assert!(!ColorChoice::Never.resolve(true, None, set()));
+    }
+
+    #[test]
+    fn painted() {
+        let theme = Theme::default();
+        assert_eq!(
+            Painter::new(Some(&theme)).name("-v").to_string(),
+            "\x1b[1m-v\x1b[0m"
+        );
+        assert_eq!(
+            Painter::new(Some(&theme)).error("tool:").to_string(),
+            "\x1b[1;31mtool:\x1b[0m"
+        );
+        assert_eq!(Painter::PLAIN.name("-v").to_string(), "-v");
+
+        let theme = Theme {
+            name: Style::new(),
+            ..Theme::default()
+        };
+        assert_eq!(Painter::new(Some(&theme)).name("-v").to_string(), "-v");
+        assert_eq!(
+            Painter::new(Some(&theme))
+                .name("-v")
+                .link(Some("https://example.com"))
+                .to_string(),
+            "\x1b]8;;https://example.com\x1b\\-v\x1b]8;;\x1b\\"
+        );
+        assert_eq!(
+            Painter::PLAIN
+                .name("-v")
+                .link(Some("https://example.com"))
+                .to_string(),
+            "-v"
+        );
+    }
+}
@@ -0,0 +1,557 @@
+//! Declares subcommands, each parsed by its own child parser.
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::errors::{Error, Result};
+#[cfg(feature = "completions")]
+use crate::suggest;
+use crate::Parser;
+
+/// A subcommand declared with [`crate::Parser::add_subcommand`].
+pub(crate) struct Subcommand<'a> {
+    pub(crate) name: String,
+    pub(crate) about: String,
+    pub(crate) category: Option<String>,
+    pub(crate) hidden: bool,
+    pub(crate) parser: Parser<'a>,
+}
+
+impl fmt::Debug for Subcommand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Subcommand")
+            .field("name", &self.name)
+            .field("about", &self.about)
+            .field("category", &self.category)
+            .field("hidden", &self.hidden)
+            .field("parser", &self.parser)
+            .finish()
+    }
+}
+
+/// A function run by [`crate::Parser::dispatch`] with the parser it is attached to.
+pub(crate) type Handler<'a> =
+    Box<dyn Fn(&Parser<'a>) -> std::result::Result<(), Box<dyn std::error::Error>>>;
+
+/// A function run by [`crate::Parser::dispatch`] around a handler, with the command and parser of
+/// the handler.
+pub(crate) type Hook<'a> = Box<dyn Fn(&str, &Parser<'a>)>;
+
+/// What the first positional argument of a parser with subcommands turned out to be.
+pub(crate) enum Matched {
+    /// The index of a declared subcommand.
+    Declared(usize),
+    /// An unknown name, reported as an external subcommand.
+    External,
+}
+
+impl<'a> Parser<'a> {
+    /// Declares a subcommand and returns its parser, on which the flags of the subcommand are
+    /// declared.
+    ///
+    /// The first positional argument selects the subcommand, flags before it belong to this
+    /// parser and everything after it is parsed by the subcommand during
+    /// [`crate::Parser::finalize`], which returns the remaining args of the subcommand. The
+    /// command of the subcommand parser is prefixed with this command, such as `tool build`,
+    /// and its about text is the given description. Once any subcommand is declared an unknown
+    /// first positional argument is an error of type [`crate::errors::Error::UnknownSubcommand`],
+    /// which suggests the closest subcommand name for a likely typo with the `completions`
+    /// feature, see [`crate::Parser::allow_external_subcommands`] to accept it instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "-verbose", "build", "-release", "app"]);
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser
+    ///     .add_subcommand("build", "compile the project")
+    ///     .bool_flag("release", "this is used to build with optimizations");
+    ///
+    /// let remaining = parser.finalize()?;
+    /// assert_eq!(vec!["app"], remaining);
+    /// assert_eq!(Some(true), parser.get_value::<bool>("verbose"));
+    ///
+    /// let (name, build) = parser.subcommand().unwrap();
+    /// assert_eq!("build", name);
+    /// assert_eq!(Some(true), build.get_value::<bool>("release"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn add_subcommand(&mut self, name: &str, about: &str) -> &mut Parser<'a> {
+        let mut parser = Parser::from_source(std::iter::empty::<&str>());
+        parser.command = Cow::Owned(format!("{} {}", self.command, name));
+        parser.bin_path = self.bin_path.clone();
+        parser.value_parsers = self.value_parsers.clone();
+        #[cfg(feature = "help")]
+        parser.set_about(about);
+        self.subcommands.push(Subcommand {
+            name: name.to_string(),
+            about: about.to_string(),
+            category: self.category.clone(),
+            hidden: false,
+            parser,
+        });
+        &mut self.subcommands.last_mut().unwrap().parser
+    }
+
+    /// Hides a declared subcommand from the help and from typo suggestions, it is still parsed and
+    /// dispatched normally.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "gc"]);
+    /// parser.add_subcommand("build", "compile the project");
+    /// parser.add_subcommand("gc", "prune the internal caches");
+    /// parser.hide_subcommand("gc");
+    ///
+    /// # #[cfg(feature = "help")]
+    /// assert_eq!("Commands:\n  build\n\tcompile the project\n", parser.help_commands());
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some("gc"), parser.subcommand().map(|(name, _)| name));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn hide_subcommand(&mut self, name: &str) {
+        for sub in self.subcommands.iter_mut().filter(|sub| sub.name == name) {
+            sub.hidden = true;
+        }
+    }
+
+    /// Assigns the subcommands declared after this call to a category, listed in its own section
+    /// of the help.
+    ///
+    /// Subcommands declared before any category are listed under `Commands:` first, followed by
+    /// each category in the order it was first used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    ///
+    /// let mut parser = Parser::from_iter(["tool"]);
+    /// parser.subcommand_category("Core commands");
+    /// parser.add_subcommand("build", "compile the project");
+    /// parser.subcommand_category("Advanced");
+    /// parser.add_subcommand("vendor", "vendor all dependencies");
+    ///
+    /// # #[cfg(feature = "help")]
+    /// assert_eq!(
+    ///     parser.help_commands(),
+    ///     [
+    ///         "Core commands:\n",
+    ///         "  build\n",
+    ///         "\tcompile the project\n",
+    ///         "Advanced:\n",
+    ///         "  vendor\n",
+    ///         "\tvendor all dependencies\n",
+    ///     ]
+    ///     .concat()
+    /// );
+    /// ```
+    pub fn subcommand_category(&mut self, category: &str) {
+        self.category = Some(category.to_string());
+    }
+
+    /// Attaches a handler to this parser, run by [`crate::Parser::dispatch`] with this parser
+    /// when it is the most nested parser with a handler for the given subcommands.
+    pub fn set_handler(
+        &mut self,
+        f: impl Fn(&Parser<'a>) -> std::result::Result<(), Box<dyn std::error::Error>> + 'static,
+    ) {
+        self.handler = Some(Box::new(f));
+    }
+
+    /// Parses the arguments like [`crate::Parser::finalize`] and runs the handler of the most
+    /// nested subcommand given on the command line, falling back to the handlers of its parents.
+    ///
+    /// Nothing is run if no parser along the way has a handler.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`crate::Parser::finalize`], and
+    /// [`crate::errors::Error::CommandFailed`] if the handler fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "build", "-release"]);
+    /// let build = parser.add_subcommand("build", "compile the project");
+    /// build.bool_flag("release", "this is used to build with optimizations");
+    /// build.set_handler(|build| {
+    ///     let release: bool = build.get_value("release").unwrap();
+    ///     println!("building, release: {}", release);
+    ///     Ok(())
+    /// });
+    /// parser
+    ///     .add_subcommand("clean", "remove build artifacts")
+    ///     .set_handler(|_| Err("nothing to clean".into()));
+    ///
+    /// parser.dispatch()?;
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn dispatch(&mut self) -> Result<()> {
+        self.finalize()?;
+        let Some(parser) = self.handler_parser() else {
+            return Ok(());
+        };
+        let Some(handler) = &parser.handler else {
+            return Ok(());
+        };
+
+        for hook in &self.before_hooks {
+            hook(&parser.command, parser);
+        }
+        let result = handler(parser)
+            .map_err(|e| Error::CommandFailed(parser.command.to_string(), e.to_string()));
+        for hook in &self.after_hooks {
+            hook(&parser.command, parser);
+        }
+        result
+    }
+
+    /// Returns the most nested parser with a handler for the given subcommands, if there is one.
+    fn handler_parser(&self) -> Option<&Parser<'a>> {
+        self.subcommand()
+            .and_then(|(_, sub)| sub.handler_parser())
+            .or(self.handler.as_ref().map(|_| self))
+    }
+
+    /// Adds a hook run by [`crate::Parser::dispatch`] on this parser before any handler, with the
+    /// command and the parser of the handler, such as to set up logging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "build"]);
+    /// parser
+    ///     .add_subcommand("build", "compile the project")
+    ///     .set_handler(|_| Ok(()));
+    ///
+    /// let start = Instant::now();
+    /// parser.add_before_hook(|command, _| eprintln!("running {}", command));
+    /// parser.add_after_hook(move |command, _| eprintln!("{} took {:?}", command, start.elapsed()));
+    ///
+    /// parser.dispatch()?;
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn add_before_hook(&mut self, f: impl Fn(&str, &Parser<'a>) + 'static) {
+        self.before_hooks.push(Box::new(f));
+    }
+
+    /// Adds a hook run by [`crate::Parser::dispatch`] on this parser after any handler, even if
+    /// it failed, with the command and the parser of the handler, such as to flush telemetry.
+    pub fn add_after_hook(&mut self, f: impl Fn(&str, &Parser<'a>) + 'static) {
+        self.after_hooks.push(Box::new(f));
+    }
+
+    /// Returns the name and parser of the subcommand given on the command line, if any.
+    pub fn subcommand(&self) -> Option<(&str, &Parser<'a>)> {
+        self.matched
+            .map(|i| &self.subcommands[i])
+            .map(|sub| (sub.name.as_str(), &sub.parser))
+    }
+
+    /// Returns the names of the subcommands given on the command line, outermost first, such as
+    /// `["remote", "add"]` for `tool remote add`.
+    ///
+    /// Use [`crate::Parser::subcommand`] on each level to reach the flags and positionals of a
+    /// nested subcommand.
+    pub fn subcommand_path(&self) -> Vec<&str> {
+        let mut path = Vec::new();
+        let mut parser = self;
+        while let Some((name, sub)) = parser.subcommand() {
+            path.push(name);
+            parser = sub;
+        }
+        path
+    }
+
+    /// Accepts unknown subcommands as external commands instead of returning an error, so the
+    /// caller can run a plugin such as `tool-foo` for `tool foo`.
+    ///
+    /// The arguments after an external subcommand are not parsed and are all returned by
+    /// [`crate::Parser::finalize`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "deploy", "-env", "prod"]);
+    /// parser.add_subcommand("build", "compile the project");
+    /// parser.allow_external_subcommands();
+    ///
+    /// let remaining = parser.finalize()?;
+    /// assert_eq!(Some("deploy"), parser.external_subcommand());
+    /// assert_eq!(vec!["-env", "prod"], remaining);
+    ///
+    /// /// The caller would now run `tool-deploy -env prod`.
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn allow_external_subcommands(&mut self) {
+        self.allow_external = true;
+    }
+
+    /// Returns the name of the external subcommand given on the command line, if any, see
+    /// [`crate::Parser::allow_external_subcommands`].
+    pub fn external_subcommand(&self) -> Option<&str> {
+        self.external.as_deref()
+    }
+
+    /// Returns whether the first positional argument selects a subcommand.
+    pub(crate) fn has_subcommands(&self) -> bool {
+        !self.subcommands.is_empty() || self.allow_external
+    }
+
+    /// Matches the first positional argument against the declared subcommands.
+    pub(crate) fn match_subcommand(&mut self, name: &str) -> Result<Matched> {
+        match self.subcommands.iter().position(|sub| sub.name == name) {
+            Some(i) => {
+                self.matched = Some(i);
+                Ok(Matched::Declared(i))
+            }
+            None if self.allow_external => {
+                self.external = Some(name.to_string());
+                Ok(Matched::External)
+            }
+            None => {
+                #[cfg(feature = "completions")]
+                let suggestion = {
+                    let names = self
+                        .subcommands
+                        .iter()
+                        .filter(|sub| !sub.hidden)
+                        .map(|sub| sub.name.as_str());
+                    suggest::closest(name, names).map(str::to_string)
+                };
+                #[cfg(not(feature = "completions"))]
+                let suggestion = None;
+                Err(Error::UnknownSubcommand(name.to_string(), suggestion))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn nested() {
+        let mut parser = Parser::from_iter(["tool", "remote", "add", "-fetch", "origin"]);
+        parser
+            .add_subcommand("remote", "manage remotes")
+            .add_subcommand("add", "add a remote")
+            .bool_flag("fetch", "this is used to fetch after adding");
+
+        let remaining = parser.finalize().unwrap();
+        assert_eq!(remaining, vec!["origin"]);
+
+        let (_, remote) = parser.subcommand().unwrap();
+        let (name, add) = remote.subcommand().unwrap();
+        assert_eq!(name, "add");
+        assert_eq!(add.get_value::<bool>("fetch"), Some(true));
+        assert_eq!(parser.subcommand_path(), vec!["remote", "add"]);
+        assert_eq!(add.positionals(), ["origin"]);
+        assert!(remote.positionals().is_empty());
+    }
+
+    #[test]
+    fn version() {
+        let mut parser = Parser::from_iter(["tool", "build", "-version"]);
+        parser.set_version("1.2.0");
+        parser.add_subcommand("build", "compile the project");
+        parser.add_subcommand("lint", "check the project");
+        assert_eq!(parser.finalize(), Err(Error::VersionRequested));
+        assert_eq!(parser.version(), Some("1.2.0"));
+
+        let mut parser = Parser::from_iter(["tool", "lint", "-version"]);
+        parser.set_version("1.2.0");
+        parser
+            .add_subcommand("lint", "check the project")
+            .set_version("0.4.0");
+        assert_eq!(parser.finalize(), Err(Error::VersionRequested));
+        assert_eq!(parser.version(), Some("0.4.0"));
+
+        let mut parser = Parser::from_iter(["tool", "build", "-version"]);
+        parser.add_subcommand("build", "compile the project");
+        assert_eq!(
+            parser.finalize(),
+            Err(Error::UnknownFlag("version".to_string()))
+        );
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut parser = Parser::from_iter(["tool", "remote", "add", "origin"]);
+        parser.set_handler(|_| Err("no command given".into()));
+        let remote = parser.add_subcommand("remote", "manage remotes");
+        remote.set_handler(|remote| Err(format!("{} needs a command", remote.command).into()));
+        remote
+            .add_subcommand("add", "add a remote")
+            .set_handler(|add| match add.get_positional::<String>(0) {
+                Some(_) => Ok(()),
+                None => Err("missing name".into()),
+            });
+        assert_eq!(parser.dispatch(), Ok(()));
+
+        // Without a handler on the subcommand, the handler of its parent runs.
+        let mut parser = Parser::from_iter(["tool", "remote", "add"]);
+        let remote = parser.add_subcommand("remote", "manage remotes");
+        remote.set_handler(|remote| Err(format!("{} needs a command", remote.command).into()));
+        remote.add_subcommand("add", "add a remote");
+        assert_eq!(
+            parser.dispatch(),
+            Err(Error::CommandFailed(
+                "tool remote".to_string(),
+                "tool remote needs a command".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn hooks() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let mut parser = Parser::from_iter(["tool", "remote", "add"]);
+        parser
+            .add_subcommand("remote", "manage remotes")
+            .add_subcommand("add", "add a remote")
+            .set_handler(|_| Err("no name given".into()));
+
+        let before = Rc::clone(&calls);
+        parser.add_before_hook(move |command, _| {
+            before.borrow_mut().push(format!("before {}", command))
+        });
+        let after = Rc::clone(&calls);
+        parser.add_after_hook(move |command, _| {
+            after.borrow_mut().push(format!("after {}", command))
+        });
+
+        assert!(parser.dispatch().is_err());
+        assert_eq!(
+            *calls.borrow(),
+            vec!["before tool remote add", "after tool remote add"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "completions")]
+    fn unknown() {
+        let mut parser = Parser::from_iter(["tool", "biuld"]);
+        parser.add_subcommand("build", "compile the project");
+
+        let result = parser.finalize();
+        assert_eq!(
+            result,
+            Err(Error::UnknownSubcommand(
+                "biuld".to_string(),
+                Some("build".to_string())
+            ))
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "subcommand 'biuld' is not recognized, did you mean 'build'?"
+        );
+
+        let mut parser = Parser::from_iter(["tool", "deploy"]);
+        parser.add_subcommand("build", "compile the project");
+        let result = parser.finalize();
+        assert_eq!(
+            result,
+            Err(Error::UnknownSubcommand("deploy".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn hidden() {
+        let mut parser = Parser::from_iter(["tool", "gcc"]);
+        parser.add_subcommand("gc", "prune the internal caches");
+        parser.hide_subcommand("gc");
+
+        let result = parser.finalize();
+        assert_eq!(
+            result,
+            Err(Error::UnknownSubcommand("gcc".to_string(), None))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help() {
+        let mut parser = Parser::from_iter(["tool"]);
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.add_subcommand("clean", "remove build artifacts");
+        parser.add_subcommand("build", "compile the project");
+
+        assert_eq!(
+            parser.render_full_help(),
+            [
+                "Usage: tool [options...] <command>\n",
+                "\n",
+                "Options:\n",
+                "  -verbose (default: false)\n",
+                "      this is used to get verbose output\n",
+                "\n",
+                "Commands:\n",
+                "  build\n",
+                "      compile the project\n",
+                "  clean\n",
+                "      remove build artifacts\n",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn categories() {
+        let mut parser = Parser::from_iter(["tool"]);
+        parser.add_subcommand("help", "show help for a command");
+        parser.subcommand_category("Core commands");
+        parser.add_subcommand("test", "run the tests");
+        parser.add_subcommand("build", "compile the project");
+        parser.subcommand_category("Advanced");
+        parser.add_subcommand("vendor", "vendor all dependencies");
+
+        assert_eq!(
+            parser.render_full_help(),
+            [
+                "Usage: tool [options...] <command>\n",
+                "\n",
+                "Options:\n",
+                "\n",
+                "Commands:\n",
+                "  help\n",
+                "      show help for a command\n",
+                "\n",
+                "Core commands:\n",
+                "  build\n",
+                "      compile the project\n",
+                "  test\n",
+                "      run the tests\n",
+                "\n",
+                "Advanced:\n",
+                "  vendor\n",
+                "      vendor all dependencies\n",
+            ]
+            .concat()
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Suggests the closest known name for a mistyped one.
+
+/// Returns the candidate closest to a name, if any is close enough to be a likely typo.
+///
+/// A candidate is close enough when at most a third of the characters of the name, and at least
+/// one, need to be inserted, removed, replaced or swapped with a neighbour to reach it.
+pub(crate) fn closest<'c>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'c str>,
+) -> Option<&'c str> {
+    let max = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|&(d, _)| d <= max)
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Returns the edit distance between two strings, counting a swap of adjacent characters as a
+/// single edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // Rows of the distance matrix for the two previous and the current prefix of `a`.
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                curr[j] = curr[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut prev);
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn distances() {
+        assert_eq!(distance("build", "build"), 0);
+        assert_eq!(distance("biuld", "build"), 1);
+        assert_eq!(distance("buil", "build"), 1);
+        assert_eq!(distance("test", "build"), 5);
+        assert_eq!(distance("", "run"), 3);
+    }
+
+    #[test]
+    fn suggestions() {
+        let names = ["build", "bench", "clean"];
+        assert_eq!(closest("biuld", names), Some("build"));
+        assert_eq!(closest("clen", names), Some("clean"));
+        assert_eq!(closest("deploy", names), None);
+    }
+}
@@ -0,0 +1,252 @@
+//! Helpers to run a configured parser against literal command lines and assert on the outcome.
+//!
+//! # Examples
+//!
+//! ```
+//! use yafp::testing;
+//! use yafp::errors::Error;
+//!
+//! let define = |parser: &mut yafp::Parser| {
+//!     parser.bool_flag("verbose", "this is used to get verbose output");
+//!     parser.required_flag("num", "this is used to set a numeric value");
+//! };
+//!
+//! testing::run("head -verbose -num 3 file.txt", define)
+//!     .assert_value("verbose", true)
+//!     .assert_value("num", 3)
+//!     .assert_remaining(&["file.txt"]);
+//!
+//! testing::run("head file.txt", define).assert_error(&Error::MissingArgument("num".to_string()));
+//! testing::run("head -help", define).assert_help_requested();
+//! ```
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::errors::{Error, Result};
+use crate::Parser;
+
+/// The outcome of running a parser against a command line.
+#[derive(Debug)]
+pub struct Outcome {
+    parser: Parser<'static>,
+    result: Result<Vec<String>>,
+}
+
+/// Splits a command line with [`crate::split::split`], defines flags on a parser for it and parses
+/// it.
+///
+/// # Panics
+///
+/// Panics if the command line has an unterminated quote.
+pub fn run(line: &str, define: impl FnOnce(&mut Parser)) -> Outcome {
+    let parser = match Parser::parse_str(line) {
+        Ok(parser) => parser,
+        Err(e) => panic!("invalid command line {:?}: {}", line, e),
+    };
+    run_parser(parser, define)
+}
+
+/// Defines flags on a parser for the given arguments and parses them.
+pub fn run_args(args: &[&str], define: impl FnOnce(&mut Parser)) -> Outcome {
+    let parser = Parser::from_iter(args.iter().map(|arg| arg.to_string()));
+    run_parser(parser, define)
+}
+
+fn run_parser(mut parser: Parser<'static>, define: impl FnOnce(&mut Parser)) -> Outcome {
+    define(&mut parser);
+    let result = parser
+        .finalize()
+        .map(|remaining| remaining.into_iter().map(|arg| arg.into_owned()).collect());
+    Outcome { parser, result }
+}
+
+impl Outcome {
+    /// Returns the parser after parsing, to access values or render help.
+    pub fn parser(&self) -> &Parser<'static> {
+        &self.parser
+    }
+
+    /// Returns the parse result with the remaining arguments.
+    pub fn result(&self) -> &Result<Vec<String>> {
+        &self.result
+    }
+
+    /// Asserts that parsing succeeded and the flag has the expected value.
+    #[track_caller]
+    pub fn assert_value<T>(&self, flag: &str, expected: T) -> &Self
+    where
+        T: FromStr + PartialEq + Debug,
+        <T as FromStr>::Err: std::fmt::Display,
+    {
+        self.assert_ok();
+        assert_eq!(
+            self.parser.get_value::<T>(flag),
+            Some(expected),
+            "unexpected value for flag '{}'",
+            flag
+        );
+        self
+    }
+
+    /// Asserts that parsing succeeded and the flag has no value.
+    #[track_caller]
+    pub fn assert_unset(&self, flag: &str) -> &Self {
+        self.assert_ok();
+        assert_eq!(
+            self.parser.get_value::<String>(flag),
+            None,
+            "expected flag '{}' to have no value",
+            flag
+        );
+        self
+    }
+
+    /// Asserts that parsing succeeded with the expected remaining arguments.
+    #[track_caller]
+    pub fn assert_remaining(&self, expected: &[&str]) -> &Self {
+        assert_eq!(self.assert_ok(), expected, "unexpected remaining arguments");
+        self
+    }
+
+    /// Asserts that parsing failed with the expected error.
+    #[track_caller]
+    pub fn assert_error(&self, expected: &Error) -> &Self {
+        match &self.result {
+            Ok(remaining) => panic!(
+                "expected error '{}' but parsing succeeded with {:?}",
+                expected, remaining
+            ),
+            Err(e) => assert_eq!(e, expected, "unexpected error"),
+        }
+        self
+    }
+
+    /// Asserts that help was requested through `-help`.
+    #[track_caller]
+    pub fn assert_help_requested(&self) -> &Self {
+        self.assert_error(&Error::HelpRequested)
+    }
+
+    /// Asserts that the rendered help text matches the expected text.
+    #[cfg(feature = "help")]
+    #[track_caller]
+    pub fn assert_help(&self, expected: &str) -> &Self {
+        assert_eq!(self.parser.help(), expected, "unexpected help text");
+        self
+    }
+
+    #[track_caller]
+    fn assert_ok(&self) -> &Vec<String> {
+        match &self.result {
+            Ok(remaining) => remaining,
+            Err(e) => panic!("expected parsing to succeed but got error '{}'", e),
+        }
+    }
+}
+
+/// Name of the environment variable which, when set, makes [`assert_snapshot`] update snapshot
+/// files instead of comparing against them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "YAFP_UPDATE_SNAPSHOTS";
+
+/// Asserts that the text matches the snapshot stored at the given path.
+///
+/// If the snapshot file does not exist, or the `YAFP_UPDATE_SNAPSHOTS` environment variable is
+/// set, the snapshot is written instead. Combined with [`crate::Parser::render_full_help`] this
+/// catches help regressions in CI.
+///
+/// # Panics
+///
+/// Panics if the text differs from the snapshot or the snapshot cannot be read or written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use yafp::Parser;
+/// use yafp::testing::assert_snapshot;
+///
+/// let mut parser = Parser::from_iter(["head"]);
+/// parser.bool_flag("verbose", "this is used to get verbose output");
+///
+/// # #[cfg(feature = "help")]
+/// assert_snapshot(&parser.render_full_help(), "tests/snapshots/help.txt");
+/// ```
+#[track_caller]
+pub fn assert_snapshot(actual: &str, path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() || !path.exists() {
+        if let Some(dir) = path.parent() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                panic!(
+                    "failed to create snapshot directory {}: {}",
+                    dir.display(),
+                    e
+                );
+            }
+        }
+        if let Err(e) = fs::write(path, actual) {
+            panic!("failed to write snapshot {}: {}", path.display(), e);
+        }
+        return;
+    }
+
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(e) => panic!("failed to read snapshot {}: {}", path.display(), e),
+    };
+    assert_eq!(
+        actual,
+        expected,
+        "snapshot {} does not match, set {} to update it",
+        path.display(),
+        UPDATE_SNAPSHOTS_ENV
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn define(parser: &mut Parser) {
+        parser.optional_flag("opt", "this is an optional flag");
+    }
+
+    #[test]
+    fn outcomes() {
+        run("head 'my file.txt'", define)
+            .assert_unset("opt")
+            .assert_remaining(&["my file.txt"]);
+        run_args(&["head", "-opt"], define).assert_error(&Error::MissingValue("opt".to_string()));
+        run("head -nope", define).assert_error(&Error::UnknownFlag("nope".to_string()));
+        run("head -help", define).assert_help_requested();
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help() {
+        run("head", define)
+            .assert_help("Usage: head [options...]\n  -opt value\n\tthis is an optional flag\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected parsing to succeed")]
+    fn failed_assertion() {
+        run("head -opt", define).assert_remaining(&[]);
+    }
+
+    #[test]
+    fn snapshot() {
+        let path = std::env::temp_dir().join(format!("yafp-snapshot-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        // A missing snapshot is written and then matches.
+        assert_snapshot("Usage: head\n", &path);
+        assert_snapshot("Usage: head\n", &path);
+
+        let result = std::panic::catch_unwind(|| assert_snapshot("Usage: tail\n", &path));
+        let _ = fs::remove_file(&path);
+        assert!(result.is_err());
+    }
+}
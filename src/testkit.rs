@@ -0,0 +1,2 @@
+//! Helpers for downstream crates testing their command line handling.
+pub mod strategies;
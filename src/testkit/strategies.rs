@@ -0,0 +1,235 @@
+//! Proptest strategies generating command lines for a parser definition.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use yafp::Parser;
+//! use yafp::testkit::strategies::valid_args;
+//!
+//! fn define(parser: &mut Parser) {
+//!     parser.bool_flag("verbose", "this is used to get verbose output");
+//!     parser.required_flag("num", "this is used to set a numeric value");
+//! }
+//!
+//! let mut definition = Parser::from_iter(["head"]);
+//! define(&mut definition);
+//!
+//! proptest!(|(args in valid_args(&definition))| {
+//!     let mut parser = Parser::from_iter(&args);
+//!     define(&mut parser);
+//!     prop_assert!(parser.finalize().is_ok());
+//! });
+//! ```
+use proptest::prelude::*;
+
+use crate::{Flag, Parser};
+
+/// The definition of a declared flag needed to generate command lines.
+#[derive(Debug, Clone)]
+struct FlagDef {
+    name: String,
+    typ: Flag,
+    required: bool,
+    /// The values accepted by the flag, any generated token if empty.
+    choices: Vec<String>,
+}
+
+fn definition(parser: &Parser<'_>) -> Vec<FlagDef> {
+    parser
+        .flags
+        .iter()
+        .map(|(name, entry)| FlagDef {
+            name: name.to_string(),
+            typ: entry.typ,
+            required: parser.required.contains(name),
+            choices: entry.choices.clone(),
+        })
+        .collect()
+}
+
+/// Returns whether positional arguments can be added to a command line, which is not the case
+/// when they are denied or the first one would be read as a subcommand name.
+fn takes_positionals(parser: &Parser<'_>) -> bool {
+    !parser.deny_positionals && !parser.has_subcommands()
+}
+
+/// Generates flag values and positional arguments, which never start with a dash.
+fn token() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9_.]{1,8}"
+}
+
+/// Generates the values of a flag, drawn from its choices if it has any.
+fn value(def: &FlagDef) -> BoxedStrategy<String> {
+    if def.choices.is_empty() {
+        token().boxed()
+    } else {
+        proptest::sample::select(def.choices.clone()).boxed()
+    }
+}
+
+/// Generates the tokens setting a flag, which may be empty when the flag is not required.
+fn flag_tokens(def: FlagDef) -> BoxedStrategy<Vec<String>> {
+    let flag = format!("-{}", def.name);
+    match (def.typ, def.required) {
+        (Flag::Bool, _) => any::<bool>()
+            .prop_map(move |set| if set { vec![flag.clone()] } else { vec![] })
+            .boxed(),
+        (Flag::TriState, _) => proptest::option::of(any::<bool>())
+            .prop_map(move |set| match set {
+                Some(true) => vec![flag.clone()],
+                Some(false) => vec![format!("-no-{}", &flag[1..])],
+                None => vec![],
+            })
+            .boxed(),
+        (Flag::Count, _) => (0..3usize)
+            .prop_map(move |count| vec![flag.clone(); count])
+            .boxed(),
+        (Flag::Value, true) => value(&def).prop_map(move |v| vec![flag.clone(), v]).boxed(),
+        (Flag::Value, false) => proptest::option::of(value(&def))
+            .prop_map(move |v| match v {
+                Some(v) => vec![flag.clone(), v],
+                None => vec![],
+            })
+            .boxed(),
+    }
+}
+
+/// Joins the command with groups of tokens in a shuffled order, along with one to three
+/// positional arguments if `positionals` is set.
+fn command_line(
+    command: String,
+    groups: Vec<BoxedStrategy<Vec<String>>>,
+    positionals: bool,
+) -> BoxedStrategy<Vec<String>> {
+    let count = if positionals { 1..4 } else { 0..1 };
+    (groups, proptest::collection::vec(token(), count))
+        .prop_flat_map(|(mut groups, positionals)| {
+            groups.extend(positionals.into_iter().map(|p| vec![p]));
+            Just(groups).prop_shuffle()
+        })
+        .prop_map(move |groups| {
+            let mut args = vec![command.clone()];
+            args.extend(groups.into_iter().flatten());
+            args
+        })
+        .boxed()
+}
+
+/// Generates command lines which the given parser accepts.
+///
+/// Every required flag is given a value, optional and boolean flags are set at random and at
+/// least one positional argument is included, all in a shuffled order. Values are drawn from the
+/// choices of a flag if it has any, such as with [`crate::FlagBuilder::value_enum`]. No
+/// positional argument is included when they are denied with [`crate::Parser::deny_positionals`]
+/// or the parser has subcommands, which are not generated.
+///
+/// Other definitions are not taken into account, so lines may be rejected by a parser which
+/// checks values with parsers or validators such as [`crate::FlagBuilder::integer`], declares
+/// positionals with [`crate::Parser::positional`], or relates flags to each other such as with
+/// [`crate::FlagBuilder::required_if`] or occurrence counts.
+pub fn valid_args(parser: &Parser<'_>) -> BoxedStrategy<Vec<String>> {
+    let groups = definition(parser).into_iter().map(flag_tokens).collect();
+    command_line(
+        parser.command.to_string(),
+        groups,
+        takes_positionals(parser),
+    )
+}
+
+/// Generates command lines which the given parser rejects with an error.
+///
+/// Lines either omit a required flag or end with a value flag missing its value. Returns `None`
+/// if the parser has no required or value flags, since every command line is then accepted.
+pub fn invalid_args(parser: &Parser<'_>) -> Option<BoxedStrategy<Vec<String>>> {
+    let defs = definition(parser);
+    let command = parser.command.to_string();
+    let positionals = takes_positionals(parser);
+
+    let mut strategies: Vec<BoxedStrategy<Vec<String>>> = Vec::new();
+    for (i, def) in defs.iter().enumerate() {
+        if def.required {
+            // Omit this required flag.
+            let groups = defs
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, def)| flag_tokens(def.clone()))
+                .collect();
+            strategies.push(command_line(command.clone(), groups, positionals));
+        }
+        if let Flag::Value = def.typ {
+            // End with this flag but no value.
+            let groups = defs.iter().cloned().map(flag_tokens).collect();
+            let flag = format!("-{}", def.name);
+            strategies.push(
+                command_line(command.clone(), groups, positionals)
+                    .prop_map(move |mut args| {
+                        args.push(flag.clone());
+                        args
+                    })
+                    .boxed(),
+            );
+        }
+    }
+
+    if strategies.is_empty() {
+        return None;
+    }
+    Some(proptest::strategy::Union::new(strategies).boxed())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn define(parser: &mut Parser) {
+        parser.bool_flag("verbose", "this is used to get verbose output");
+        parser.required_flag("num", "this is used to set a numeric value");
+        parser.optional_flag("opt", "this is an optional flag");
+    }
+
+    fn definition() -> Parser<'static> {
+        let mut parser = Parser::from_iter(["head"]);
+        define(&mut parser);
+        parser
+    }
+
+    fn define_choices(parser: &mut Parser) {
+        parser.deny_positionals();
+        parser
+            .required_flag("level", "this is used to set the level")
+            .choices(&["low", "high"]);
+        parser.add_subcommand("build", "compile the project");
+    }
+
+    fn definition_choices() -> Parser<'static> {
+        let mut parser = Parser::from_iter(["tool"]);
+        define_choices(&mut parser);
+        parser
+    }
+
+    proptest! {
+        #[test]
+        fn valid(args in valid_args(&definition())) {
+            let mut parser = Parser::from_iter(&args);
+            define(&mut parser);
+            prop_assert!(parser.finalize().is_ok());
+        }
+
+        #[test]
+        fn valid_choices(args in valid_args(&definition_choices())) {
+            let mut parser = Parser::from_iter(&args);
+            define_choices(&mut parser);
+            prop_assert!(parser.finalize().is_ok());
+        }
+
+        #[test]
+        fn invalid(args in invalid_args(&definition()).unwrap()) {
+            let mut parser = Parser::from_iter(&args);
+            define(&mut parser);
+            prop_assert!(parser.finalize().is_err());
+        }
+    }
+}
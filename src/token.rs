@@ -0,0 +1,125 @@
+//! Decides which tokens are flags and which are positional arguments, see
+//! [`crate::Parser::set_classifier`].
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use crate::Parser;
+
+/// The kind of a token, as decided by a [`TokenClassifier`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token<'t> {
+    /// A flag with its name as declared, which takes the next token as its value if it is not
+    /// a boolean flag.
+    Flag(Cow<'t, str>),
+
+    /// A positional argument.
+    Positional,
+}
+
+/// Classifies each token the parser reads which is not the value of a flag.
+///
+/// # Examples
+///
+/// Treat `+name` as setting and `-name` as unsetting a tri-state flag.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use yafp::Parser;
+/// use yafp::errors::Error;
+/// use yafp::token::{Token, TokenClassifier};
+///
+/// struct Toggles;
+///
+/// impl TokenClassifier for Toggles {
+///     fn classify<'t>(&self, token: &'t str) -> Token<'t> {
+///         if let Some(name) = token.strip_prefix('+') {
+///             Token::Flag(Cow::Borrowed(name))
+///         } else if let Some(name) = token.strip_prefix('-') {
+///             Token::Flag(Cow::Owned(format!("no-{}", name)))
+///         } else {
+///             Token::Positional
+///         }
+///     }
+/// }
+///
+/// let mut parser = Parser::from_iter(["set", "+xtrace", "-errexit"]);
+/// parser.set_classifier(Toggles);
+/// parser.tristate_flag("xtrace", "this is used to print commands");
+/// parser.tristate_flag("errexit", "this is used to exit on errors");
+///
+/// parser.finalize()?;
+/// assert_eq!(Some(true), parser.get_value("xtrace"));
+/// assert_eq!(Some(false), parser.get_value("errexit"));
+/// # Ok::<(), Error>(())
+/// ```
+pub trait TokenClassifier {
+    /// Returns the kind of a token.
+    fn classify<'t>(&self, token: &'t str) -> Token<'t>;
+}
+
+/// The default classifier, taking tokens starting with `-` as flags named by the rest.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DashPrefix;
+
+impl TokenClassifier for DashPrefix {
+    fn classify<'t>(&self, token: &'t str) -> Token<'t> {
+        match token.strip_prefix('-') {
+            Some(name) => Token::Flag(Cow::Borrowed(name)),
+            None => Token::Positional,
+        }
+    }
+}
+
+impl Parser<'_> {
+    /// Sets how tokens are classified into flags and positional arguments, which defaults to
+    /// [`DashPrefix`]. Subcommands without a classifier of their own inherit it.
+    pub fn set_classifier(&mut self, classifier: impl TokenClassifier + 'static) {
+        self.classifier = Some(Rc::new(classifier));
+    }
+
+    /// Returns the kind of a token according to the classifier set.
+    pub(crate) fn classify<'t>(&self, token: &'t str) -> Token<'t> {
+        match &self.classifier {
+            Some(classifier) => classifier.classify(token),
+            None => DashPrefix.classify(token),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn dash_prefix() {
+        assert_eq!(
+            DashPrefix.classify("-num"),
+            Token::Flag(Cow::Borrowed("num"))
+        );
+        assert_eq!(DashPrefix.classify("file.txt"), Token::Positional);
+    }
+
+    #[test]
+    fn bare_words() {
+        struct BareWords;
+
+        impl TokenClassifier for BareWords {
+            fn classify<'t>(&self, token: &'t str) -> Token<'t> {
+                match token.split_once('=') {
+                    Some(_) => Token::Positional,
+                    None => Token::Flag(Cow::Borrowed(token)),
+                }
+            }
+        }
+
+        let mut parser = Parser::from_iter(["dd", "status", "progress", "if=in.img"]);
+        parser.set_classifier(BareWords);
+        parser.optional_flag("status", "this is used to set the status output");
+        assert_eq!(parser.finalize().unwrap(), vec!["if=in.img"]);
+        assert_eq!(
+            parser.get_value::<String>("status").as_deref(),
+            Some("progress")
+        );
+    }
+}
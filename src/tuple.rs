@@ -0,0 +1,143 @@
+//! Allows tuples to be read from flags with a fixed number of values, see
+//! [`crate::FlagBuilder::tuple`].
+use std::str::FromStr;
+
+use crate::{Parser, LIST_SEPARATOR};
+
+/// A tuple whose items are parsed from the values of a flag declared with
+/// [`crate::FlagBuilder::tuple`], implemented for tuples of two to four items.
+pub trait TupleValue: Sized {
+    /// The number of items.
+    const ARITY: usize;
+
+    /// Parses each item from its value, returning `None` if the count does not match or any item
+    /// fails to parse.
+    fn from_values(values: &[&str]) -> Option<Self>;
+}
+
+macro_rules! tuple_value {
+    ($arity:literal => $($item:ident),+) => {
+        impl<$($item: FromStr),+> TupleValue for ($($item,)+) {
+            const ARITY: usize = $arity;
+
+            fn from_values(values: &[&str]) -> Option<Self> {
+                let mut values = values.iter();
+                let tuple = ($($item::from_str(values.next()?).ok()?,)+);
+                match values.next() {
+                    Some(_) => None,
+                    None => Some(tuple),
+                }
+            }
+        }
+    };
+}
+
+tuple_value!(2 => A, B);
+tuple_value!(3 => A, B, C);
+tuple_value!(4 => A, B, C, D);
+
+/// Checks that a value holds the given number of items separated by a delimiter.
+pub(crate) fn check(value: &str, arity: usize, delimiter: char) -> Result<String, String> {
+    if value.split(delimiter).count() == arity {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "expected {} values separated by '{}'",
+            arity, delimiter
+        ))
+    }
+}
+
+impl Parser<'_> {
+    /// Returns the value of a flag declared with [`crate::FlagBuilder::tuple`] as a tuple, or the
+    /// last value of a flag taking multiple values.
+    ///
+    /// Returns `None` if the flag is not set, is not a tuple flag with as many items as the
+    /// tuple, or any item fails to parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    ///
+    /// let mut parser = Parser::from_iter(["convert", "-resize", "800x600", "-offset", "10", "20"]);
+    /// parser
+    ///     .optional_flag("resize", "this is used to set the size")
+    ///     .tuple(2, 'x');
+    /// parser
+    ///     .optional_flag("offset", "this is used to set the offset")
+    ///     .tuple(2, ',');
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Some((800, 600)), parser.get_tuple::<(u32, u32)>("resize"));
+    /// assert_eq!(Some((10, 20)), parser.get_tuple::<(i32, i32)>("offset"));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn get_tuple<T: TupleValue>(&self, flag: &str) -> Option<T> {
+        let entry = self.entry(flag)?;
+        let (_, delimiter) = entry.tuple.filter(|(arity, _)| *arity == T::ARITY)?;
+        let value = entry.value.as_deref()?;
+        let value = if entry.multiple {
+            value.rsplit(LIST_SEPARATOR).next()?
+        } else {
+            value
+        };
+        T::from_values(&value.split(delimiter).collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn from_values() {
+        assert_eq!(<(u8, u8)>::from_values(&["1", "2"]), Some((1, 2)));
+        assert_eq!(
+            <(f64, f64, String)>::from_values(&["1.5", "2", "z"]),
+            Some((1.5, 2.0, "z".to_string()))
+        );
+        assert_eq!(<(u8, u8)>::from_values(&["1", "2", "3"]), None);
+        assert_eq!(<(u8, u8)>::from_values(&["1"]), None);
+        assert_eq!(<(u8, u8)>::from_values(&["1", "x"]), None);
+    }
+
+    #[test]
+    fn tokens() {
+        let args = [
+            "convert", "-box", "1", "2", "3", "4", "-size", "1x2x3", "in.png",
+        ];
+        let mut parser = Parser::from_iter(args);
+        parser
+            .optional_flag("box", "this is used to set the box")
+            .tuple(4, ',');
+        parser
+            .optional_flag("size", "this is used to set the size")
+            .tuple(2, 'x');
+        assert!(matches!(
+            parser.finalize(),
+            Err(crate::errors::Error::InvalidValue(flag, _)) if flag == "size"
+        ));
+
+        let mut parser = Parser::from_iter(["convert", "-box", "1", "2", "3", "4", "in.png"]);
+        parser
+            .optional_flag("box", "this is used to set the box")
+            .tuple(4, ',');
+        assert_eq!(parser.finalize().unwrap(), vec!["in.png"]);
+        assert_eq!(
+            parser.get_tuple::<(u8, u8, u8, u8)>("box"),
+            Some((1, 2, 3, 4))
+        );
+
+        let mut parser = Parser::from_iter(["convert", "-box", "1", "2"]);
+        parser
+            .optional_flag("box", "this is used to set the box")
+            .tuple(4, ',');
+        assert_eq!(
+            parser.finalize(),
+            Err(crate::errors::Error::MissingValue("box".to_string()))
+        );
+    }
+}
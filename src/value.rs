@@ -0,0 +1,698 @@
+//! Defines the checks and conversions applied to flag values during [`crate::Parser::finalize`].
+use std::fmt::Display;
+use std::net::{IpAddr, SocketAddr};
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{DecimalMark, SizeUnits};
+
+/// A closure checking a value and returning it, possibly converted, or the reason it is invalid.
+///
+/// Reasons must not repeat the value since it may belong to a sensitive flag.
+pub(crate) type ValueParser = Box<dyn Fn(&str) -> Result<String, String>>;
+
+/// A closure decoding a value into the bytes returned by [`crate::Parser::get_bytes`], or the
+/// reason it is invalid.
+pub(crate) type ByteDecoder = Box<dyn Fn(&str) -> Result<Vec<u8>, String>>;
+
+/// A closure rewriting a value into its canonical form before it is checked.
+pub(crate) type Normalizer = Box<dyn Fn(&str) -> String>;
+
+/// Removes trailing slashes from a value, keeping a lone `/`.
+pub(crate) fn strip_trailing_slash(value: &str) -> String {
+    match value.trim_end_matches('/') {
+        "" if value.starts_with('/') => "/".to_string(),
+        stripped => stripped.to_string(),
+    }
+}
+
+/// Checks that a path exists on the filesystem.
+pub(crate) fn path_exists(value: &str) -> Result<String, String> {
+    match Path::new(value).try_exists() {
+        Ok(true) => Ok(value.to_string()),
+        Ok(false) => Err("path does not exist".to_string()),
+        Err(e) => Err(format!("path could not be checked: {}", e)),
+    }
+}
+
+/// Checks that a path exists and is a directory.
+pub(crate) fn path_is_dir(value: &str) -> Result<String, String> {
+    path_exists(value)?;
+    if Path::new(value).is_dir() {
+        Ok(value.to_string())
+    } else {
+        Err("path is not a directory".to_string())
+    }
+}
+
+/// Checks that a path exists and is a regular file.
+pub(crate) fn path_is_file(value: &str) -> Result<String, String> {
+    path_exists(value)?;
+    if Path::new(value).is_file() {
+        Ok(value.to_string())
+    } else {
+        Err("path is not a file".to_string())
+    }
+}
+
+/// Expands a leading `~` to the home directory and `$VAR` or `${VAR}` to the value of the
+/// environment variable, looked up with `env`.
+pub(crate) fn expand_path(
+    value: &str,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    if rest == "~" || rest.starts_with("~/") || (cfg!(windows) && rest.starts_with("~\\")) {
+        let home = env("HOME")
+            .or_else(|| env("USERPROFILE"))
+            .ok_or_else(|| "home directory is not set".to_string())?;
+        expanded.push_str(&home);
+        rest = &rest[1..];
+    }
+
+    while let Some(i) = rest.find('$') {
+        expanded.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+
+        let (name, len) = match rest.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => return Err("unterminated variable reference".to_string()),
+            },
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..end], end)
+            }
+        };
+
+        // A dollar sign not followed by a name is kept as is.
+        if name.is_empty() && len == 0 {
+            expanded.push('$');
+            continue;
+        }
+        match env(name) {
+            Some(var) => expanded.push_str(&var),
+            None => return Err(format!("environment variable '{}' is not set", name)),
+        }
+        rest = &rest[len..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Checks that a value is one of the given choices.
+pub(crate) fn choice(value: &str, choices: &[String]) -> Result<String, String> {
+    if choices.iter().any(|c| c == value) {
+        Ok(value.to_string())
+    } else {
+        Err(format!("expected one of: {}", choices.join(", ")))
+    }
+}
+
+/// Checks that a value is a list of names separated by `|` or `,`, returning the combined bits of
+/// the named flags.
+pub(crate) fn bitflags(value: &str, flags: &[(String, u64)]) -> Result<String, String> {
+    let mut bits = 0;
+    for name in value.split(['|', ',']).map(str::trim) {
+        match flags.iter().find(|(flag, _)| flag == name) {
+            Some((_, flag_bits)) => bits |= flag_bits,
+            None if name.is_empty() => return Err("empty name in list".to_string()),
+            None => {
+                let names: Vec<&str> = flags.iter().map(|(flag, _)| flag.as_str()).collect();
+                return Err(format!(
+                    "unknown name, expected any of: {}",
+                    names.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(bits.to_string())
+}
+
+/// Checks that a value is a boolean written as `1`, `0`, `true`, `false`, `yes`, `no`, `on` or
+/// `off` in any case, returning it as `true` or `false`.
+pub(crate) fn boolean(value: &str) -> Result<String, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok("true".to_string()),
+        "0" | "false" | "no" | "off" => Ok("false".to_string()),
+        _ => Err("expected one of: 1, 0, true, false, yes, no, on, off".to_string()),
+    }
+}
+
+/// Checks that a value is an integer, written in decimal or with a `0x`, `0o` or `0b` prefix,
+/// returning it in decimal.
+pub(crate) fn integer(value: &str) -> Result<String, String> {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        _ => (10, digits),
+    };
+
+    // Leave signs to the sign prefix, which from_str_radix would otherwise accept again.
+    if digits.starts_with(['+', '-']) {
+        return Err("not a valid integer".to_string());
+    }
+    match u128::from_str_radix(digits, radix) {
+        Ok(0) => Ok("0".to_string()),
+        Ok(n) => Ok(format!("{}{}", sign, n)),
+        Err(e) if *e.kind() == std::num::IntErrorKind::PosOverflow => {
+            Err("integer is too large".to_string())
+        }
+        Err(_) => Err("not a valid integer".to_string()),
+    }
+}
+
+/// Rewrites a number written with a decimal mark and digit grouping, such as `1.234,56` or
+/// `1_000_000`, into the form read by [`std::str::FromStr`], checking that digits are grouped by
+/// three.
+pub(crate) fn localized_number(value: &str, mark: DecimalMark) -> Result<String, String> {
+    let (decimal, group) = match mark {
+        DecimalMark::Point => ('.', ','),
+        DecimalMark::Comma => (',', '.'),
+    };
+    let is_separator =
+        |c: char| c == group || matches!(c, '_' | '\'' | ' ' | '\u{a0}' | '\u{202f}');
+    let (sign, digits) = match value.strip_prefix(['-', '+']) {
+        Some(digits) => (&value[..1], digits),
+        None => ("", value),
+    };
+    let (integer, fraction) = match digits.split_once(decimal) {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (digits, None),
+    };
+    if fraction.is_some_and(|fraction| fraction.contains(|c| c == decimal || is_separator(c))) {
+        return Err("only the integer part of a number may be grouped".to_string());
+    }
+
+    let groups: Vec<&str> = integer.split(is_separator).collect();
+    if let [first, rest @ ..] = &groups[..] {
+        let digits = |group: &str| group.chars().all(|c| c.is_ascii_digit());
+        let grouped = (1..=3).contains(&first.len())
+            && digits(first)
+            && rest.iter().all(|group| group.len() == 3 && digits(group));
+        if !rest.is_empty() && !grouped {
+            return Err("digits must be grouped by three".to_string());
+        }
+    }
+    let mut number = format!("{}{}", sign, groups.concat());
+    if let Some(fraction) = fraction {
+        number.push('.');
+        number.push_str(fraction);
+    }
+    Ok(number)
+}
+
+/// Checks that a value parses as `T` and lies within a range.
+pub(crate) fn in_range<T, R>(value: &str, range: &R) -> Result<String, String>
+where
+    T: FromStr + PartialOrd + Display,
+    R: RangeBounds<T>,
+{
+    let parsed: T = value.parse().map_err(|_| "not a valid value".to_string())?;
+    if range.contains(&parsed) {
+        return Ok(value.to_string());
+    }
+    let reason = match (range.start_bound(), range.end_bound()) {
+        (Bound::Included(min), Bound::Included(max)) => format!("between {} and {}", min, max),
+        (Bound::Included(min), Bound::Excluded(max)) => {
+            format!("at least {} and below {}", min, max)
+        }
+        (Bound::Included(min), Bound::Unbounded) => format!("at least {}", min),
+        (Bound::Excluded(min), Bound::Unbounded) => format!("above {}", min),
+        (Bound::Unbounded, Bound::Included(max)) => format!("at most {}", max),
+        (Bound::Unbounded, Bound::Excluded(max)) => format!("below {}", max),
+        (Bound::Excluded(min), Bound::Included(max)) => {
+            format!("above {} and at most {}", min, max)
+        }
+        (Bound::Excluded(min), Bound::Excluded(max)) => format!("above {} and below {}", min, max),
+        (Bound::Unbounded, Bound::Unbounded) => {
+            unreachable!("an unbounded range contains any value")
+        }
+    };
+    Err(format!("expected a value {}", reason))
+}
+
+/// Checks that a value is a byte size such as `512`, `4k`, `10MiB` or `2G`, returning the number of
+/// bytes.
+///
+/// Prefixes with an `i`, such as `KiB`, are always powers of 1024 while the rest follow the given
+/// units.
+pub(crate) fn byte_size(value: &str, units: SizeUnits) -> Result<String, String> {
+    let value = value.trim();
+    let end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(end);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| "not a valid byte size".to_string())?;
+
+    let suffix = suffix.trim_start().to_ascii_lowercase();
+    let suffix = suffix.strip_suffix('b').unwrap_or(&suffix);
+    let (prefix, base) = match suffix.strip_suffix('i') {
+        Some(prefix) if !prefix.is_empty() => (prefix, 1024u64),
+        _ if units == SizeUnits::Iec => (suffix, 1024),
+        _ => (suffix, 1000),
+    };
+    let exponent = match prefix {
+        "" => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        "p" => 5,
+        "e" => 6,
+        _ => return Err("byte size has an unknown unit".to_string()),
+    };
+
+    base.checked_pow(exponent)
+        .and_then(|multiplier| number.checked_mul(multiplier))
+        .map(|bytes| bytes.to_string())
+        .ok_or_else(|| "byte size is too large".to_string())
+}
+
+/// Reads the contents of the file at the path given by a value.
+pub(crate) fn file_contents(value: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(value).map_err(|e| format!("file could not be read: {}", e))
+}
+
+/// Decodes standard or URL-safe base64, with or without padding.
+pub(crate) fn base64(value: &str) -> Result<Vec<u8>, String> {
+    let trimmed = value.trim_end_matches('=');
+    if value.len() - trimmed.len() > 2 || trimmed.len() % 4 == 1 {
+        return Err("not valid base64: invalid length".to_string());
+    }
+
+    let mut bytes = Vec::with_capacity(trimmed.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in trimmed.bytes() {
+        let sextet = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err("not valid base64: invalid character".to_string()),
+        };
+        buffer = buffer << 6 | u32::from(sextet);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Checks that a value is an IPv4 or IPv6 address.
+pub(crate) fn ip_addr(value: &str) -> Result<String, String> {
+    match value.parse::<IpAddr>() {
+        Ok(_) => Ok(value.to_string()),
+        Err(_) => Err("not a valid IP address".to_string()),
+    }
+}
+
+/// Checks that a value is an address with a port, or an address alone if a default port is given
+/// which is then appended.
+pub(crate) fn socket_addr(value: &str, default_port: Option<u16>) -> Result<String, String> {
+    if value.parse::<SocketAddr>().is_ok() {
+        return Ok(value.to_string());
+    }
+    match (default_port, value.parse::<IpAddr>()) {
+        (Some(port), Ok(ip)) => Ok(SocketAddr::new(ip, port).to_string()),
+        (None, Ok(_)) => Err("address is missing a port".to_string()),
+        _ => Err("not a valid socket address".to_string()),
+    }
+}
+
+/// Checks that a value is an absolute URL with a host, and if any schemes are given that it uses
+/// one of them.
+#[cfg(feature = "url")]
+pub(crate) fn url(value: &str, schemes: &[String]) -> Result<String, String> {
+    let url = url::Url::parse(value).map_err(|e| format!("not a valid URL: {}", e))?;
+    if !url.has_host() {
+        return Err("URL is missing a host".to_string());
+    }
+    if !schemes.is_empty() && !schemes.iter().any(|s| s == url.scheme()) {
+        return Err(format!(
+            "URL scheme '{}' is not allowed, expected one of: {}",
+            url.scheme(),
+            schemes.join(", ")
+        ));
+    }
+    Ok(value.to_string())
+}
+
+/// Checks that a value is a semantic version such as `1.2.3`.
+#[cfg(feature = "semver")]
+pub(crate) fn semver(value: &str) -> Result<String, String> {
+    match semver::Version::parse(value) {
+        Ok(_) => Ok(value.to_string()),
+        Err(e) => Err(format!("not a valid version: {}", e)),
+    }
+}
+
+/// Checks that a value is an RFC 3339 timestamp or a `YYYY-MM-DD` date, which is taken as midnight
+/// UTC, returning it as an RFC 3339 timestamp.
+#[cfg(feature = "chrono")]
+pub(crate) fn datetime(value: &str) -> Result<String, String> {
+    use chrono::{DateTime, NaiveDate, SecondsFormat};
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true));
+    }
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => Ok(date
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+        Err(_) => Err("not a valid RFC 3339 timestamp or YYYY-MM-DD date".to_string()),
+    }
+}
+
+/// Checks that a value is a `YYYY-MM-DD` date.
+#[cfg(feature = "chrono")]
+pub(crate) fn date(value: &str) -> Result<String, String> {
+    match chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(_) => Ok(value.to_string()),
+        Err(_) => Err("not a valid YYYY-MM-DD date".to_string()),
+    }
+}
+
+/// Checks that a value is a regular expression which compiles.
+///
+/// The reason only names the kind of syntax error, since the message of the regex crate quotes
+/// the pattern.
+#[cfg(feature = "regex")]
+pub(crate) fn regex(value: &str) -> Result<String, String> {
+    match regex::Regex::new(value) {
+        Ok(_) => Ok(value.to_string()),
+        Err(regex::Error::Syntax(message)) => {
+            let kind = message
+                .lines()
+                .rev()
+                .find_map(|l| l.strip_prefix("error: "));
+            match kind {
+                Some(kind) => Err(format!("not a valid regular expression: {}", kind)),
+                None => Err("not a valid regular expression".to_string()),
+            }
+        }
+        Err(_) => Err("regular expression is too large".to_string()),
+    }
+}
+
+/// Checks that a value is JSON which deserializes into `T`.
+///
+/// The reason only gives the kind of error and where it is, since the message of serde_json
+/// quotes parts of the value.
+#[cfg(feature = "serde_json")]
+pub(crate) fn json<T: serde::de::DeserializeOwned>(value: &str) -> Result<String, String> {
+    use serde_json::error::Category;
+
+    match serde_json::from_str::<T>(value) {
+        Ok(_) => Ok(value.to_string()),
+        Err(e) => {
+            let kind = match e.classify() {
+                Category::Io => "failed to read",
+                Category::Syntax => "syntax error",
+                Category::Data => "unexpected data",
+                Category::Eof => "unexpected end of input",
+            };
+            Err(format!(
+                "not valid JSON: {} at line {} column {}",
+                kind,
+                e.line(),
+                e.column()
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn paths() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let file = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        let missing = concat!(env!("CARGO_MANIFEST_DIR"), "/missing");
+
+        assert_eq!(path_exists(file), Ok(file.to_string()));
+        assert_eq!(path_exists(missing), Err("path does not exist".to_string()));
+        assert_eq!(path_is_dir(dir), Ok(dir.to_string()));
+        assert_eq!(
+            path_is_dir(file),
+            Err("path is not a directory".to_string())
+        );
+        assert_eq!(path_is_file(file), Ok(file.to_string()));
+        assert_eq!(path_is_file(dir), Err("path is not a file".to_string()));
+    }
+
+    #[test]
+    fn expansion() {
+        let env = |name: &str| match name {
+            "YAFP_TEST_EXPAND" => Some("app".to_string()),
+            "HOME" => Some("/home/user".to_string()),
+            _ => None,
+        };
+
+        assert_eq!(
+            expand_path("~/.config/$YAFP_TEST_EXPAND/${YAFP_TEST_EXPAND}.toml", &env),
+            Ok("/home/user/.config/app/app.toml".to_string())
+        );
+        assert_eq!(expand_path("a~/$/b", &env), Ok("a~/$/b".to_string()));
+        assert_eq!(
+            expand_path("$YAFP_TEST_UNSET", &env),
+            Err("environment variable 'YAFP_TEST_UNSET' is not set".to_string())
+        );
+    }
+
+    #[test]
+    fn addresses() {
+        assert_eq!(ip_addr("::1"), Ok("::1".to_string()));
+        assert!(ip_addr("localhost").is_err());
+        assert_eq!(
+            socket_addr("0.0.0.0", Some(8080)),
+            Ok("0.0.0.0:8080".to_string())
+        );
+        assert_eq!(socket_addr("::1", Some(80)), Ok("[::1]:80".to_string()));
+        assert_eq!(
+            socket_addr("10.0.0.1:443", Some(8080)),
+            Ok("10.0.0.1:443".to_string())
+        );
+        assert_eq!(
+            socket_addr("10.0.0.1", None),
+            Err("address is missing a port".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "url")]
+    fn urls() {
+        let https = vec!["https".to_string()];
+        assert!(url("https://example.com/path", &https).is_ok());
+        assert_eq!(
+            url("http://example.com", &https),
+            Err("URL scheme 'http' is not allowed, expected one of: https".to_string())
+        );
+        assert_eq!(
+            url("mailto:someone", &[]),
+            Err("URL is missing a host".to_string())
+        );
+        assert_eq!(
+            url("example.com", &[]),
+            Err("not a valid URL: relative URL without a base".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "semver")]
+    fn versions() {
+        assert_eq!(semver("1.2.3-rc.1"), Ok("1.2.3-rc.1".to_string()));
+        assert!(semver("1.2").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn dates() {
+        assert_eq!(
+            datetime("2024-01-31T10:00:00+01:00"),
+            Ok("2024-01-31T10:00:00+01:00".to_string())
+        );
+        assert_eq!(
+            datetime("2024-01-31"),
+            Ok("2024-01-31T00:00:00Z".to_string())
+        );
+        assert!(datetime("2024-02-30").is_err());
+        assert_eq!(date("2024-01-31"), Ok("2024-01-31".to_string()));
+        assert!(date("31/01/2024").is_err());
+    }
+
+    #[test]
+    fn booleans() {
+        assert_eq!(boolean("YES"), Ok("true".to_string()));
+        assert_eq!(boolean("1"), Ok("true".to_string()));
+        assert_eq!(boolean("Off"), Ok("false".to_string()));
+        assert!(boolean("2").is_err());
+        assert!(boolean("").is_err());
+    }
+
+    #[test]
+    fn trailing_slashes() {
+        assert_eq!(
+            strip_trailing_slash("https://example.com/"),
+            "https://example.com"
+        );
+        assert_eq!(strip_trailing_slash("/var/log//"), "/var/log");
+        assert_eq!(strip_trailing_slash("/"), "/");
+        assert_eq!(strip_trailing_slash(""), "");
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(in_range::<u8, _>("5", &(1..=10)), Ok("5".to_string()));
+        assert_eq!(
+            in_range::<u8, _>("11", &(1..=10)),
+            Err("expected a value between 1 and 10".to_string())
+        );
+        assert_eq!(
+            in_range::<f64, _>("0", &(0.5..)),
+            Err("expected a value at least 0.5".to_string())
+        );
+        assert_eq!(
+            in_range::<i32, _>("x", &(..3)),
+            Err("not a valid value".to_string())
+        );
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(integer("0xff"), Ok("255".to_string()));
+        assert_eq!(integer("0o755"), Ok("493".to_string()));
+        assert_eq!(integer("0b1010"), Ok("10".to_string()));
+        assert_eq!(integer("-0x10"), Ok("-16".to_string()));
+        assert_eq!(integer("42"), Ok("42".to_string()));
+        assert_eq!(integer("0x"), Err("not a valid integer".to_string()));
+        assert_eq!(integer("--1"), Err("not a valid integer".to_string()));
+        assert_eq!(integer("0b12"), Err("not a valid integer".to_string()));
+    }
+
+    #[test]
+    fn localized_numbers() {
+        let comma = |v| localized_number(v, DecimalMark::Comma);
+        let point = |v| localized_number(v, DecimalMark::Point);
+        assert_eq!(comma("1.234,56"), Ok("1234.56".to_string()));
+        assert_eq!(comma("-1 234 567"), Ok("-1234567".to_string()));
+        assert_eq!(point("1,000,000.5"), Ok("1000000.5".to_string()));
+        assert_eq!(point("1_000_000"), Ok("1000000".to_string()));
+        assert_eq!(point("1e5"), Ok("1e5".to_string()));
+        assert_eq!(
+            comma("1.5"),
+            Err("digits must be grouped by three".to_string())
+        );
+        assert_eq!(
+            point("1.000,5"),
+            Err("only the integer part of a number may be grouped".to_string())
+        );
+    }
+
+    #[test]
+    fn byte_sizes() {
+        assert_eq!(byte_size("512", SizeUnits::Si), Ok("512".to_string()));
+        assert_eq!(byte_size("4k", SizeUnits::Si), Ok("4000".to_string()));
+        assert_eq!(byte_size("4k", SizeUnits::Iec), Ok("4096".to_string()));
+        assert_eq!(
+            byte_size("10MiB", SizeUnits::Si),
+            Ok("10485760".to_string())
+        );
+        assert_eq!(
+            byte_size("2 GB", SizeUnits::Si),
+            Ok("2000000000".to_string())
+        );
+        assert_eq!(
+            byte_size("4x", SizeUnits::Si),
+            Err("byte size has an unknown unit".to_string())
+        );
+        assert_eq!(
+            byte_size("k", SizeUnits::Si),
+            Err("not a valid byte size".to_string())
+        );
+        assert_eq!(
+            byte_size("20EiB", SizeUnits::Si),
+            Err("byte size is too large".to_string())
+        );
+    }
+
+    #[test]
+    fn bit_names() {
+        let flags = vec![("read".to_string(), 1), ("write".to_string(), 2)];
+        assert_eq!(bitflags("read|write", &flags), Ok("3".to_string()));
+        assert_eq!(bitflags("write, write", &flags), Ok("2".to_string()));
+        assert_eq!(
+            bitflags("read|exec", &flags),
+            Err("unknown name, expected any of: read, write".to_string())
+        );
+        assert_eq!(
+            bitflags("read|", &flags),
+            Err("empty name in list".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn regexes() {
+        assert_eq!(regex("^a+$"), Ok("^a+$".to_string()));
+        assert_eq!(
+            regex("secret(").unwrap_err(),
+            "not a valid regular expression: unclosed group"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    fn json_values() {
+        assert!(json::<serde_json::Value>(r#"{"a": [1, 2]}"#).is_ok());
+        assert_eq!(
+            json::<serde_json::Value>("{"),
+            Err("not valid JSON: unexpected end of input at line 1 column 1".to_string())
+        );
+        assert_eq!(
+            json::<Vec<u32>>(r#"{"a": 1}"#),
+            Err("not valid JSON: unexpected data at line 1 column 0".to_string())
+        );
+        assert_eq!(
+            json::<Vec<u32>>(r#""hunter2-secret""#),
+            Err("not valid JSON: unexpected data at line 1 column 16".to_string())
+        );
+    }
+
+    #[test]
+    fn base64_values() {
+        assert_eq!(base64("aGVsbG8="), Ok(b"hello".to_vec()));
+        assert_eq!(base64("aGVsbG8"), Ok(b"hello".to_vec()));
+        assert_eq!(base64("__8-"), Ok(vec![0xff, 0xff, 0x3e]));
+        assert_eq!(base64(""), Ok(Vec::new()));
+        assert_eq!(
+            base64("aGVsbG8*"),
+            Err("not valid base64: invalid character".to_string())
+        );
+        assert_eq!(
+            base64("a"),
+            Err("not valid base64: invalid length".to_string())
+        );
+    }
+}
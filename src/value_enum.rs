@@ -0,0 +1,111 @@
+//! Allows Rust enums to be used as flag values, see [`crate::value_enum!`].
+
+/// A type with a fixed set of named values, usually implemented with [`crate::value_enum!`].
+pub trait ValueEnum: std::str::FromStr {
+    /// The names of every value, in declaration order.
+    const VARIANTS: &'static [&'static str];
+
+    /// Returns the name of the value.
+    fn as_str(&self) -> &'static str;
+}
+
+/// Defines an enum usable as a flag value, where each variant is given the name used on the command
+/// line.
+///
+/// The enum implements [`crate::ValueEnum`], [`std::str::FromStr`] and [`std::fmt::Display`] so it
+/// can be declared with [`crate::FlagBuilder::value_enum`] and read with `get_value`.
+///
+/// # Examples
+///
+/// ```
+/// use yafp::{value_enum, Parser};
+/// use yafp::errors::Error;
+///
+/// value_enum! {
+///     /// The log level.
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     pub enum Level {
+///         Debug => "debug",
+///         Info => "info",
+///         Error => "error",
+///     }
+/// }
+///
+/// let mut parser = Parser::from_iter(["serve", "-level", "debug"]);
+/// parser
+///     .optional_flag("level", "this is used to set the log level")
+///     .value_enum::<Level>();
+///
+/// parser.finalize()?;
+/// assert_eq!(Some(Level::Debug), parser.get_value::<Level>("level"));
+/// # Ok::<(), Error>(())
+/// ```
+#[macro_export]
+macro_rules! value_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $value:literal),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant),+
+        }
+
+        impl $crate::ValueEnum for $name {
+            const VARIANTS: &'static [&'static str] = &[$($value),+];
+
+            fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $value),+
+                }
+            }
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = ::std::string::String;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                match s {
+                    $($value => ::std::result::Result::Ok($name::$variant),)+
+                    _ => ::std::result::Result::Err(::std::format!(
+                        "expected one of: {}",
+                        <$name as $crate::ValueEnum>::VARIANTS.join(", ")
+                    )),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str($crate::ValueEnum::as_str(self))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    value_enum! {
+        #[derive(Debug, PartialEq)]
+        enum Mode {
+            Fast => "fast",
+            Safe => "safe",
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(Mode::VARIANTS, &["fast", "safe"]);
+        assert_eq!("safe".parse::<Mode>(), Ok(Mode::Safe));
+        assert_eq!(Mode::Fast.to_string(), "fast");
+        assert_eq!(
+            "slow".parse::<Mode>(),
+            Err("expected one of: fast, safe".to_string())
+        );
+    }
+}
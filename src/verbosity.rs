@@ -0,0 +1,125 @@
+//! Combines `-v` and `-quiet` into a log level, see [`crate::Parser::verbosity_flags`].
+use crate::Parser;
+
+/// Name of the flag which raises the verbosity each time it is given.
+pub(crate) const VERBOSE_FLAG: &str = "v";
+
+/// Name of the flag which lowers the verbosity each time it is given.
+pub(crate) const QUIET_FLAG: &str = "quiet";
+
+/// A log level computed from the verbosity flags, from the quietest to the most verbose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Verbosity {
+    /// No logging.
+    Off,
+    /// Errors only.
+    Error,
+    /// Warnings and errors.
+    Warn,
+    /// Informational messages and above.
+    Info,
+    /// Debug messages and above.
+    Debug,
+    /// Everything.
+    Trace,
+}
+
+impl Verbosity {
+    const LEVELS: [Verbosity; 6] = [
+        Verbosity::Off,
+        Verbosity::Error,
+        Verbosity::Warn,
+        Verbosity::Info,
+        Verbosity::Debug,
+        Verbosity::Trace,
+    ];
+
+    /// Returns the level a number of steps above this one, or below it for negative steps,
+    /// stopping at [`Verbosity::Off`] and [`Verbosity::Trace`].
+    pub fn offset(self, steps: isize) -> Self {
+        let level = (self as isize + steps).clamp(0, Self::LEVELS.len() as isize - 1);
+        Self::LEVELS[level as usize]
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<Verbosity> for log::LevelFilter {
+    fn from(verbosity: Verbosity) -> Self {
+        match verbosity {
+            Verbosity::Off => log::LevelFilter::Off,
+            Verbosity::Error => log::LevelFilter::Error,
+            Verbosity::Warn => log::LevelFilter::Warn,
+            Verbosity::Info => log::LevelFilter::Info,
+            Verbosity::Debug => log::LevelFilter::Debug,
+            Verbosity::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+impl Parser<'_> {
+    /// Declares the `-v` and `-quiet` count flags, read together with
+    /// [`crate::Parser::verbosity`].
+    pub fn verbosity_flags(&mut self) {
+        self.count_flag(VERBOSE_FLAG, "this is used to get more verbose output");
+        self.count_flag(QUIET_FLAG, "this is used to get less output");
+    }
+
+    /// Returns the level given by the verbosity flags, see [`crate::Parser::verbosity_flags`],
+    /// starting at a default level.
+    ///
+    /// Each `-v` raises the level and each `-quiet` lowers it by one step, so they cancel each
+    /// other out when both are given, and the level stops at [`Verbosity::Off`] and
+    /// [`Verbosity::Trace`]. With the `log` feature the level converts into a
+    /// `log::LevelFilter`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::errors::Error;
+    /// use yafp::verbosity::Verbosity;
+    ///
+    /// let mut parser = Parser::from_iter(["tool", "-v", "-v"]);
+    /// parser.verbosity_flags();
+    ///
+    /// parser.finalize()?;
+    /// assert_eq!(Verbosity::Debug, parser.verbosity(Verbosity::Warn));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn verbosity(&self, default: Verbosity) -> Verbosity {
+        let count = |flag| self.get_value::<isize>(flag).unwrap_or(0);
+        default.offset(count(VERBOSE_FLAG) - count(QUIET_FLAG))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn verbosity(args: &[&str]) -> Verbosity {
+        let mut parser = Parser::from_iter(["tool"].iter().chain(args).copied());
+        parser.verbosity_flags();
+        parser.finalize().unwrap();
+        parser.verbosity(Verbosity::Warn)
+    }
+
+    #[test]
+    fn levels() {
+        assert_eq!(verbosity(&[]), Verbosity::Warn);
+        assert_eq!(verbosity(&["-v"]), Verbosity::Info);
+        assert_eq!(verbosity(&["-v", "-v", "-v", "-v"]), Verbosity::Trace);
+        assert_eq!(verbosity(&["-quiet"]), Verbosity::Error);
+        assert_eq!(verbosity(&["-quiet", "-quiet", "-quiet"]), Verbosity::Off);
+        assert_eq!(verbosity(&["-v", "-quiet"]), Verbosity::Warn);
+    }
+
+    #[test]
+    #[cfg(feature = "log")]
+    fn level_filter() {
+        assert_eq!(
+            log::LevelFilter::from(Verbosity::Debug),
+            log::LevelFilter::Debug
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Walks the user through the declared flags to build a command line, see
+//! [`crate::Parser::wizard`].
+use std::io::{self, BufRead, Write};
+
+use crate::token::Token;
+use crate::{value, Flag, FlagEntry, Parser, REDACTED};
+
+impl Parser<'_> {
+    /// Asks for the value of every declared flag and then every declared positional in turn,
+    /// and returns the equivalent command line, starting with the command.
+    ///
+    /// Each question shows the usage text, the choices and the default. Answers are checked like
+    /// values on the command line, and asked again with the reason when they are invalid. An
+    /// empty answer leaves a flag unset, unless it is required. Boolean flags take `yes` or
+    /// `no`, counted flags the number of times they are given, and flags taking multiple values
+    /// are asked again until the answer is empty. The command line can be printed with
+    /// [`crate::split::join`].
+    ///
+    /// Answers are echoed as typed, so sensitive flags and positionals are not asked and left out
+    /// of the command line, with a note telling the user to give them separately, along with
+    /// the positionals after a sensitive one. A positional answer which would be read as a flag
+    /// is asked again, unless it goes to the trailing positional, which is then preceded by
+    /// `--`, see [`crate::Parser::trailing_positional`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading or writing fails, of kind [`std::io::ErrorKind::UnexpectedEof`]
+    /// if the input ends before every question is answered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use yafp::Parser;
+    /// use yafp::split::join;
+    ///
+    /// let mut parser = Parser::from_iter(["head"]);
+    /// parser.required_flag("num", "this is used to set the number of lines").integer();
+    /// parser.bool_flag("verbose", "this is used to get verbose output");
+    /// parser.positional("file", "the file to read");
+    ///
+    /// let mut input = "ten\n10\nyes\nnotes.txt\n".as_bytes();
+    /// let mut output = Vec::new();
+    /// let args = parser.wizard(&mut input, &mut output).unwrap();
+    ///
+    /// assert_eq!("head -num 10 -verbose notes.txt", join(args));
+    /// assert_eq!(
+    ///     String::from_utf8(output).unwrap(),
+    ///     [
+    ///         "-num: this is used to set the number of lines (required)\n> ",
+    ///         "invalid value: not a valid integer\n",
+    ///         "-num: this is used to set the number of lines (required)\n> ",
+    ///         "-verbose: this is used to get verbose output [yes/no]\n> ",
+    ///         "file: the file to read (required)\n> ",
+    ///     ]
+    ///     .concat()
+    /// );
+    /// ```
+    pub fn wizard(
+        &self,
+        input: &mut impl BufRead,
+        output: &mut impl Write,
+    ) -> io::Result<Vec<String>> {
+        let mut args = vec![self.command.to_string()];
+        for (key, entry) in self.flags.iter() {
+            let flag = format!("-{}", key);
+            if entry.sensitive && matches!(entry.typ, Flag::Value) {
+                writeln!(output, "{}: {}", flag, SENSITIVE_NOTE)?;
+                continue;
+            }
+            match entry.typ {
+                Flag::Bool | Flag::TriState => {
+                    let boolean = |answer: &str| value::boolean(answer).map(|b| b == "true");
+                    match ask(input, output, &flag, entry, "[yes/no]", boolean)? {
+                        Some(true) => args.push(flag),
+                        Some(false) if matches!(entry.typ, Flag::TriState) => {
+                            args.push(format!("-no-{}", key))
+                        }
+                        _ => {}
+                    }
+                }
+                Flag::Count => {
+                    let count = |answer: &str| answer.parse::<usize>().map_err(|e| e.to_string());
+                    let count = ask(input, output, &flag, entry, "[times]", count)?;
+                    for _ in 0..count.unwrap_or(0) {
+                        args.push(flag.clone());
+                    }
+                }
+                Flag::Value => {
+                    let required = self.required.contains(key);
+                    for value in ask_values(input, output, &flag, entry, required, |_| Ok(()))? {
+                        args.push(flag.clone());
+                        args.push(value);
+                    }
+                }
+            }
+        }
+        let is_flag = |value: &str| matches!(self.classify(value), Token::Flag(_));
+        for (name, entry) in self.declared_positionals.iter() {
+            if entry.sensitive {
+                writeln!(
+                    output,
+                    "{}: {}, with the arguments after it",
+                    name, SENSITIVE_NOTE
+                )?;
+                break;
+            }
+            let required = entry.min_occurrences > 0;
+            let trailing = self.trailing.as_ref() == Some(name);
+            let not_flag = |answer: &str| match trailing || !is_flag(answer) {
+                true => Ok(()),
+                false => Err("the value would be read as a flag".to_string()),
+            };
+            let values = ask_values(input, output, name, entry, required, not_flag)?;
+            if trailing && values.iter().any(|value| is_flag(value)) {
+                args.push("--".to_string());
+            }
+            args.extend(values);
+        }
+        Ok(args)
+    }
+}
+
+/// Tells the user a sensitive flag or positional is not asked.
+const SENSITIVE_NOTE: &str = "left out since it is sensitive, give it separately";
+
+/// Asks a question until the answer is empty or parses, returning the parsed answer if any.
+fn ask<T>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    name: &str,
+    entry: &FlagEntry,
+    hint: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> io::Result<Option<T>> {
+    loop {
+        write!(output, "{}: {}", name, entry.usage)?;
+        if !hint.is_empty() {
+            write!(output, " {}", hint)?;
+        }
+        write!(output, "\n> ")?;
+        output.flush()?;
+
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the input ended before every question was answered",
+            ));
+        }
+        let answer = answer.trim_end_matches(['\r', '\n']);
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        match parse(answer) {
+            Ok(parsed) => return Ok(Some(parsed)),
+            Err(reason) => writeln!(output, "invalid value: {}", reason)?,
+        }
+    }
+}
+
+/// Asks for the values of a flag or positional, until one is given if it takes a single value
+/// or until the answer is empty, asking again while a required one has none. Answers are checked
+/// with `accept` before the checks of the entry.
+fn ask_values(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    name: &str,
+    entry: &FlagEntry,
+    required: bool,
+    accept: impl Fn(&str) -> Result<(), String>,
+) -> io::Result<Vec<String>> {
+    let mut hint = Vec::new();
+    if !entry.choices.is_empty() {
+        hint.push(format!("({})", entry.choices.join(", ")));
+    }
+    if let Some(default) = &entry.default {
+        let default = if entry.sensitive { REDACTED } else { default };
+        hint.push(format!("[default: {}]", default));
+    }
+    if required {
+        hint.push("(required)".to_string());
+    }
+    let hint = hint.join(" ");
+
+    let mut values = Vec::new();
+    loop {
+        let check = |answer: &str| {
+            accept(answer)?;
+            entry.check(answer).map(|_| answer.to_string())
+        };
+        match ask(input, output, name, entry, &hint, check)? {
+            Some(value) => values.push(value),
+            None if required && values.is_empty() => {
+                writeln!(output, "a value is required")?;
+                continue;
+            }
+            None => return Ok(values),
+        }
+        if !entry.multiple {
+            return Ok(values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn wizard() {
+        let mut parser = Parser::from_iter(["serve"]);
+        parser
+            .optional_flag("level", "this is used to set the log level")
+            .choices(&["debug", "info"])
+            .default_value("info");
+        parser.tristate_flag("color", "this is used to color output");
+        parser.count_flag("v", "this is used to get more verbose output");
+        parser
+            .optional_flag("tag", "this is used to add a tag")
+            .multiple();
+        parser.positional("dir", "the directory to serve");
+
+        let mut input = "trace\ndebug\nno\n2\na\nb c\n\n\n.\n".as_bytes();
+        let mut output = Vec::new();
+        let args = parser.wizard(&mut input, &mut output).unwrap();
+        assert_eq!(
+            args,
+            [
+                "serve",
+                "-level",
+                "debug",
+                "-no-color",
+                "-v",
+                "-v",
+                "-tag",
+                "a",
+                "-tag",
+                "b c",
+                "."
+            ]
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with(
+            "-level: this is used to set the log level (debug, info) [default: info]\n> \
+             invalid value: "
+        ));
+        assert!(output.ends_with("dir: the directory to serve (required)\n> a value is required\ndir: the directory to serve (required)\n> "));
+
+        let mut parser = Parser::from_iter(["ssh"]);
+        parser
+            .optional_flag("password", "this is used to authenticate")
+            .sensitive();
+        parser.positional("host", "the host to connect to");
+        parser.trailing_positional("command", "the command to run");
+        let mut input = "-v\nexample.com\nls\n\n".as_bytes();
+        let mut output = Vec::new();
+        let args = parser.wizard(&mut input, &mut output).unwrap();
+        assert_eq!(args, ["ssh", "example.com", "ls"]);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with(
+            "-password: left out since it is sensitive, give it separately\n\
+             host: the host to connect to (required)\n> \
+             invalid value: the value would be read as a flag\n"
+        ));
+
+        let mut parser = Parser::from_iter(["ssh"]);
+        parser.positional("host", "the host to connect to");
+        parser.trailing_positional("command", "the command to run");
+        let mut input = "example.com\n-la\n\n".as_bytes();
+        let args = parser.wizard(&mut input, &mut Vec::new()).unwrap();
+        assert_eq!(args, ["ssh", "example.com", "--", "-la"]);
+
+        let mut parser = Parser::from_iter(["serve"]);
+        parser.positional("dir", "the directory to serve");
+        let error = parser.wizard(&mut "".as_bytes(), &mut Vec::new());
+        assert_eq!(error.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+}